@@ -0,0 +1,393 @@
+#![allow(clippy::needless_doctest_main)]
+#![recursion_limit = "256"]
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, Lit, Meta, NestedMeta};
+
+#[derive(Debug)]
+struct VersionItem {
+    ident: Ident,
+    version: String,
+    image: Option<String>,
+    default: bool,
+}
+
+///
+/// Derive everything a versioned enum needs from a table of variants.
+///
+/// Each variant is given its version, and optionally an image. Images can
+/// also be set once for the whole enum, where `{}` is replaced by the version.
+///
+/// ```
+/// use version_enum::VersionEnum;
+///
+/// #[derive(Debug, Clone, PartialEq, VersionEnum)]
+/// #[version(image = "redis:{}-alpine")]
+/// pub enum Redis {
+///     #[version("5.0")]
+///     Five,
+///     #[version("6.2", default)]
+///     SixTwo,
+///     #[version("7.2", image = "redis:7.2.4-alpine")]
+///     SevenTwo,
+/// }
+///
+/// fn main() {
+///     assert_eq!(Redis::default(), Redis::SixTwo);
+///     assert_eq!(Redis::versions(), vec!["5.0", "6.2", "7.2"]);
+///     assert_eq!(Redis::SixTwo.image(), "redis:6.2-alpine");
+///     assert_eq!(Redis::SevenTwo.image(), "redis:7.2.4-alpine");
+///     assert_eq!(Redis::SevenTwo.to_string(), "7.2");
+///     assert_eq!("5.0".parse::<Redis>(), Ok(Redis::Five));
+///     assert_eq!(Redis::expected(), "`5.0`, `6.2` or `7.2`");
+/// }
+/// ```
+///
+/// The following are generated:
+///
+/// - `ALL`, `version()`, `versions()`, `from_version()` & `expected()`
+/// - `image()`, when any images are given
+/// - `Default`, when a variant is marked with `default`
+/// - `Display`, `FromStr`, `Serialize` & `Deserialize`
+///
+/// When deserializing, strings, floats & integers are all accepted,
+/// so `5`, `5.0` & `"5.0"` are all the same version.
+///
+#[proc_macro_derive(VersionEnum, attributes(version))]
+pub fn version_enum(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => TokenStream::from(tokens),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &input.ident;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "VersionEnum can only be derived for enums",
+            ))
+        }
+    };
+
+    let template = enum_image(&input.attrs)?;
+    let mut items: Vec<VersionItem> = vec![];
+
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "VersionEnum variants cannot have fields",
+            ));
+        }
+        let item = version_item(variant.ident.clone(), &variant.attrs)?;
+        if items.iter().any(|prev| prev.version == item.version) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("version `{}` is used more than once", item.version),
+            ));
+        }
+        items.push(item);
+    }
+
+    // `quote` can't mix `#name` into a repetition, so build each path up front
+    let paths = items
+        .iter()
+        .map(|item| {
+            let ident = &item.ident;
+            quote! { #name::#ident }
+        })
+        .collect::<Vec<proc_macro2::TokenStream>>();
+    let versions = items
+        .iter()
+        .map(|item| item.version.as_str())
+        .collect::<Vec<&str>>();
+    let expected = expected(&versions);
+
+    let image_fn = image_fn(&items, &template)?;
+    let default_impl = default_impl(name, &items)?;
+
+    // `quote` needs separate iterators for each repetition
+    let paths_2 = paths.clone();
+    let paths_3 = paths.clone();
+    let versions_2 = versions.clone();
+    let versions_3 = versions.clone();
+
+    Ok(quote! {
+        impl #name {
+            pub const ALL: &'static [#name] = &[#(#paths,)*];
+
+            pub fn version(&self) -> &'static str {
+                match self {
+                    #(#paths_2 => #versions,)*
+                }
+            }
+
+            pub fn versions() -> Vec<&'static str> {
+                vec![#(#versions_2,)*]
+            }
+
+            pub fn from_version(version: &str) -> Option<#name> {
+                match version {
+                    #(#versions_3 => Some(#paths_3),)*
+                    _ => None,
+                }
+            }
+
+            ///
+            /// Every supported version, ready for error messages
+            ///
+            pub fn expected() -> String {
+                String::from(#expected)
+            }
+
+            #image_fn
+        }
+
+        #default_impl
+
+        impl ::std::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}", self.version())
+            }
+        }
+
+        impl ::std::str::FromStr for #name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                #name::from_version(s).ok_or_else(|| format!("expected either {}", #expected))
+            }
+        }
+
+        impl ::serde::Serialize for #name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer,
+            {
+                serializer.serialize_str(self.version())
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for #name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                struct VersionVisitor;
+
+                impl<'de> ::serde::de::Visitor<'de> for VersionVisitor {
+                    type Value = #name;
+
+                    fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(f, "either {}", #expected)
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                    where
+                        E: ::serde::de::Error,
+                    {
+                        v.parse::<#name>().map_err(E::custom)
+                    }
+
+                    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+                    where
+                        E: ::serde::de::Error,
+                    {
+                        // `{:?}` keeps the trailing zero, so `8.0` is not read as `8`
+                        let exact = format!("{:?}", v);
+                        match #name::from_version(&exact) {
+                            Some(version) => Ok(version),
+                            None if v.fract() == 0.0 => self.visit_str(&format!("{}", v as i64)),
+                            None => self.visit_str(&exact),
+                        }
+                    }
+
+                    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+                    where
+                        E: ::serde::de::Error,
+                    {
+                        match #name::from_version(&v.to_string()) {
+                            Some(version) => Ok(version),
+                            None => self.visit_str(&format!("{}.0", v)),
+                        }
+                    }
+
+                    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+                    where
+                        E: ::serde::de::Error,
+                    {
+                        match #name::from_version(&v.to_string()) {
+                            Some(version) => Ok(version),
+                            None => self.visit_str(&format!("{}.0", v)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_any(VersionVisitor)
+            }
+        }
+    })
+}
+
+///
+/// All `#[version(...)]` entries, flattened
+///
+fn version_attrs(attrs: &[Attribute]) -> Result<Vec<NestedMeta>, syn::Error> {
+    let mut nested = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("version")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => nested.extend(list.nested),
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    r#"expected #[version("1.0")]"#,
+                ))
+            }
+        }
+    }
+    Ok(nested)
+}
+
+///
+/// `#[version(image = "redis:{}-alpine")]` on the enum itself
+///
+fn enum_image(attrs: &[Attribute]) -> Result<Option<String>, syn::Error> {
+    let mut image = None;
+    for nested in version_attrs(attrs)? {
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(ref nv)) if nv.ident == "image" => {
+                image = Some(lit_str(&nv.lit)?);
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    r#"expected #[version(image = "name:{}")]"#,
+                ))
+            }
+        }
+    }
+    Ok(image)
+}
+
+///
+/// `#[version("6.2", default, image = "redis:6.2-alpine")]` on a variant
+///
+fn version_item(ident: Ident, attrs: &[Attribute]) -> Result<VersionItem, syn::Error> {
+    let mut version = None;
+    let mut image = None;
+    let mut default = false;
+    for nested in version_attrs(attrs)? {
+        match nested {
+            NestedMeta::Literal(ref lit) => version = Some(lit_str(lit)?),
+            NestedMeta::Meta(Meta::Word(ref word)) if word == "default" => default = true,
+            NestedMeta::Meta(Meta::NameValue(ref nv)) if nv.ident == "image" => {
+                image = Some(lit_str(&nv.lit)?);
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    r#"expected a version, `default` or `image = "..."`"#,
+                ))
+            }
+        }
+    }
+    match version {
+        Some(version) => Ok(VersionItem {
+            ident,
+            version,
+            image,
+            default,
+        }),
+        None => Err(syn::Error::new_spanned(
+            &ident,
+            r#"missing version, eg: #[version("1.0")]"#,
+        )),
+    }
+}
+
+fn lit_str(lit: &Lit) -> Result<String, syn::Error> {
+    match lit {
+        Lit::Str(s) => Ok(s.value()),
+        other => Err(syn::Error::new_spanned(other, "expected a string")),
+    }
+}
+
+///
+/// eg: "`7.1`, `7.2` or `7.3`"
+///
+fn expected(versions: &[&str]) -> String {
+    let versions = versions
+        .iter()
+        .map(|version| format!("`{}`", version))
+        .collect::<Vec<String>>();
+    match versions.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => versions.join(""),
+    }
+}
+
+fn image_fn(
+    items: &[VersionItem],
+    template: &Option<String>,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if template.is_none() && items.iter().all(|item| item.image.is_none()) {
+        return Ok(quote! {});
+    }
+    let mut arms = vec![];
+    for item in items {
+        let image = match (&item.image, template) {
+            (Some(image), _) => image.clone(),
+            (None, Some(template)) => template.replace("{}", &item.version),
+            (None, None) => {
+                return Err(syn::Error::new_spanned(
+                    &item.ident,
+                    "missing image, either give every variant an image or set one on the enum",
+                ))
+            }
+        };
+        let ident = &item.ident;
+        arms.push(quote! { Self::#ident => String::from(#image), });
+    }
+    Ok(quote! {
+        pub fn image(&self) -> String {
+            match self {
+                #(#arms)*
+            }
+        }
+    })
+}
+
+fn default_impl(
+    name: &Ident,
+    items: &[VersionItem],
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let defaults = items
+        .iter()
+        .filter(|item| item.default)
+        .collect::<Vec<&VersionItem>>();
+    match defaults.as_slice() {
+        [] => Ok(quote! {}),
+        [item] => {
+            let ident = &item.ident;
+            Ok(quote! {
+                impl Default for #name {
+                    fn default() -> Self {
+                        #name::#ident
+                    }
+                }
+            })
+        }
+        [_, extra, ..] => Err(syn::Error::new_spanned(
+            &extra.ident,
+            "only one variant can be the default",
+        )),
+    }
+}
@@ -0,0 +1,46 @@
+use version_enum::VersionEnum;
+
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "wearejh/node:{}-m2")]
+pub enum Node {
+    #[version("8", default)]
+    Eight,
+    #[version("16")]
+    Sixteen,
+}
+
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+pub enum Es {
+    #[version("6.8")]
+    SixEight,
+    #[version("7.0")]
+    SevenZero,
+}
+
+#[test]
+fn test_deserialize() {
+    let from = |input: &str| serde_yaml::from_str::<Node>(input);
+    assert_eq!(from("16").expect("test"), Node::Sixteen);
+    assert_eq!(from("16.0").expect("test"), Node::Sixteen);
+    assert_eq!(from("\"8\"").expect("test"), Node::Eight);
+    assert!(from("14")
+        .expect_err("test")
+        .to_string()
+        .contains("expected either `8` or `16`"));
+
+    let from = |input: &str| serde_yaml::from_str::<Es>(input);
+    assert_eq!(from("6.8").expect("test"), Es::SixEight);
+    assert_eq!(from("7").expect("test"), Es::SevenZero);
+    assert_eq!(from("7.0").expect("test"), Es::SevenZero);
+    assert_eq!(from("\"7.0\"").expect("test"), Es::SevenZero);
+}
+
+#[test]
+fn test_serialize() {
+    assert_eq!(
+        serde_yaml::to_string(&Node::Sixteen).expect("test"),
+        "---\n\"16\""
+    );
+    assert_eq!(Node::default().image(), "wearejh/node:8-m2");
+    assert_eq!(Es::ALL, &[Es::SixEight, Es::SevenZero]);
+}
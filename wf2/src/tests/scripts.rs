@@ -184,6 +184,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wp_sh() {
+        let args = vec!["prog", "--config", "../fixtures/wp.yml", "hello-world"];
+        let cmds = _test(args);
+        assert_eq!(vec!["echo hello world!"], cmds);
+    }
+
+    #[test]
+    fn test_wp_dc_run_script() {
+        let args = vec!["prog", "--config", "../fixtures/wp.yml", "flush"];
+        let cmds = _test(args);
+        let expected = r#"docker-compose -f /users/acme/.wf2_wp_acme/docker-compose.yml run wp-cli wp cache flush"#;
+        assert_eq!(expected, cmds.get(0).expect("test"));
+    }
+
+    #[test]
+    fn test_wp_dc_pass_through() {
+        let args = vec!["prog", "--config", "../fixtures/wp.yml", "db-logs"];
+        let cmds = _test(args);
+        let expected = r#"docker-compose -f /users/acme/.wf2_wp_acme/docker-compose.yml logs db"#;
+        assert_eq!(expected, cmds.get(0).expect("test"));
+    }
+
+    #[test]
+    fn test_wp_doesnt_allow_invalid_service_names() {
+        let args = vec!["prog", "--config", "../fixtures/wp.yml", "invalid"];
+        let cli_output = CLIOutput::from_input(CLIInput {
+            args: args.into_iter().map(String::from).collect(),
+            cwd: PathBuf::from("/users/acme"),
+            ..CLIInput::default()
+        })
+        .unwrap();
+        use wf2_core::task::Task;
+
+        match cli_output.tasks.unwrap().get(0).unwrap() {
+            Task::NotifyError { .. } => { /* yay! */ }
+            _output => unreachable!(),
+        }
+    }
+
     fn _test(args: Vec<&str>) -> Vec<String> {
         let cli_output = CLIOutput::from_input(CLIInput {
             args: args.into_iter().map(String::from).collect(),
@@ -2,6 +2,7 @@
 //! Global commands that can be run independent of any project
 //!
 //! - [env](env/index.html)
+//! - [proxy](proxy/index.html)
 //! - [timelog](timelog/index.html)
 //! - [self-update](self_update/index.html)
 //! - [m2-playground](../recipes/m2/subcommands/m2_playground_cmd/index.html)
@@ -11,6 +12,7 @@
 //! in the [recipes section](../recipes/index.html)
 //!
 use crate::commands::env::EnvCmd;
+use crate::commands::proxy::ProxyCmd;
 use crate::commands::timelog::TimelogCmd;
 use crate::context::Context;
 use crate::task::Task;
@@ -18,6 +20,7 @@ use clap::{App, ArgMatches};
 use self_update::SelfUpdate;
 
 pub mod env;
+pub mod proxy;
 pub mod self_update;
 pub mod timelog;
 
@@ -48,5 +51,6 @@ pub fn internal_commands<'a, 'b>() -> Vec<Box<dyn CliCommand<'a, 'b>>> {
         Box::new(TimelogCmd::new()),
         Box::new(SelfUpdate::new()),
         Box::new(EnvCmd),
+        Box::new(ProxyCmd),
     ]
 }
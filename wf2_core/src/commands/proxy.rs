@@ -0,0 +1,81 @@
+//!
+//! # `proxy`
+//!
+//! Start or stop the shared Traefik + MailHog stack, used by any
+//! project with the following in its `wf2.yml`
+//!
+//! ```yaml
+//! options:
+//!   proxy:
+//!     shared: true
+//! ```
+//!
+//! Projects using the proxy don't start their own Traefik, so any number
+//! of them can run at the same time, each routed by domain.
+//!
+//! ### `wf2 proxy up`
+//!
+//! ```rust
+//! # use wf2_core::test::Test;
+//! # let cmd = r#"
+//! wf2 proxy up
+//! # "#;
+//! # let commands = Test::from_cmd(cmd).commands();
+//! # assert_eq!(commands.len(), 2);
+//! # assert!(commands[1].ends_with("docker-compose.yml up -d"));
+//! ```
+//!
+//! ### `wf2 proxy down`
+//!
+//! ```rust
+//! # use wf2_core::test::Test;
+//! # let cmd = r#"
+//! wf2 proxy down
+//! # "#;
+//! # let commands = Test::from_cmd(cmd).commands();
+//! # assert!(commands[0].ends_with("docker-compose.yml down"));
+//! ```
+//!
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::proxy::SharedProxy;
+use crate::task::Task;
+use clap::{App, ArgMatches, SubCommand};
+
+#[doc_link::doc_link("/commands/proxy")]
+pub struct ProxyCmd;
+
+impl ProxyCmd {
+    const NAME: &'static str = "proxy";
+    const ABOUT: &'static str =
+        "Start or stop the shared proxy, used by projects with `proxy.shared: true`";
+
+    const UP: &'static str = "up";
+    const DOWN: &'static str = "down";
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for ProxyCmd {
+    fn name(&self) -> String {
+        String::from(ProxyCmd::NAME)
+    }
+    fn exec(&self, matches: Option<&ArgMatches>, _ctx: &Context) -> Option<Vec<Task>> {
+        let proxy = match SharedProxy::from_home() {
+            Ok(proxy) => proxy,
+            Err(e) => return Some(Task::task_err_vec(e)),
+        };
+        match matches.and_then(|m| m.subcommand_name()) {
+            Some(ProxyCmd::UP) => Some(proxy.up_tasks()),
+            Some(ProxyCmd::DOWN) => Some(proxy.down_tasks()),
+            _ => Some(vec![Task::notify_error("missing `up` or `down`")]),
+        }
+    }
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(ProxyCmd::NAME)
+            .about(ProxyCmd::ABOUT)
+            .after_help(ProxyCmd::DOC_LINK)
+            .subcommands(vec![
+                SubCommand::with_name(ProxyCmd::UP).about("Start the shared proxy"),
+                SubCommand::with_name(ProxyCmd::DOWN).about("Stop the shared proxy"),
+            ])]
+    }
+}
@@ -7,7 +7,10 @@ use from_file::{FromFile, FromFileError};
 use serde_yaml::Value;
 use std::path::PathBuf;
 
+use crate::versions::composer::COMPOSER;
 use crate::versions::elasticsearch::ELASTICSEARCH;
+use crate::versions::node::NODE;
+use crate::versions::search_engine::SearchEngine;
 use serde::Deserialize;
 use std::{fmt, fs};
 
@@ -74,18 +77,20 @@ pub struct Context {
     #[serde(skip_serializing, default = "default_cwd")]
     pub npm_path: PathBuf,
 
-    #[serde(
-        skip_serializing,
-        default,
-        deserialize_with = "crate::php::deserialize_php"
-    )]
+    #[serde(skip_serializing, default)]
     pub php_version: PHP,
-    #[serde(
-        skip_serializing,
-        default,
-        deserialize_with = "crate::versions::elasticsearch::deserialize_elasticsearch"
-    )]
-    pub es_version: crate::versions::elasticsearch::ELASTICSEARCH,
+
+    #[serde(skip_serializing, default)]
+    pub es_version: ELASTICSEARCH,
+
+    #[serde(skip_serializing, default)]
+    pub search_engine: Option<SearchEngine>,
+
+    #[serde(skip_serializing, default)]
+    pub node_version: Option<NODE>,
+
+    #[serde(skip_serializing, default)]
+    pub composer_version: Option<COMPOSER>,
 
     #[serde(skip_serializing, default)]
     pub config_path: Option<PathBuf>,
@@ -111,6 +116,15 @@ pub struct Context {
     #[serde(skip_serializing, default = "default_id")]
     pub gid: u32,
 
+    ///
+    /// Where Xdebug connects back to, when set this skips
+    /// detecting it, see [`XdebugHost::detected`]
+    ///
+    /// [`XdebugHost::detected`]: ../recipes/m2/xdebug/struct.XdebugHost.html#method.detected
+    ///
+    #[serde(skip)]
+    pub xdebug_host: Option<String>,
+
     #[serde(skip_serializing, default)]
     pub scripts: Option<Scripts>,
 
@@ -169,18 +183,22 @@ impl Default for Context {
             recipe: None,
             cwd: default_cwd(),
             run_mode: default_run_mode(),
-            domains: default_domains(),
+            domains: vec![],
             term: default_term(),
             pv: None,
             npm_path: default_cwd(),
             php_version: PHP::SevenThree,
             es_version: ELASTICSEARCH::SevenSix,
+            search_engine: None,
+            node_version: None,
+            composer_version: None,
             config_path: None,
             config_env_path: None,
             overrides: None,
             debug: default_debug(),
             uid: 0,
             gid: 0,
+            xdebug_host: None,
             env: None,
             scripts: None,
             origin: None,
@@ -263,9 +281,6 @@ impl Context {
     }
 }
 
-fn default_domains() -> Vec<String> {
-    vec![DEFAULT_DOMAIN.into()]
-}
 fn default_file_prefix() -> PathBuf {
     PathBuf::from(format!(".{}", DEFAULT_NAME))
 }
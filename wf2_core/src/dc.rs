@@ -1,3 +1,6 @@
+use crate::dc_depends_on::DcDependsOnCondition;
+use crate::dc_network::DcNetwork;
+use crate::dc_secret::DcSecret;
 use crate::dc_service::DcService;
 use crate::dc_volume::DcVolume;
 use std::collections::BTreeMap;
@@ -10,10 +13,20 @@ pub struct Dc {
     pub version: String,
     pub volumes: Option<BTreeMap<String, DcVolume>>,
     pub services: Option<BTreeMap<String, DcService>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub networks: Option<BTreeMap<String, DcNetwork>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<BTreeMap<String, DcSecret>>,
 }
 
 impl Dc {
-    const VERSION: &'static str = "3.7";
+    ///
+    /// 3.8 is the last of the numbered formats - it's the minimum
+    /// needed for `deploy.resources` outside of swarm mode
+    ///
+    const VERSION: &'static str = "3.8";
 
     pub fn new() -> Dc {
         Dc {
@@ -37,6 +50,61 @@ impl Dc {
         self.services = Some(as_hashmap);
         self
     }
+    pub fn set_version(&mut self, version: impl Into<String>) -> &mut Dc {
+        self.version = version.into();
+        self
+    }
+    pub fn set_network(&mut self, name: impl Into<String>, network: DcNetwork) -> &mut Dc {
+        self.networks
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.into(), network);
+        self
+    }
+    pub fn set_secret(&mut self, name: impl Into<String>, secret: DcSecret) -> &mut Dc {
+        self.secrets
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.into(), secret);
+        self
+    }
+    ///
+    /// Any `depends_on` entry that points to a service with a `healthcheck`
+    /// will wait for it to be healthy, rather than just started.
+    ///
+    /// This needs docker-compose 1.27.0 or newer, `wf2 doctor` checks for it,
+    /// see [`compose_version`](../tasks/compose_version/index.html)
+    ///
+    pub fn healthy_depends_on(&mut self) -> &mut Dc {
+        let with_healthcheck = self.services_with_healthcheck();
+        if let Some(services) = self.services.as_mut() {
+            for service in services.values_mut() {
+                let depends_on = service.depends_on.as_ref().filter(|depends_on| {
+                    depends_on
+                        .names()
+                        .iter()
+                        .any(|name| with_healthcheck.contains(name))
+                });
+                if let Some(depends_on) = depends_on {
+                    service.depends_on = Some(depends_on.with_conditions(|name| {
+                        if with_healthcheck.iter().any(|h| h == name) {
+                            DcDependsOnCondition::HEALTHY
+                        } else {
+                            DcDependsOnCondition::STARTED
+                        }
+                    }));
+                }
+            }
+        }
+        self
+    }
+    pub fn services_with_healthcheck(&self) -> Vec<String> {
+        self.services.as_ref().map_or(vec![], |services| {
+            services
+                .iter()
+                .filter(|(_, service)| service.healthcheck.is_some())
+                .map(|(name, _)| name.to_string())
+                .collect()
+        })
+    }
     pub fn build(&self) -> Dc {
         Dc { ..self.clone() }
     }
@@ -48,12 +116,90 @@ impl Dc {
             .as_ref()
             .map(|services| services.iter().map(|(key, _)| key.into()).collect())
     }
+    ///
+    /// Services that are only built locally have no image to pull
+    ///
     pub fn service_img(&self) -> Vec<(String, String)> {
         self.services.as_ref().map_or(vec![], |services| {
             services
                 .iter()
+                .filter(|(_, service)| !service.image.is_empty())
                 .map(|(key, service)| (key.to_string(), service.image.clone()))
                 .collect()
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_depends_on::DcDependsOn;
+    use crate::dc_healthcheck::DcHealthcheck;
+
+    #[test]
+    fn test_dc_round_trip() {
+        let input = r#"
+            version: "3.8"
+            volumes:
+              db-data:
+                name: wf2__acme__db-data
+            services:
+              db:
+                container_name: wf2__acme__db
+                image: mysql:8.0
+                secrets:
+                  - composer_auth
+            networks:
+              proxy:
+                name: wf2_proxy
+                external: true
+            secrets:
+              composer_auth:
+                file: ./auth.json
+        "#;
+        let built = Dc::new()
+            .set_volumes(&[DcVolume::new("acme", "db-data")])
+            .set_services(&[DcService::new("acme", "db", "mysql:8.0")
+                .set_secrets(vec!["composer_auth"])
+                .finish()])
+            .set_network("proxy", DcNetwork::external("wf2_proxy"))
+            .set_secret("composer_auth", DcSecret::from_file("./auth.json"))
+            .build();
+
+        let output: serde_yaml::Value = serde_yaml::from_slice(&built.to_bytes()).expect("test");
+        let expected: serde_yaml::Value = serde_yaml::from_str(input).expect("test");
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_healthy_depends_on() {
+        let dc = Dc::new()
+            .set_services(&[
+                DcService::new("acme", "php", "php")
+                    .set_depends_on(vec!["db", "mail"])
+                    .finish(),
+                DcService::new("acme", "nginx", "nginx")
+                    .set_depends_on(vec!["php"])
+                    .finish(),
+                DcService::new("acme", "db", "mysql")
+                    .set_healthcheck(DcHealthcheck::cmd_shell("mysqladmin ping"))
+                    .finish(),
+                DcService::new("acme", "mail", "mail"),
+            ])
+            .healthy_depends_on()
+            .build();
+        let services = dc.services.expect("test");
+        let expected: DcDependsOn = serde_yaml::from_str(
+            r#"
+            db: { condition: service_healthy }
+            mail: { condition: service_started }
+            "#,
+        )
+        .expect("test");
+        assert_eq!(services["php"].depends_on, Some(expected));
+        assert_eq!(
+            services["nginx"].depends_on,
+            Some(DcDependsOn::from(vec!["php"]))
+        );
+    }
+}
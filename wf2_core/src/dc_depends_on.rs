@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+///
+/// `depends_on` can either be a plain list of service names, or a map
+/// that gives a condition for each one.
+///
+/// Services are always created with a plain list, the map form is only
+/// used once a dependency has a `healthcheck` to wait for, see [`Dc::healthy_depends_on`]
+///
+/// [`Dc::healthy_depends_on`]: ../dc/struct.Dc.html#method.healthy_depends_on
+///
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum DcDependsOn {
+    Names(Vec<String>),
+    Conditions(BTreeMap<String, DcDependsOnCondition>),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcDependsOnCondition {
+    pub condition: String,
+}
+
+impl DcDependsOnCondition {
+    pub const STARTED: &'static str = "service_started";
+    pub const HEALTHY: &'static str = "service_healthy";
+
+    pub fn new(condition: impl Into<String>) -> Self {
+        DcDependsOnCondition {
+            condition: condition.into(),
+        }
+    }
+}
+
+impl Default for DcDependsOn {
+    fn default() -> Self {
+        DcDependsOn::Names(vec![])
+    }
+}
+
+impl DcDependsOn {
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            DcDependsOn::Names(names) => names.clone(),
+            DcDependsOn::Conditions(conditions) => conditions.keys().cloned().collect(),
+        }
+    }
+    pub fn push(&mut self, name: impl Into<String>) {
+        match self {
+            DcDependsOn::Names(names) => names.push(name.into()),
+            DcDependsOn::Conditions(conditions) => {
+                conditions.insert(
+                    name.into(),
+                    DcDependsOnCondition::new(DcDependsOnCondition::STARTED),
+                );
+            }
+        }
+    }
+    pub fn retain(&mut self, f: impl Fn(&str) -> bool) {
+        match self {
+            DcDependsOn::Names(names) => names.retain(|name| f(name)),
+            DcDependsOn::Conditions(conditions) => {
+                let next = conditions
+                    .iter()
+                    .filter(|(name, _)| f(name))
+                    .map(|(name, condition)| (name.clone(), condition.clone()))
+                    .collect();
+                *conditions = next;
+            }
+        }
+    }
+    ///
+    /// Convert to the map form, using `condition` to decide
+    /// the condition for each dependency
+    ///
+    pub fn with_conditions(&self, condition: impl Fn(&str) -> &'static str) -> DcDependsOn {
+        DcDependsOn::Conditions(
+            self.names()
+                .into_iter()
+                .map(|name| {
+                    let c = DcDependsOnCondition::new(condition(&name));
+                    (name, c)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<T: Into<String>> From<Vec<T>> for DcDependsOn {
+    fn from(names: Vec<T>) -> Self {
+        DcDependsOn::Names(names.into_iter().map(|x| x.into()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depends_on_forms() {
+        let names: DcDependsOn = serde_yaml::from_str("[db, redis]").expect("test");
+        assert_eq!(names, DcDependsOn::from(vec!["db", "redis"]));
+
+        let conditions = names.with_conditions(|name| match name {
+            "db" => DcDependsOnCondition::HEALTHY,
+            _ => DcDependsOnCondition::STARTED,
+        });
+        let expected: DcDependsOn = serde_yaml::from_str(
+            r#"
+            db:
+              condition: service_healthy
+            redis:
+              condition: service_started
+            "#,
+        )
+        .expect("test");
+        assert_eq!(conditions, expected);
+        assert_eq!(conditions.names(), vec!["db", "redis"]);
+    }
+}
@@ -0,0 +1,64 @@
+///
+/// The `deploy` section of a service. Only `resources` is modelled,
+/// since that's the only part that applies outside of swarm mode.
+///
+/// `mem_limit` isn't valid in the 3.x file format that's generated,
+/// so memory limits are always given here instead.
+///
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcDeploy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<DcResources>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcResources {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<DcResourceSpec>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reservations: Option<DcResourceSpec>,
+}
+
+///
+/// `cpus` is kept as a string (eg: "0.5") to match the compose spec
+///
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcResourceSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+}
+
+impl DcDeploy {
+    pub fn memory_limit(memory: impl Into<String>) -> Self {
+        DcDeploy {
+            resources: Some(DcResources {
+                limits: Some(DcResourceSpec {
+                    memory: Some(memory.into()),
+                    ..DcResourceSpec::default()
+                }),
+                ..DcResources::default()
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dc_deploy() {
+        let actual = DcDeploy::memory_limit("2g");
+        let expected = r#"
+            resources:
+              limits:
+                memory: 2g
+        "#;
+        let expected: DcDeploy = serde_yaml::from_str(expected).expect("test yaml");
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,47 @@
+///
+/// `entrypoint` can either be a single string, which is run through a shell,
+/// or a list of arguments in the exec form
+///
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum DcEntrypoint {
+    Shell(String),
+    Exec(Vec<String>),
+}
+
+impl From<&str> for DcEntrypoint {
+    fn from(entrypoint: &str) -> Self {
+        DcEntrypoint::Shell(entrypoint.to_string())
+    }
+}
+
+impl From<String> for DcEntrypoint {
+    fn from(entrypoint: String) -> Self {
+        DcEntrypoint::Shell(entrypoint)
+    }
+}
+
+impl<T: Into<String>> From<Vec<T>> for DcEntrypoint {
+    fn from(args: Vec<T>) -> Self {
+        DcEntrypoint::Exec(args.into_iter().map(|x| x.into()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entrypoint_forms() {
+        let shell: DcEntrypoint = serde_yaml::from_str("/docker-entrypoint.sh").expect("test");
+        assert_eq!(shell, DcEntrypoint::from("/docker-entrypoint.sh"));
+
+        let exec: DcEntrypoint =
+            serde_yaml::from_str(r#"["sh", "-c", "exec php-fpm"]"#).expect("test");
+        assert_eq!(exec, DcEntrypoint::from(vec!["sh", "-c", "exec php-fpm"]));
+
+        let output = serde_yaml::to_string(&exec).expect("test");
+        let reparsed: DcEntrypoint = serde_yaml::from_str(&output).expect("test");
+        assert_eq!(reparsed, exec);
+    }
+}
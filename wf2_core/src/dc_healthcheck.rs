@@ -0,0 +1,71 @@
+///
+/// A service `healthcheck`, as used by `depends_on` conditions
+/// and anything waiting for a container to become ready
+///
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcHealthcheck {
+    pub test: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<String>,
+}
+
+impl DcHealthcheck {
+    ///
+    /// A check that's run via the container's shell, eg: `CMD-SHELL redis-cli ping`
+    ///
+    pub fn cmd_shell(cmd: impl Into<String>) -> Self {
+        DcHealthcheck {
+            test: vec![String::from("CMD-SHELL"), cmd.into()],
+            ..DcHealthcheck::default()
+        }
+    }
+    pub fn set_interval(&mut self, interval: impl Into<String>) -> &mut Self {
+        self.interval = Some(interval.into());
+        self
+    }
+    pub fn set_timeout(&mut self, timeout: impl Into<String>) -> &mut Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+    pub fn set_retries(&mut self, retries: u32) -> &mut Self {
+        self.retries = Some(retries);
+        self
+    }
+    pub fn set_start_period(&mut self, start_period: impl Into<String>) -> &mut Self {
+        self.start_period = Some(start_period.into());
+        self
+    }
+    pub fn finish(&self) -> DcHealthcheck {
+        DcHealthcheck { ..self.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dc_healthcheck() {
+        let actual = DcHealthcheck::cmd_shell("redis-cli ping")
+            .set_interval("10s")
+            .set_retries(5)
+            .finish();
+        let expected = r#"
+            test: ["CMD-SHELL", "redis-cli ping"]
+            interval: 10s
+            retries: 5
+        "#;
+        let expected: DcHealthcheck = serde_yaml::from_str(expected).expect("test yaml");
+        assert_eq!(actual, expected);
+    }
+}
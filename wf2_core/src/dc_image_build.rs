@@ -4,6 +4,12 @@ use std::path::PathBuf;
 #[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct DcImageBuild {
     pub context: PathBuf,
+    #[serde(default = "default_dockerfile")]
     pub dockerfile: PathBuf,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub args: BTreeMap<String, String>,
 }
+
+fn default_dockerfile() -> PathBuf {
+    PathBuf::from("Dockerfile")
+}
@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcLogging {
+    pub driver: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<BTreeMap<String, String>>,
+}
+
+impl DcLogging {
+    pub fn new(driver: impl Into<String>) -> Self {
+        DcLogging {
+            driver: driver.into(),
+            options: None,
+        }
+    }
+    pub fn set_option(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.options
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+    pub fn finish(&self) -> DcLogging {
+        DcLogging { ..self.clone() }
+    }
+}
@@ -0,0 +1,25 @@
+///
+/// A top-level network. Use `external` to join a network that
+/// was created outside of this project.
+///
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcNetwork {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external: Option<bool>,
+}
+
+impl DcNetwork {
+    pub fn external(name: impl Into<String>) -> Self {
+        DcNetwork {
+            name: Some(name.into()),
+            external: Some(true),
+            ..DcNetwork::default()
+        }
+    }
+}
@@ -0,0 +1,20 @@
+///
+/// A top-level secret, referenced by name in a service's `secrets`
+///
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DcSecret {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external: Option<bool>,
+}
+
+impl DcSecret {
+    pub fn from_file(file: impl Into<String>) -> Self {
+        DcSecret {
+            file: Some(file.into()),
+            external: None,
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use crate::dc_depends_on::DcDependsOn;
+use crate::dc_deploy::DcDeploy;
+use crate::dc_entrypoint::DcEntrypoint;
+use crate::dc_healthcheck::DcHealthcheck;
 use crate::dc_image_build::DcImageBuild;
+use crate::dc_logging::DcLogging;
 use crate::dc_service_network::DcServiceNetwork;
-use std::collections::HashMap;
+use crate::dc_ulimit::DcUlimit;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct DcService {
@@ -11,7 +17,8 @@ pub struct DcService {
     // required field
     pub container_name: String,
 
-    // required field
+    // required field, unless the service is only built from `build`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub image: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,7 +37,7 @@ pub struct DcService {
     pub labels: Option<Vec<String>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub depends_on: Option<Vec<String>>,
+    pub depends_on: Option<DcDependsOn>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ports: Option<Vec<String>>,
@@ -49,6 +56,39 @@ pub struct DcService {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub networks: Option<HashMap<String, DcServiceNetwork>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<DcHealthcheck>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_hosts: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmpfs: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ulimits: Option<BTreeMap<String, DcUlimit>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<DcDeploy>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cap_add: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<DcEntrypoint>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<DcLogging>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shm_size: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<Vec<String>>,
 }
 
 impl DcService {
@@ -96,16 +136,14 @@ impl DcService {
         self
     }
     pub fn set_depends_on(&mut self, depends_on: Vec<impl Into<String>>) -> &mut Self {
-        self.depends_on = Some(depends_on.into_iter().map(|x| x.into()).collect());
+        self.depends_on = Some(DcDependsOn::from(depends_on));
         self
     }
     pub fn add_depends_on(&mut self, depends_on: Vec<impl Into<String>>) -> &mut Self {
-        let next_depends_on = depends_on
-            .into_iter()
-            .map(|x| x.into())
-            .collect::<Vec<String>>();
         let mut prev_depends_on = self.depends_on.clone().unwrap_or_default();
-        prev_depends_on.extend(next_depends_on);
+        for name in depends_on {
+            prev_depends_on.push(name);
+        }
         self.depends_on = Some(prev_depends_on);
         self
     }
@@ -155,6 +193,52 @@ impl DcService {
         }
         self
     }
+    pub fn set_healthcheck(&mut self, healthcheck: DcHealthcheck) -> &mut Self {
+        self.healthcheck = Some(healthcheck);
+        self
+    }
+    pub fn set_extra_hosts(&mut self, extra_hosts: Vec<impl Into<String>>) -> &mut Self {
+        self.extra_hosts = Some(extra_hosts.into_iter().map(|x| x.into()).collect());
+        self
+    }
+    pub fn set_tmpfs(&mut self, tmpfs: Vec<impl Into<String>>) -> &mut Self {
+        self.tmpfs = Some(tmpfs.into_iter().map(|x| x.into()).collect());
+        self
+    }
+    pub fn set_ulimit(&mut self, name: impl Into<String>, ulimit: DcUlimit) -> &mut Self {
+        self.ulimits
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.into(), ulimit);
+        self
+    }
+    pub fn set_deploy(&mut self, deploy: DcDeploy) -> &mut Self {
+        self.deploy = Some(deploy);
+        self
+    }
+    pub fn set_cap_add(&mut self, cap_add: Vec<impl Into<String>>) -> &mut Self {
+        self.cap_add = Some(cap_add.into_iter().map(|x| x.into()).collect());
+        self
+    }
+    pub fn set_user(&mut self, user: impl Into<String>) -> &mut Self {
+        self.user = Some(user.into());
+        self
+    }
+    pub fn set_entrypoint(&mut self, entrypoint: impl Into<DcEntrypoint>) -> &mut Self {
+        self.entrypoint = Some(entrypoint.into());
+        self
+    }
+    pub fn set_logging(&mut self, logging: DcLogging) -> &mut Self {
+        self.logging = Some(logging);
+        self
+    }
+    pub fn set_shm_size(&mut self, shm_size: impl Into<String>) -> &mut Self {
+        self.shm_size = Some(shm_size.into());
+        self
+    }
+    pub fn set_secrets(&mut self, secrets: Vec<impl Into<String>>) -> &mut Self {
+        self.secrets = Some(secrets.into_iter().map(|x| x.into()).collect());
+        self
+    }
     pub fn finish(&self) -> DcService {
         DcService { ..self.clone() }
     }
@@ -163,6 +247,7 @@ impl DcService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dc_deploy::{DcResourceSpec, DcResources};
 
     #[test]
     fn test_dc_service() {
@@ -171,4 +256,103 @@ mod tests {
         assert_eq!(dcs.container_name, "wf2__acme__php");
         assert_eq!(dcs.image, "wearejh/php:7.1");
     }
+
+    #[test]
+    fn test_dc_service_round_trip() {
+        let input = r#"
+            container_name: wf2__acme__db
+            image: mysql:8.0
+            healthcheck:
+              test: ["CMD-SHELL", "mysqladmin ping -h localhost"]
+              interval: 5s
+              timeout: 3s
+              retries: 10
+              start_period: 30s
+            extra_hosts:
+              - "host.docker.internal:host-gateway"
+            tmpfs:
+              - /tmp
+            ulimits:
+              nofile:
+                soft: 20000
+                hard: 40000
+              nproc: 65535
+            deploy:
+              resources:
+                limits:
+                  cpus: "1.5"
+                  memory: 2g
+                reservations:
+                  memory: 512m
+            cap_add:
+              - SYS_PTRACE
+            user: "1000:1000"
+            entrypoint: /docker-entrypoint.sh
+            logging:
+              driver: json-file
+              options:
+                max-size: 10m
+            shm_size: 256m
+            secrets:
+              - composer_auth
+        "#;
+        let expected: serde_yaml::Value = serde_yaml::from_str(input).expect("test yaml");
+
+        // `name` is internal only, so it's given separately
+        let mut with_name = expected.clone();
+        if let serde_yaml::Value::Mapping(m) = &mut with_name {
+            m.insert("name".into(), "db".into());
+        }
+        let parsed: DcService = serde_yaml::from_value(with_name).expect("test yaml");
+
+        let built = DcService::new("acme", "db", "mysql:8.0")
+            .set_healthcheck(
+                DcHealthcheck::cmd_shell("mysqladmin ping -h localhost")
+                    .set_interval("5s")
+                    .set_timeout("3s")
+                    .set_retries(10)
+                    .set_start_period("30s")
+                    .finish(),
+            )
+            .set_extra_hosts(vec!["host.docker.internal:host-gateway"])
+            .set_tmpfs(vec!["/tmp"])
+            .set_ulimit(
+                "nofile",
+                DcUlimit::Pair {
+                    soft: 20000,
+                    hard: 40000,
+                },
+            )
+            .set_ulimit("nproc", DcUlimit::Single(65535))
+            .set_deploy(DcDeploy {
+                resources: Some(DcResources {
+                    limits: Some(DcResourceSpec {
+                        cpus: Some(String::from("1.5")),
+                        memory: Some(String::from("2g")),
+                    }),
+                    reservations: Some(DcResourceSpec {
+                        cpus: None,
+                        memory: Some(String::from("512m")),
+                    }),
+                }),
+            })
+            .set_cap_add(vec!["SYS_PTRACE"])
+            .set_user("1000:1000")
+            .set_entrypoint("/docker-entrypoint.sh")
+            .set_logging(
+                DcLogging::new("json-file")
+                    .set_option("max-size", "10m")
+                    .finish(),
+            )
+            .set_shm_size("256m")
+            .set_secrets(vec!["composer_auth"])
+            .finish();
+
+        assert_eq!(parsed, built);
+
+        // and back again, `name` is never written
+        let output = serde_yaml::to_string(&built).expect("serialize");
+        let reparsed: serde_yaml::Value = serde_yaml::from_str(&output).expect("test");
+        assert_eq!(reparsed, expected);
+    }
 }
@@ -1,7 +1,12 @@
 use crate::dc::Dc;
 
+use crate::dc_network::DcNetwork;
 use crate::dc_volume::DcVolume;
 use crate::file::File;
+use crate::proxy::SharedProxy;
+use crate::services::extra::ExtraServices;
+use crate::services::overrides::ServiceOverrides;
+use crate::services::ports::ServicePorts;
 use crate::services::Services;
 use crate::{context::Context, task::Task};
 use std::path::PathBuf;
@@ -73,24 +78,41 @@ pub trait DcTasksTrait {
     }
     fn services(&self, _ctx: &Context) -> Result<Box<dyn Services>, failure::Error>;
 
-    fn dc_tasks(&self, ctx: &Context) -> Result<DcTasks, failure::Error> {
-        let volumes = self.volumes(ctx);
-        let services = self.services(ctx)?;
-        let dc = Dc::new()
-            .set_volumes(&volumes)
-            .set_services(&services.dc_services())
-            .build();
+    ///
+    /// For recipes without their own Traefik labels, the service (and port)
+    /// that the shared proxy should route the project's domains to
+    ///
+    fn proxy_service(&self) -> Option<(&'static str, u32)> {
+        None
+    }
 
+    fn dc_tasks(&self, ctx: &Context) -> Result<DcTasks, failure::Error> {
+        let dc = self.dc(&ctx)?;
         Ok(DcTasks::from_ctx(&ctx, dc.to_bytes()))
     }
     fn dc(&self, ctx: &Context) -> Result<Dc, failure::Error> {
-        let volumes = self.volumes(ctx);
-        let services = self.services(ctx)?;
-        let dc = Dc::new()
-            .set_volumes(&volumes)
-            .set_services(&services.dc_services())
-            .build();
-        Ok(dc)
+        // any recipe can have extra services added via `options.services.extra`
+        let services = ExtraServices::from_ctx(ctx, self.services(ctx)?.as_ref())?;
+        let mut volumes = self.volumes(ctx);
+        volumes.extend(services.volumes());
+        // published ports can be replaced via `options.services.<name>.ports`
+        let dc_services = ServicePorts::apply(ctx, services.dc_services())?;
+        // and any service can be tweaked via `options.services.<name>.override`
+        let dc_services = ServiceOverrides::apply(ctx, dc_services)?;
+
+        let mut dc = Dc::new();
+        // projects using the shared proxy join its network
+        if SharedProxy::enabled(ctx) {
+            dc.set_services(&SharedProxy::attach(ctx, self.proxy_service(), dc_services))
+                .set_network(
+                    SharedProxy::NETWORK,
+                    DcNetwork::external(SharedProxy::NETWORK),
+                );
+        } else {
+            dc.set_services(&dc_services);
+        }
+
+        Ok(dc.set_volumes(&volumes).healthy_depends_on().build())
     }
     fn dc_and_tasks(&self, ctx: &Context) -> Result<(Dc, DcTasks), failure::Error> {
         let dc = self.dc(&ctx)?;
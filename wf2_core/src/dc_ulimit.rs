@@ -0,0 +1,17 @@
+///
+/// A ulimit can be given as a single value, or as a soft/hard pair
+///
+/// ```yaml
+/// ulimits:
+///   nproc: 65535
+///   nofile:
+///     soft: 20000
+///     hard: 40000
+/// ```
+///
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum DcUlimit {
+    Single(i64),
+    Pair { soft: i64, hard: i64 },
+}
@@ -0,0 +1,374 @@
+//!
+//! Manage the `/etc/hosts` entries needed for a project.
+//!
+//! Every domain is collected from the context + the Traefik labels of the
+//! recipe's services, and written into a delimited block per project, like:
+//!
+//! ```txt
+//! # wf2 start: acme
+//! 127.0.0.1 acme.m2 mail.jh queue.jh
+//! # wf2 end: acme
+//! ```
+//!
+//! Anything outside of that block is never modified.
+//!
+use crate::context::Context;
+use crate::recipes::local_domain::LocalDomain;
+use crate::recipes::recipe_kinds::RecipeKinds;
+use crate::services::traefik::TraefikService;
+use crate::task::Task;
+use ansi_term::Colour::Yellow;
+use futures::future::lazy;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+#[derive(Debug, Fail)]
+enum HostsError {
+    #[fail(
+        display = "{} entries missing from {}: {}\nRun `sudo wf2 hosts apply` to add them",
+        count, path, domains
+    )]
+    Missing {
+        count: usize,
+        path: String,
+        domains: String,
+    },
+    #[fail(
+        display = "Permission denied when writing {}, try again with `sudo`",
+        _0
+    )]
+    PermissionDenied(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostEntries {
+    pub project: String,
+    pub domains: Vec<String>,
+}
+
+impl HostEntries {
+    pub const DEFAULT_PATH: &'static str = "/etc/hosts";
+    pub const IP: &'static str = "127.0.0.1";
+
+    pub fn new(project: impl Into<String>, domains: Vec<String>) -> HostEntries {
+        let mut unique: Vec<String> = vec![];
+        for domain in domains.iter().filter_map(|d| HostEntries::host_name(d)) {
+            if !unique.contains(&domain) {
+                unique.push(domain);
+            }
+        }
+        HostEntries {
+            project: project.into(),
+            domains: unique,
+        }
+    }
+
+    ///
+    /// All domains from the context, plus any routed to the recipe's services
+    ///
+    pub fn from_ctx(ctx: &Context) -> Result<HostEntries, failure::Error> {
+        let dc = RecipeKinds::from_ctx(ctx).dc(ctx)?;
+        let mut domains = match ctx.recipe {
+            Some(recipe) if recipe != RecipeKinds::M2 => vec![LocalDomain::from_ctx(ctx)],
+            _ => ctx.domains(),
+        };
+        for service in dc.services.unwrap_or_default().values() {
+            if let Some(labels) = service.labels.as_ref() {
+                domains.extend(TraefikService::hosts_from_labels(labels));
+            }
+        }
+        Ok(HostEntries::new(ctx.name(), domains))
+    }
+
+    ///
+    /// `localhost:8080` or `127.0.0.1` never need an entry
+    ///
+    fn host_name(domain: &str) -> Option<String> {
+        let host = domain.split(':').next().unwrap_or("").trim();
+        if host.is_empty() || host == "localhost" || host.parse::<IpAddr>().is_ok() {
+            None
+        } else {
+            Some(host.to_string())
+        }
+    }
+
+    pub fn start_marker(&self) -> String {
+        format!("# wf2 start: {}", self.project)
+    }
+
+    pub fn end_marker(&self) -> String {
+        format!("# wf2 end: {}", self.project)
+    }
+
+    pub fn block(&self) -> String {
+        format!(
+            "{}\n{} {}\n{}\n",
+            self.start_marker(),
+            HostEntries::IP,
+            self.domains.join(" "),
+            self.end_marker()
+        )
+    }
+
+    ///
+    /// Domains that are not mapped anywhere in the given hosts file content
+    ///
+    pub fn missing(&self, hosts: &str) -> Vec<String> {
+        let mapped: Vec<&str> = hosts
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split_whitespace().skip(1))
+            .collect();
+        self.domains
+            .iter()
+            .filter(|domain| !mapped.contains(&domain.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Add (or replace) this project's block
+    ///
+    pub fn apply(&self, hosts: &str) -> String {
+        if self.domains.is_empty() {
+            return self.remove(hosts);
+        }
+        format!("{}{}", self.remove(hosts), self.block())
+    }
+
+    ///
+    /// Remove this project's block, leaving everything else untouched.
+    ///
+    /// A start marker without a matching end marker is left as-is, since
+    /// there's no way to tell where the block was meant to finish
+    ///
+    pub fn remove(&self, hosts: &str) -> String {
+        let start = self.start_marker();
+        let end = self.end_marker();
+        let all: Vec<&str> = hosts.lines().collect();
+        let mut lines: Vec<&str> = vec![];
+        let mut index = 0;
+        while index < all.len() {
+            if all[index].trim() == start {
+                let block_len = all[index + 1..].iter().position(|line| line.trim() == end);
+                if let Some(block_len) = block_len {
+                    index += block_len + 2;
+                    continue;
+                }
+            }
+            lines.push(all[index]);
+            index += 1;
+        }
+        match lines.len() {
+            0 => String::new(),
+            _ => format!("{}\n", lines.join("\n")),
+        }
+    }
+
+    pub fn check_task(&self, path: impl Into<PathBuf>) -> Task {
+        let entries = self.clone();
+        let path = path.into();
+        Task::Exec {
+            description: Some(format!("check host entries in {}", path.display())),
+            exec: Box::new(lazy(move || {
+                let missing = entries.missing(&read(&path)?);
+                if missing.is_empty() {
+                    println!(
+                        "All {} entries present in {}",
+                        entries.domains.len(),
+                        path.display()
+                    );
+                    return Ok(());
+                }
+                Err(HostsError::Missing {
+                    count: missing.len(),
+                    path: path.display().to_string(),
+                    domains: missing.join(", "),
+                }
+                .into())
+            })),
+        }
+    }
+
+    pub fn apply_task(&self, path: impl Into<PathBuf>) -> Task {
+        let entries = self.clone();
+        let path = path.into();
+        Task::Exec {
+            description: Some(format!("add host entries to {}", path.display())),
+            exec: Box::new(lazy(move || {
+                write(&path, entries.apply(&read(&path)?))?;
+                println!(
+                    "{} entries written to {}: {}",
+                    entries.domains.len(),
+                    path.display(),
+                    entries.domains.join(", ")
+                );
+                Ok(())
+            })),
+        }
+    }
+
+    pub fn remove_task(&self, path: impl Into<PathBuf>) -> Task {
+        let entries = self.clone();
+        let path = path.into();
+        Task::Exec {
+            description: Some(format!("remove host entries from {}", path.display())),
+            exec: Box::new(lazy(move || {
+                write(&path, entries.remove(&read(&path)?))?;
+                println!(
+                    "Entries for {} removed from {}",
+                    entries.project,
+                    path.display()
+                );
+                Ok(())
+            })),
+        }
+    }
+
+    ///
+    /// Like [`HostEntries::check_task`], but only prints a warning
+    ///
+    pub fn warn_task(&self, path: impl Into<PathBuf>) -> Task {
+        let entries = self.clone();
+        let path = path.into();
+        Task::Exec {
+            description: Some(format!("check host entries in {}", path.display())),
+            exec: Box::new(lazy(move || {
+                if let Ok(hosts) = read(&path) {
+                    let missing = entries.missing(&hosts);
+                    if !missing.is_empty() {
+                        println!(
+                            "{}: missing from {}: {} (run `sudo wf2 hosts apply`)",
+                            Yellow.paint("[wf2 warning]"),
+                            path.display(),
+                            missing.join(", ")
+                        );
+                    }
+                }
+                Ok(())
+            })),
+        }
+    }
+}
+
+fn read(path: &PathBuf) -> Result<String, failure::Error> {
+    match fs::read_to_string(path) {
+        Ok(hosts) => Ok(hosts),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+///
+/// Written to a temporary file next to the original first, then moved over it,
+/// so that a failed write can never leave a half-written hosts file behind
+///
+fn write(path: &PathBuf, content: String) -> Result<(), failure::Error> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".wf2-tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let written = fs::write(&tmp, content)
+        .and_then(|_| match fs::metadata(path) {
+            Ok(meta) => fs::set_permissions(&tmp, meta.permissions()),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    written.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        match e.kind() {
+            ErrorKind::PermissionDenied => {
+                HostsError::PermissionDenied(path.display().to_string()).into()
+            }
+            _ => e.into(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WF2;
+    use futures::Future;
+
+    #[test]
+    fn test_from_ctx() {
+        let ctx = Context::new_from_file("../fixtures/pwa.yml")
+            .expect("test")
+            .expect("test");
+        let entries = HostEntries::from_ctx(&ctx).expect("test");
+        assert_eq!(
+            entries.domains,
+            vec![
+                "example.m2",
+                "mail.jh",
+                "queue.jh",
+                "example.pwa",
+                "test.ngrok.io"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_remove() {
+        let entries = HostEntries::new(
+            "acme",
+            vec![
+                String::from("acme.m2"),
+                String::from("localhost:8080"),
+                String::from("mail.jh"),
+            ],
+        );
+        let hosts = "127.0.0.1 localhost\n::1 localhost\n";
+        assert_eq!(entries.missing(hosts), vec!["acme.m2", "mail.jh"]);
+
+        let applied = entries.apply(hosts);
+        assert_eq!(
+            applied,
+            "127.0.0.1 localhost\n::1 localhost\n# wf2 start: acme\n127.0.0.1 acme.m2 mail.jh\n# wf2 end: acme\n"
+        );
+        assert!(entries.missing(&applied).is_empty());
+        assert_eq!(entries.apply(&applied), applied);
+        assert_eq!(entries.remove(&applied), hosts);
+
+        // other projects are left alone
+        let other = HostEntries::new("other", vec![String::from("other.m2")]);
+        assert_eq!(other.remove(&applied), applied);
+    }
+
+    #[test]
+    fn test_remove_without_end_marker() {
+        let entries = HostEntries::new("acme", vec![String::from("acme.m2")]);
+        let hosts =
+            "127.0.0.1 localhost\n# wf2 start: acme\n127.0.0.1 acme.m2\n10.0.0.1 intranet\n";
+        assert_eq!(entries.remove(hosts), hosts);
+    }
+
+    #[test]
+    fn test_from_ctx_wp() {
+        let ctx =
+            Context::new_from_str("{ recipe: Wp, domains: [ acme.test:8080 ] }").expect("test");
+        let entries = HostEntries::from_ctx(&ctx).expect("test");
+        assert_eq!(entries.domains, vec!["acme.test"]);
+
+        let ctx = Context::new_from_str("recipe: Drupal").expect("test");
+        let entries = HostEntries::from_ctx(&ctx).expect("test");
+        assert!(!entries.domains.contains(&String::from("local.m2")));
+    }
+
+    #[test]
+    fn test_apply_task() -> Result<(), failure::Error> {
+        let dir = tempdir::TempDir::new("wf2-hosts")?;
+        let path = dir.path().join("hosts");
+        fs::write(&path, "127.0.0.1 localhost\n")?;
+        let entries = HostEntries::new("acme", vec![String::from("acme.m2")]);
+        assert!(WF2::sequence(vec![entries.apply_task(&path)])
+            .wait()
+            .is_ok());
+        assert!(entries.missing(&fs::read_to_string(&path)?).is_empty());
+        assert!(!dir.path().join("hosts.wf2-tmp").exists());
+        Ok(())
+    }
+}
@@ -106,6 +106,9 @@ extern crate from_file_derive;
 #[macro_use]
 extern crate failure;
 
+#[macro_use]
+extern crate lazy_static;
+
 use futures::{future::lazy, future::Future, stream::iter_ok, Stream};
 
 use crate::condition::{Answer, Con, ConditionFuture};
@@ -114,6 +117,8 @@ use crate::{
     task::{as_future, Task},
 };
 
+#[doc(hidden)]
+pub mod certs;
 #[doc(hidden)]
 pub mod cli;
 #[doc(hidden)]
@@ -128,24 +133,44 @@ pub mod context;
 #[doc(hidden)]
 pub mod dc;
 #[doc(hidden)]
+pub mod dc_depends_on;
+#[doc(hidden)]
+pub mod dc_deploy;
+#[doc(hidden)]
+pub mod dc_entrypoint;
+#[doc(hidden)]
+pub mod dc_healthcheck;
+#[doc(hidden)]
 pub mod dc_image_build;
 #[doc(hidden)]
+pub mod dc_logging;
+#[doc(hidden)]
+pub mod dc_network;
+#[doc(hidden)]
+pub mod dc_secret;
+#[doc(hidden)]
 pub mod dc_service;
 #[doc(hidden)]
 pub mod dc_service_network;
 #[doc(hidden)]
 pub mod dc_tasks;
 #[doc(hidden)]
+pub mod dc_ulimit;
+#[doc(hidden)]
 pub mod dc_volume;
 #[doc(hidden)]
 pub mod file;
 #[doc(hidden)]
 pub mod file_op;
 #[doc(hidden)]
+pub mod hosts;
+#[doc(hidden)]
 pub mod output;
 #[doc(hidden)]
 pub mod output_files;
 #[doc(hidden)]
+pub mod proxy;
+#[doc(hidden)]
 pub mod recipes;
 #[doc(hidden)]
 pub mod scripts;
@@ -1,5 +1,4 @@
-use serde::de;
-use std::fmt;
+use version_enum::VersionEnum;
 
 ///
 /// The PHP version used throughout this project.
@@ -10,63 +9,47 @@ use std::fmt;
 /// php_version: 7.1
 /// ```
 ///
-#[derive(Debug, Clone, PartialEq)]
+/// Adding a version is a one-line change, everything else -
+/// deserializing, error messages & image names - is derived from this table.
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "wearejh/php:{}-m2")]
 pub enum PHP {
+    #[version("7.1")]
     SevenOne,
+    #[version("7.2")]
     SevenTwo,
+    #[version("7.3", default)]
     SevenThree,
+    #[version("7.4")]
     SevenFour,
+    #[version("8.0")]
+    EightZero,
+    #[version("8.1")]
+    EightOne,
+    #[version("8.2")]
+    EightTwo,
+    #[version("8.3")]
+    EightThree,
 }
 
-impl Default for PHP {
-    fn default() -> Self {
-        PHP::SevenThree
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
 
-///
-/// Helpers for deserializing direct from yaml
-///
-pub fn deserialize_php<'de, D>(deserializer: D) -> Result<PHP, D::Error>
-where
-    D: de::Deserializer<'de>,
-{
-    struct PHPVisitor;
-
-    impl<'de> de::Visitor<'de> for PHPVisitor {
-        type Value = PHP;
+    #[test]
+    fn test_php_versions() {
+        let ctx = Context::new_from_str("php_version: 8.0").expect("test");
+        assert_eq!(ctx.php_version, PHP::EightZero);
+        assert_eq!(ctx.php_version.image(), "wearejh/php:8.0-m2");
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("either `7.1`, `7.2`, `7.3` or `7.4`")
-        }
+        let ctx = Context::new_from_str("php_version: \"8.3\"").expect("test");
+        assert_eq!(ctx.php_version, PHP::EightThree);
 
-        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            let r = match v {
-                num if num == 7.1 => Ok(PHP::SevenOne),
-                num if num == 7.2 => Ok(PHP::SevenTwo),
-                num if num == 7.3 => Ok(PHP::SevenThree),
-                num if num == 7.4 => Ok(PHP::SevenFour),
-                _ => Err("expected either 7.1, 7.2, 7.3 or 7.4"),
-            };
-            r.map_err(E::custom)
-        }
-        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            let r = match v {
-                "7.1" => Ok(PHP::SevenOne),
-                "7.2" => Ok(PHP::SevenTwo),
-                "7.3" => Ok(PHP::SevenThree),
-                "7.4" => Ok(PHP::SevenFour),
-                _ => Err("expected either 7.1, 7.2, 7.3 or 7.4"),
-            };
-            r.map_err(E::custom)
-        }
+        let err = Context::new_from_str("php_version: 5.6").expect_err("test");
+        assert!(err
+            .to_string()
+            .contains("expected either `7.1`, `7.2`, `7.3`, `7.4`, `8.0`, `8.1`, `8.2` or `8.3`"));
     }
-
-    deserializer.deserialize_any(PHPVisitor)
 }
@@ -0,0 +1,493 @@
+//!
+//! A single Traefik + MailHog stack, shared by every project.
+//!
+//! Without it, each project starts its own `traefik` on ports 80/443, meaning
+//! only one project can run at a time. Opt in per project with:
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # use wf2_core::proxy::SharedProxy;
+//! # let input = r#"
+//! recipe: M2
+//! domains: [ acme.m2 ]
+//! options:
+//!   proxy:
+//!     shared: true
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx).expect("valid dc");
+//! # let services = dc.services.expect("services");
+//! # assert!(services.get("traefik").is_none());
+//! # assert!(services.get("mail").is_none());
+//! # let varnish = services.get("varnish").expect("varnish");
+//! # assert!(varnish.networks.as_ref().expect("networks").contains_key(SharedProxy::NETWORK));
+//! # assert!(dc.networks.expect("networks").contains_key(SharedProxy::NETWORK));
+//! ```
+//!
+//! Then start the proxy once, with `wf2 proxy up`.
+//!
+//! - Routed services join the external `wf2_proxy` network, everything else
+//!   stays on the project's own network.
+//! - MailHog is reachable from containers on `host.docker.internal:1025`.
+//! - Each project's certificate is written into `~/.wf2/proxy/certs`.
+//! - Published ports (like `3306` for the database) get a random host port, so that
+//!   projects don't clash. Use `options.services.<name>.ports` to fix one, or
+//!   `wf2 dc port db 3306` to find it.
+//! - Without any `domains` (eg: the `localhost:8080` default of Wp & Drupal) there's
+//!   nothing to route, so the web service keeps its published port instead.
+//!
+use crate::certs::ProjectCert;
+use crate::context::Context;
+use crate::dc::Dc;
+use crate::dc_network::DcNetwork;
+use crate::dc_service::DcService;
+use crate::dc_service_network::DcServiceNetwork;
+use crate::recipes::recipe_kinds::RecipeKinds;
+use crate::services::mail::MailService;
+use crate::services::ports::ServicePorts;
+use crate::services::traefik::TraefikService;
+use crate::services::Service;
+use crate::task::Task;
+use crate::tasks::docker_clean::docker_clean;
+use crate::util::path_buf_to_string;
+use std::path::PathBuf;
+
+#[derive(Debug, Fail)]
+enum SharedProxyError {
+    #[fail(display = "Could not determine the home directory for the shared proxy")]
+    MissingHome,
+}
+
+#[derive(Debug, Clone)]
+pub struct SharedProxy {
+    pub dir: PathBuf,
+}
+
+impl SharedProxy {
+    pub const NAME: &'static str = "proxy";
+    pub const NETWORK: &'static str = "wf2_proxy";
+    pub const MAIL_HOST: &'static str = "host.docker.internal";
+    pub const HOST_GATEWAY: &'static str = "host.docker.internal:host-gateway";
+    pub const CONTAINER_DYNAMIC_DIR: &'static str = "/etc/traefik/dynamic";
+
+    ///
+    /// Services that the proxy provides, so projects don't start their own
+    ///
+    pub const PROVIDED: [&'static str; 2] = [TraefikService::NAME, MailService::NAME];
+
+    pub fn new(dir: impl Into<PathBuf>) -> SharedProxy {
+        SharedProxy { dir: dir.into() }
+    }
+
+    ///
+    /// The default location, `~/.wf2/proxy`
+    ///
+    pub fn from_home() -> Result<SharedProxy, failure::Error> {
+        dirs::home_dir()
+            .map(|home| SharedProxy::new(home.join(".wf2").join(SharedProxy::NAME)))
+            .ok_or_else(|| SharedProxyError::MissingHome.into())
+    }
+
+    ///
+    /// `options.proxy.shared: true`
+    ///
+    pub fn enabled(ctx: &Context) -> bool {
+        ctx.options
+            .as_ref()
+            .and_then(|opts| opts["proxy"]["shared"].as_bool())
+            .unwrap_or(false)
+    }
+
+    pub fn compose_file(&self) -> PathBuf {
+        self.dir.join("docker-compose.yml")
+    }
+
+    pub fn traefik_file(&self) -> PathBuf {
+        self.dir.join("traefik/traefik.toml")
+    }
+
+    pub fn dynamic_dir(&self) -> PathBuf {
+        self.dir.join("dynamic")
+    }
+
+    pub fn certs_dir(&self) -> PathBuf {
+        self.dir.join("certs")
+    }
+
+    ///
+    /// The proxy's own docker-compose file
+    ///
+    pub fn dc(&self) -> Dc {
+        let network = || {
+            let mut networks = std::collections::HashMap::new();
+            networks.insert(
+                SharedProxy::NETWORK.to_string(),
+                DcServiceNetwork::default(),
+            );
+            Some(networks)
+        };
+
+        let mut traefik = DcService::new(
+            SharedProxy::NAME,
+            TraefikService::NAME,
+            TraefikService::IMAGE_V2,
+        )
+        .set_volumes(vec![
+            "/var/run/docker.sock:/var/run/docker.sock".to_string(),
+            format!(
+                "{}:/etc/traefik/traefik.toml",
+                path_buf_to_string(&self.traefik_file())
+            ),
+            format!(
+                "{}:{}",
+                path_buf_to_string(&self.dynamic_dir()),
+                SharedProxy::CONTAINER_DYNAMIC_DIR
+            ),
+            format!(
+                "{}:{}",
+                path_buf_to_string(&self.certs_dir()),
+                ProjectCert::CONTAINER_DIR
+            ),
+        ])
+        .set_ports(vec!["80:80", "443:443", "8080:8080"])
+        .set_labels(vec![TraefikService::TRAEFIK_DISABLE_LABEL])
+        .finish();
+        traefik.networks = network();
+
+        let mut labels = TraefikService::router_labels(
+            &format!("{}-{}", SharedProxy::NAME, MailService::NAME),
+            &[MailService::DOMAIN.to_string()],
+            Some(MailService::UI_PORT),
+        );
+        labels.push(SharedProxy::network_label());
+        let mut mail = DcService::new(SharedProxy::NAME, MailService::NAME, MailService::IMAGE)
+            .set_ports(vec![format!(
+                "{port}:{port}",
+                port = MailService::SMTP_PORT
+            )])
+            .set_labels(labels)
+            .finish();
+        mail.networks = network();
+
+        Dc::new()
+            .set_services(&[traefik, mail])
+            .set_network(
+                SharedProxy::NETWORK,
+                DcNetwork::external(SharedProxy::NETWORK),
+            )
+            .build()
+    }
+
+    ///
+    /// Traefik v2 static config - certificates for each project
+    /// are picked up from the dynamic directory
+    ///
+    pub fn traefik_toml() -> String {
+        format!(
+            include_str!("./recipes/m2/output_files/traefik_proxy.toml"),
+            network = SharedProxy::NETWORK,
+            dynamic_dir = SharedProxy::CONTAINER_DYNAMIC_DIR,
+        )
+    }
+
+    pub fn network_label() -> String {
+        format!("traefik.docker.network={}", SharedProxy::NETWORK)
+    }
+
+    ///
+    /// Create the network if it doesn't exist, so that projects can start
+    /// before the proxy does
+    ///
+    pub fn network_task() -> Task {
+        Task::simple_command(format!(
+            "docker network inspect {name} >/dev/null 2>&1 || docker network create {name}",
+            name = SharedProxy::NETWORK
+        ))
+    }
+
+    ///
+    /// Before a project using the proxy starts: the network it joins
+    ///
+    pub fn project_up_tasks(ctx: &Context) -> Vec<Task> {
+        if !SharedProxy::enabled(ctx) {
+            return vec![];
+        }
+        vec![
+            SharedProxy::network_task(),
+            Task::notify_info("routing via the shared proxy, start it with `wf2 proxy up`"),
+        ]
+    }
+
+    ///
+    /// `--clean` stops every container, including the proxy, so it's skipped when in use
+    ///
+    pub fn clean_tasks(ctx: &Context, clean: bool) -> Vec<Task> {
+        match (clean, SharedProxy::enabled(ctx)) {
+            (true, true) => vec![Task::notify_warn(
+                "skipping --clean, it's not needed when using the shared proxy",
+            )],
+            (true, false) => docker_clean(),
+            (false, _) => vec![],
+        }
+    }
+
+    pub fn up_tasks(&self) -> Vec<Task> {
+        vec![
+            Task::file_write(
+                self.traefik_file(),
+                "Writes the shared traefik file",
+                SharedProxy::traefik_toml().into_bytes(),
+            ),
+            Task::file_write(
+                self.compose_file(),
+                "Writes the shared proxy docker-compose file",
+                self.dc().to_bytes(),
+            ),
+            Task::dir_create(self.dynamic_dir(), "Create the dynamic config directory"),
+            Task::dir_create(self.certs_dir(), "Create the certificates directory"),
+            SharedProxy::network_task(),
+            Task::simple_command(self.compose_cmd("up -d")),
+        ]
+    }
+
+    pub fn down_tasks(&self) -> Vec<Task> {
+        vec![Task::simple_command(self.compose_cmd("down"))]
+    }
+
+    fn compose_cmd(&self, trailing: &str) -> String {
+        format!(
+            "docker-compose -p {project} -f {file} {trailing}",
+            project = SharedProxy::NETWORK,
+            file = self.compose_file().display(),
+            trailing = trailing
+        )
+    }
+
+    ///
+    /// For a project using the proxy: its certificate, plus
+    /// the dynamic config telling Traefik where to find it
+    ///
+    pub fn project_tasks(&self, ctx: &Context) -> Vec<Task> {
+        let project_cert = ProjectCert {
+            dir: self.certs_dir().join(ctx.name()),
+            ..ProjectCert::from_ctx(ctx)
+        };
+        let container_dir = format!("{}/{}", ProjectCert::CONTAINER_DIR, ctx.name());
+        let dynamic = format!(
+            "[[tls.certificates]]\n  certFile = \"{dir}/{cert}\"\n  keyFile = \"{dir}/{key}\"\n",
+            dir = container_dir,
+            cert = ProjectCert::CERT,
+            key = ProjectCert::KEY,
+        );
+        let cert_task = project_cert.write_task_from_home();
+        vec![
+            cert_task,
+            Task::file_write(
+                self.dynamic_dir().join(format!("{}.toml", ctx.name())),
+                "Writes the TLS config for the shared proxy",
+                dynamic.into_bytes(),
+            ),
+        ]
+    }
+
+    ///
+    /// Adjust a project's services to use the proxy:
+    ///
+    /// - drop anything the proxy already provides
+    /// - route `route` (for recipes without their own Traefik labels) to the project's domains
+    /// - add every routed service to the shared network
+    /// - let docker pick host ports, unless they were set explicitly
+    ///
+    pub fn attach(
+        ctx: &Context,
+        route: Option<(&str, u32)>,
+        services: Vec<DcService>,
+    ) -> Vec<DcService> {
+        // only M2 falls back to `local.m2`, the others are served on `localhost`
+        let domains = match ctx.recipe {
+            Some(recipe) if recipe != RecipeKinds::M2 => ctx.domains.clone(),
+            _ => ctx.domains(),
+        };
+        let domains: Vec<String> = domains
+            .into_iter()
+            .filter(|domain| !domain.contains(':') && domain != "localhost")
+            .collect();
+        let explicit_ports = ServicePorts::from_ctx(ctx).unwrap_or_default();
+
+        services
+            .into_iter()
+            .filter(|service| !SharedProxy::PROVIDED.contains(&service.name.as_str()))
+            .map(|mut service| {
+                if let Some(depends_on) = service.depends_on.as_mut() {
+                    depends_on.retain(|name| !SharedProxy::PROVIDED.contains(&name));
+                }
+
+                if let Some(env) = service.environment.as_mut() {
+                    for entry in env.iter_mut() {
+                        if entry == &format!("MAIL_HOST={}", MailService::NAME) {
+                            *entry = format!("MAIL_HOST={}", SharedProxy::MAIL_HOST);
+                        }
+                    }
+                }
+
+                // without a domain to route, the published port is the only way in
+                let unrouted = route.map_or(false, |(name, _)| {
+                    name == service.name && domains.is_empty()
+                });
+                if !explicit_ports.contains_key(&service.name) && !unrouted {
+                    if let Some(ports) = service.ports.as_mut() {
+                        for port in ports.iter_mut() {
+                            *port = without_host_port(port);
+                        }
+                    }
+                }
+
+                let mut extra_hosts = service.extra_hosts.clone().unwrap_or_default();
+                if !extra_hosts.iter().any(|h| h == SharedProxy::HOST_GATEWAY) {
+                    extra_hosts.push(SharedProxy::HOST_GATEWAY.to_string());
+                }
+                service.set_extra_hosts(extra_hosts);
+
+                let mut labels = service.labels.clone().unwrap_or_default();
+                if let Some((name, port)) = route {
+                    if name == service.name && !domains.is_empty() && !is_routed(&labels) {
+                        labels.extend(TraefikService::labels(
+                            ctx,
+                            name,
+                            domains.clone(),
+                            Some(port),
+                        ));
+                    }
+                }
+
+                if is_routed(&labels) {
+                    labels.push(SharedProxy::network_label());
+                    let default = service
+                        .networks
+                        .as_ref()
+                        .and_then(|networks| networks.get("default").cloned())
+                        .unwrap_or_default();
+                    service
+                        .set_labels(labels)
+                        .set_network("default", default)
+                        .set_network(SharedProxy::NETWORK, DcServiceNetwork::default());
+                }
+
+                service
+            })
+            .collect()
+    }
+}
+
+fn is_routed(labels: &[String]) -> bool {
+    labels.iter().any(|label| label == "traefik.enable=true")
+}
+
+///
+/// `3306:3306` -> `3306`, `127.0.0.1:3306:3306` -> `127.0.0.1::3306`
+///
+fn without_host_port(port: &str) -> String {
+    let parts: Vec<&str> = port.split(':').collect();
+    match parts.len() {
+        2 => parts[1].to_string(),
+        3 => format!("{}::{}", parts[0], parts[2]),
+        _ => port.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_tasks::DcTasksTrait;
+    use crate::recipes::wp::WpRecipe;
+
+    #[test]
+    fn test_proxy_dc() {
+        let proxy = SharedProxy::new("/users/shane/.wf2/proxy");
+        let dc = proxy.dc();
+        let services = dc.services.expect("test");
+        let traefik = services.get("traefik").expect("test");
+        assert_eq!(traefik.container_name, "wf2__proxy__traefik");
+        assert_eq!(traefik.image, TraefikService::IMAGE_V2);
+        let mail = services.get("mail").expect("test");
+        assert_eq!(mail.ports, Some(vec![String::from("1025:1025")]));
+        assert!(mail
+            .labels
+            .as_ref()
+            .expect("test")
+            .contains(&String::from("traefik.docker.network=wf2_proxy")));
+        assert!(SharedProxy::traefik_toml().contains("network = \"wf2_proxy\""));
+    }
+
+    #[test]
+    fn test_wp_attach() {
+        let ctx = Context::new_from_str(
+            "recipe: Wp\ndomains: [ acme.wp ]\noptions: { proxy: { shared: true } }",
+        )
+        .expect("test");
+        let dc = (WpRecipe).dc(&ctx).expect("test");
+        let services = dc.services.expect("test");
+        let nginx = services.get("nginx").expect("test");
+        let labels = nginx.labels.as_ref().expect("test");
+        assert!(labels.contains(&String::from(
+            "traefik.http.routers.wf2-default-nginx.rule=Host(`acme.wp`)"
+        )));
+        let networks = nginx.networks.as_ref().expect("test");
+        assert!(networks.contains_key("default"));
+        assert!(networks.contains_key(SharedProxy::NETWORK));
+
+        // unrouted services stay on the project network only
+        assert!(services.get("db").expect("test").networks.is_none());
+
+        // and docker picks the host ports
+        assert_eq!(
+            services.get("db").expect("test").ports,
+            Some(vec![String::from("3306")])
+        );
+    }
+
+    #[test]
+    fn test_attach_keeps_explicit_ports() {
+        let ctx = Context::new_from_str(
+            r#"
+            recipe: M2
+            options:
+              proxy: { shared: true }
+              services:
+                db: { ports: [ "3310:3306" ] }
+            "#,
+        )
+        .expect("test");
+        let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx).expect("test");
+        let services = dc.services.expect("test");
+        assert_eq!(
+            services.get("db").expect("test").ports,
+            Some(vec![String::from("3310:3306")])
+        );
+        assert_eq!(
+            services.get("elasticsearch").expect("test").ports,
+            Some(vec![String::from("9200")])
+        );
+        assert_eq!(without_host_port("127.0.0.1:3306:3306"), "127.0.0.1::3306");
+    }
+
+    #[test]
+    fn test_attach_without_domains() {
+        let ctx = Context::new_from_str(
+            "recipe: Wp
+options: { proxy: { shared: true } }",
+        )
+        .expect("test");
+        let dc = (WpRecipe).dc(&ctx).expect("test");
+        let services = dc.services.expect("test");
+        let nginx = services.get("nginx").expect("test");
+        assert!(!is_routed(nginx.labels.as_deref().unwrap_or_default()));
+        assert_eq!(nginx.ports, Some(vec![String::from("8080:80")]));
+        assert_eq!(
+            services.get("db").expect("test").ports,
+            Some(vec![String::from("3306")])
+        );
+    }
+}
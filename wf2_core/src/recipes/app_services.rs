@@ -0,0 +1,93 @@
+//!
+//! Describes which services in a recipe play the well-known roles that the
+//! shared sub-commands (such as `exec`, `pull`, `db-import` & `sql`) need.
+//!
+//! For example, `wf2 exec ls` runs `ls` in whichever service the current recipe
+//! returns from [`AppServices::app_service`]
+//!
+use crate::context::Context;
+use crate::dc_service::DcService;
+
+///
+/// A database service along with the credentials needed to access it.
+///
+#[derive(Debug, Clone)]
+pub struct DbCredentials {
+    pub service: DcService,
+    pub user: String,
+    pub pass: String,
+    pub db: String,
+    ///
+    /// The client binaries inside the service, `mysql` + `mysqldump` by default
+    ///
+    pub client: String,
+    pub dump_client: String,
+}
+
+impl DbCredentials {
+    pub fn new(
+        service: DcService,
+        user: impl Into<String>,
+        pass: impl Into<String>,
+        db: impl Into<String>,
+    ) -> DbCredentials {
+        DbCredentials {
+            service,
+            user: user.into(),
+            pass: pass.into(),
+            db: db.into(),
+            client: String::from("mysql"),
+            dump_client: String::from("mysqldump"),
+        }
+    }
+
+    pub fn set_clients(
+        &mut self,
+        client: impl Into<String>,
+        dump_client: impl Into<String>,
+    ) -> &mut Self {
+        self.client = client.into();
+        self.dump_client = dump_client.into();
+        self
+    }
+}
+
+#[derive(Debug, Fail)]
+enum AppServicesError {
+    #[fail(display = "This recipe doesn't have an 'app' service")]
+    MissingApp,
+    #[fail(display = "This recipe doesn't have a database service")]
+    MissingDb,
+}
+
+pub trait AppServices {
+    ///
+    /// The main container, where commands are executed and files are
+    /// pushed to & pulled from.
+    ///
+    fn app_service(&self, _ctx: &Context) -> Result<DcService, failure::Error> {
+        Err(AppServicesError::MissingApp.into())
+    }
+
+    ///
+    /// The path inside the app service that the project root is mounted at
+    ///
+    fn app_root(&self) -> &'static str {
+        "/var/www"
+    }
+
+    ///
+    /// Paths that are already synced into the app service, and therefore
+    /// should never be pushed
+    ///
+    fn synced_paths(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    ///
+    /// The database container, along with its credentials
+    ///
+    fn db_service(&self, _ctx: &Context) -> Result<DbCredentials, failure::Error> {
+        Err(AppServicesError::MissingDb.into())
+    }
+}
@@ -0,0 +1,127 @@
+//!
+//! Drupal 8+ environments, based on the `drupal/recommended-project` layout.
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # let input = r#"
+//! recipe: Drupal
+//! domains: [ "localhost:8080" ]
+//! # "#;
+//! # let _ctx: Context = serde_yaml::from_str(input).expect("Can parse drupal yaml example");
+//! ```
+//!
+//! Database & other local settings are written to a `settings.local.php`, which
+//! `web/sites/default/settings.php` must include - uncomment the block at the end of it.
+//!
+//! ## Redis
+//!
+//! Redis is not started by default, enable it in your `wf2.yml` file and
+//! the generated `settings.local.php` will be configured to use it. The `redis`
+//! module must be installed & enabled (`composer require drupal/redis`).
+//!
+//! ```yaml
+//! options:
+//!   services:
+//!     redis:
+//!       enabled: true
+//! ```
+//!
+use crate::cmd::PassThruCmd;
+use crate::commands::{CliCommand, Commands};
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::dc_tasks::DcTasksTrait;
+use crate::dc_volume::DcVolume;
+use crate::file::File;
+use crate::recipes::app_services::{AppServices, DbCredentials};
+use crate::recipes::drupal::output_files::settings_local::DrupalSettingsLocal;
+use crate::recipes::drupal::pass_thru::DrupalPassThru;
+use crate::recipes::drupal::services::drupal_db::DrupalDbService;
+use crate::recipes::drupal::services::drupal_nginx::DrupalNginxService;
+use crate::recipes::drupal::services::drupal_php::DrupalPhpService;
+use crate::recipes::drupal::services::DrupalServices;
+use crate::recipes::drupal::subcommands::drupal_recipe_subcommands;
+use crate::recipes::drupal::volumes::get_volumes;
+use crate::recipes::m2::output_files::composer::Composer;
+use crate::recipes::m2::services::db::DbService;
+use crate::recipes::validate::ValidateRecipe;
+use crate::recipes::Recipe;
+use crate::scripts::script::ResolveScript;
+use crate::services::{Service, Services};
+use crate::subcommands::PassThru;
+use crate::task::Task;
+
+pub mod output_files;
+pub mod pass_thru;
+pub mod services;
+pub mod subcommands;
+pub mod volumes;
+
+#[derive(Clone)]
+pub struct DrupalRecipe;
+
+impl DrupalRecipe {
+    /// The folder (relative to the project root) that's served by nginx
+    pub const DOCROOT: &'static str = "web";
+}
+
+impl<'a, 'b> Recipe<'a, 'b> for DrupalRecipe {}
+
+impl ValidateRecipe for DrupalRecipe {
+    fn validate(&self, ctx: &Context) -> Task {
+        match Composer::from_ctx(&ctx) {
+            Ok(c) => Task::Seq(vec![
+                c.exists_task(),
+                DrupalSettingsLocal::include_warn_task(ctx),
+            ]),
+            Err(e) => Task::notify_error(e.to_string()),
+        }
+    }
+}
+
+impl DcTasksTrait for DrupalRecipe {
+    fn volumes(&self, ctx: &Context) -> Vec<DcVolume> {
+        get_volumes(ctx)
+    }
+    fn proxy_service(&self) -> Option<(&'static str, u32)> {
+        Some((DrupalNginxService::NAME, 80))
+    }
+    fn services(&self, ctx: &Context) -> Result<Box<dyn Services>, failure::Error> {
+        let services = DrupalServices::from_ctx(ctx)?;
+        Ok(Box::new(services))
+    }
+}
+
+impl<'a, 'b> Commands<'a, 'b> for DrupalRecipe {
+    fn subcommands(&self, _ctx: &Context) -> Vec<Box<dyn CliCommand<'a, 'b>>> {
+        drupal_recipe_subcommands()
+    }
+}
+
+impl PassThru for DrupalRecipe {
+    fn resolve(&self, ctx: &Context, cmd: &PassThruCmd) -> Option<Vec<Task>> {
+        DrupalPassThru::resolve_cmd(&ctx, cmd.cmd.to_string(), &cmd.trailing)
+    }
+    fn names(&self, _ctx: &Context) -> Vec<(String, String)> {
+        DrupalPassThru::commands()
+    }
+}
+
+impl ResolveScript for DrupalRecipe {}
+
+impl AppServices for DrupalRecipe {
+    fn app_service(&self, ctx: &Context) -> Result<DcService, failure::Error> {
+        Ok(DrupalPhpService::select(&ctx))
+    }
+    fn app_root(&self) -> &'static str {
+        DrupalServices::ROOT
+    }
+    fn db_service(&self, ctx: &Context) -> Result<DbCredentials, failure::Error> {
+        Ok(DbCredentials::new(
+            (DrupalDbService).dc_service(&ctx, &()),
+            DbService::DB_USER,
+            DbService::DB_PASS,
+            DbService::DB_NAME,
+        ))
+    }
+}
@@ -0,0 +1,26 @@
+use crate::context::Context;
+use crate::file::File;
+
+use crate::output_files::OutputFiles;
+use crate::proxy::SharedProxy;
+use crate::recipes::drupal::DrupalRecipe;
+use crate::task::Task;
+use nginx::{DrupalNginxConf, DrupalNginxHost};
+use settings_local::DrupalSettingsLocal;
+
+pub mod nginx;
+pub mod settings_local;
+
+impl OutputFiles for DrupalRecipe {
+    fn output_files(&self, ctx: &Context) -> Result<Vec<Task>, failure::Error> {
+        let mut files = vec![
+            DrupalNginxConf::from_ctx(&ctx)?.write_task(),
+            DrupalNginxHost::from_ctx(&ctx)?.write_task(),
+            DrupalSettingsLocal::from_ctx(&ctx)?.write_task(),
+        ];
+        if SharedProxy::enabled(ctx) {
+            files.extend(SharedProxy::from_home()?.project_tasks(ctx));
+        }
+        Ok(files)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::context::Context;
+use crate::file::File;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct DrupalNginxConf {
+    file_path: PathBuf,
+}
+
+impl DrupalNginxConf {
+    pub const REMOTE: &'static str = "/etc/nginx/nginx.conf";
+}
+
+impl File<DrupalNginxConf> for DrupalNginxConf {
+    const DESCRIPTION: &'static str = "Writes the nginx conf file";
+    const HOST_OUTPUT_PATH: &'static str = "nginx/nginx.conf";
+
+    fn from_ctx(ctx: &Context) -> Result<DrupalNginxConf, failure::Error> {
+        Ok(DrupalNginxConf {
+            file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
+        })
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.file_path.clone()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        include_bytes!("./nginx.conf").to_vec()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DrupalNginxHost {
+    file_path: PathBuf,
+}
+
+impl DrupalNginxHost {
+    pub const REMOTE: &'static str = "/etc/nginx/conf.d/default.conf";
+}
+
+impl File<DrupalNginxHost> for DrupalNginxHost {
+    const DESCRIPTION: &'static str = "Writes the nginx host file";
+    const HOST_OUTPUT_PATH: &'static str = "nginx/host.conf";
+
+    fn from_ctx(ctx: &Context) -> Result<DrupalNginxHost, failure::Error> {
+        Ok(DrupalNginxHost {
+            file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
+        })
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.file_path.clone()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        include_bytes!("./host.conf").to_vec()
+    }
+}
@@ -0,0 +1,204 @@
+use crate::context::Context;
+use crate::file::File;
+use crate::recipes::drupal::services::drupal_db::DrupalDbService;
+use crate::recipes::drupal::services::{DrupalRecipeOptions, DrupalServices};
+use crate::recipes::drupal::DrupalRecipe;
+use crate::recipes::local_domain::LocalDomain;
+use crate::recipes::m2::services::db::DbService;
+use crate::services::redis::RedisService;
+use crate::services::Service;
+use crate::task::Task;
+use ansi_term::Colour::Yellow;
+use futures::future::lazy;
+use std::fs;
+use std::path::PathBuf;
+
+///
+/// A `settings.local.php` that's mounted into the PHP containers.
+///
+/// Drupal's `settings.php` only includes it once this block at the end of it is
+/// uncommented, otherwise none of these settings take effect:
+///
+/// ```php
+/// if (file_exists($app_root . '/' . $site_path . '/settings.local.php')) {
+///   include $app_root . '/' . $site_path . '/settings.local.php';
+/// }
+/// ```
+///
+/// `wf2 up` warns when it's still commented out.
+///
+#[derive(Debug, Clone)]
+pub struct DrupalSettingsLocal {
+    file_path: PathBuf,
+    domain: String,
+    redis: bool,
+}
+
+impl DrupalSettingsLocal {
+    pub const FILE_NAME: &'static str = "settings.local.php";
+
+    pub fn remote_path() -> String {
+        format!(
+            "{}/{}/sites/default/settings.local.php",
+            DrupalServices::ROOT,
+            DrupalRecipe::DOCROOT
+        )
+    }
+
+    ///
+    /// The project's `settings.php` is read at run time, since it may not exist until
+    /// Drupal is installed - in which case there's nothing to warn about yet
+    ///
+    pub fn include_warn_task(ctx: &Context) -> Task {
+        let settings = ctx
+            .cwd
+            .join(DrupalRecipe::DOCROOT)
+            .join("sites/default/settings.php");
+        Task::Exec {
+            description: Some(format!(
+                "check that settings.php includes {}",
+                DrupalSettingsLocal::FILE_NAME
+            )),
+            exec: Box::new(lazy(move || {
+                let content = fs::read_to_string(&settings).unwrap_or_default();
+                if !content.is_empty() && !DrupalSettingsLocal::is_included(&content) {
+                    println!(
+                        "{}: {} doesn't include {}, so the database & other local settings won't be used.\nUncomment the `settings.local.php` block at the end of it.",
+                        Yellow.paint("[wf2 warning]"),
+                        settings.display(),
+                        DrupalSettingsLocal::FILE_NAME
+                    );
+                }
+                Ok(())
+            })),
+        }
+    }
+
+    ///
+    /// `true` when an uncommented line includes the file
+    ///
+    pub fn is_included(settings_php: &str) -> bool {
+        settings_php.lines().map(str::trim).any(|line| {
+            !line.starts_with('#')
+                && !line.starts_with("//")
+                && !line.starts_with('*')
+                && line.contains("include")
+                && line.contains(DrupalSettingsLocal::FILE_NAME)
+        })
+    }
+}
+
+impl File<DrupalSettingsLocal> for DrupalSettingsLocal {
+    const DESCRIPTION: &'static str = "Writes the Drupal settings.local.php file";
+    const HOST_OUTPUT_PATH: &'static str = "drupal/settings.local.php";
+
+    fn from_ctx(ctx: &Context) -> Result<DrupalSettingsLocal, failure::Error> {
+        let opts = DrupalRecipeOptions::from_ctx(&ctx)?;
+        Ok(DrupalSettingsLocal {
+            file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
+            domain: LocalDomain::from_ctx(&ctx),
+            redis: opts.redis_enabled(),
+        })
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.file_path.clone()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        let host = self.domain.split(':').next().unwrap_or("localhost");
+        let mut output = format!(
+            r#"<?php
+// This file is generated by wf2, any changes will be overwritten
+
+$databases['default']['default'] = [
+  'database' => '{db}',
+  'username' => '{user}',
+  'password' => '{pass}',
+  'host' => '{db_host}',
+  'port' => '3306',
+  'driver' => 'mysql',
+  'prefix' => '',
+  'collation' => 'utf8mb4_general_ci',
+];
+
+$settings['trusted_host_patterns'] = ['^{host}$'];
+$settings['hash_salt'] = 'wf2-local-development';
+$settings['file_private_path'] = '../private';
+$config['system.mail']['interface']['default'] = 'php_mail';
+"#,
+            db = DbService::DB_NAME,
+            user = DbService::DB_USER,
+            pass = DbService::DB_PASS,
+            db_host = DrupalDbService::NAME,
+            host = host.replace('.', "\\."),
+        );
+
+        if self.redis {
+            output.push_str(&format!(
+                r#"
+$settings['redis.connection']['interface'] = 'PhpRedis';
+$settings['redis.connection']['host'] = '{redis_host}';
+$settings['cache']['default'] = 'cache.backend.redis';
+$settings['container_yamls'][] = 'modules/contrib/redis/example.services.yml';
+"#,
+                redis_host = RedisService::NAME,
+            ));
+        }
+
+        output.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_local_without_redis() -> Result<(), failure::Error> {
+        let ctx =
+            Context::new_from_str("{ recipe: Drupal, domains: [ 'acme.test' ] }").expect("test");
+        let file = DrupalSettingsLocal::from_ctx(&ctx)?;
+        let output = String::from_utf8(file.bytes())?;
+        assert!(output.contains("'host' => 'db',"));
+        assert!(output.contains(r#"['^acme\.test$']"#));
+        assert!(!output.contains("redis"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_local_with_redis() -> Result<(), failure::Error> {
+        let ctx_yaml = r#"
+        recipe: Drupal
+        options:
+          services:
+            redis:
+              enabled: true
+        "#;
+        let ctx = Context::new_from_str(ctx_yaml).expect("test");
+        let file = DrupalSettingsLocal::from_ctx(&ctx)?;
+        let output = String::from_utf8(file.bytes())?;
+        assert!(output.contains(r#"['^localhost$']"#));
+        assert!(output.contains("$settings['redis.connection']['host'] = 'redis';"));
+        assert!(output.contains(
+            "$settings['container_yamls'][] = 'modules/contrib/redis/example.services.yml';"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_included() {
+        let commented = r#"
+# if (file_exists($app_root . '/' . $site_path . '/settings.local.php')) {
+#   include $app_root . '/' . $site_path . '/settings.local.php';
+# }
+"#;
+        assert!(!DrupalSettingsLocal::is_included(commented));
+        let included = r#"
+if (file_exists($app_root . '/' . $site_path . '/settings.local.php')) {
+  include $app_root . '/' . $site_path . '/settings.local.php';
+}
+"#;
+        assert!(DrupalSettingsLocal::is_included(included));
+    }
+}
@@ -0,0 +1,130 @@
+//!
+//! pass-thru commands for the Drupal recipe
+//!
+//! # Example: clear all caches with `drush`
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 drush cr
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::DRUPAL_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # let expected = "docker exec -it -u www-data -e COLUMNS=\"80\" -e LINES=\"30\" wf2__shane__php vendor/bin/drush cr";
+//! # assert_eq!(commands, vec![expected]);
+//! ```
+use crate::context::Context;
+use crate::recipes::drupal::services::drupal_php::DrupalPhpService;
+use crate::recipes::m2::subcommands::composer::composer;
+use crate::subcommands::dc::dc_passthru;
+use crate::task::Task;
+use std::cmp;
+
+///
+/// Enum to represent all of the possible pass-thru commands available
+///
+#[derive(Debug, Clone)]
+pub enum DrupalPassThru {
+    Composer,
+    Dc,
+    Drush,
+}
+
+impl DrupalPassThru {
+    ///
+    /// Passthru command names
+    ///
+    const COMPOSER: &'static str = "composer";
+    const DC: &'static str = "dc";
+    const DRUSH: &'static str = "drush";
+
+    ///
+    /// Helper method for converting an enum member to a String
+    ///
+    pub fn name(&self) -> String {
+        match self {
+            DrupalPassThru::Composer => DrupalPassThru::COMPOSER,
+            DrupalPassThru::Dc => DrupalPassThru::DC,
+            DrupalPassThru::Drush => DrupalPassThru::DRUSH,
+        }
+        .to_string()
+    }
+    pub fn resolve_cmd(ctx: &Context, cmd: String, trailing: &[String]) -> Option<Vec<Task>> {
+        match cmd {
+            ref x if *x == DrupalPassThru::Dc => {
+                let res = dc_passthru(ctx, trailing);
+                Some(res.unwrap_or_else(Task::task_err_vec))
+            }
+            ref x if *x == DrupalPassThru::Drush => Some(drush(&ctx, trailing)),
+            ref x if *x == DrupalPassThru::Composer => Some(composer(&ctx, trailing)),
+            _ => None,
+        }
+    }
+
+    pub fn commands() -> Vec<(String, String)> {
+        vec![
+            (
+                DrupalPassThru::Composer,
+                "[drupal] Run composer commands with the correct user",
+            ),
+            (DrupalPassThru::Dc, "[drupal] Run docker-compose commands"),
+            (
+                DrupalPassThru::Drush,
+                "[drupal] Execute drush commands inside the PHP container",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, help)| (name.into(), help.into()))
+        .collect()
+    }
+}
+
+///
+/// Run `drush` from the project's vendor folder, inside the PHP container
+///
+pub fn drush(ctx: &Context, trailing: &[String]) -> Vec<Task> {
+    let service = DrupalPhpService::select(&ctx);
+    let full_command = format!(
+        r#"docker exec -it -u www-data -e COLUMNS="{width}" -e LINES="{height}" {container_name} vendor/bin/drush {trailing_args}"#,
+        width = ctx.term.width,
+        height = ctx.term.height,
+        container_name = service.container_name,
+        trailing_args = trailing
+            .iter()
+            .skip(1)
+            .map(String::from)
+            .collect::<Vec<String>>()
+            .join(" ")
+    );
+    vec![Task::simple_command(full_command)]
+}
+
+impl From<DrupalPassThru> for String {
+    fn from(p: DrupalPassThru) -> Self {
+        p.name()
+    }
+}
+
+///
+/// Allow a comparison to String
+///
+/// ```
+/// use wf2_core::recipes::drupal::pass_thru::DrupalPassThru;
+/// assert_eq!(true, DrupalPassThru::Drush == String::from("drush"));
+/// ```
+///
+impl cmp::PartialEq<String> for DrupalPassThru {
+    fn eq(&self, other: &String) -> bool {
+        self.name() == *other
+    }
+}
+
+impl cmp::PartialEq<DrupalPassThru> for String {
+    fn eq(&self, other: &DrupalPassThru) -> bool {
+        *self == other.name()
+    }
+}
@@ -0,0 +1,31 @@
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::recipes::drupal::volumes::DrupalVolumes;
+use crate::recipes::m2::services::db::DbService;
+use crate::services::Service;
+
+pub struct DrupalDbService;
+
+impl Service for DrupalDbService {
+    const NAME: &'static str = "db";
+    const IMAGE: &'static str = "mysql:5.7";
+
+    fn dc_service(&self, ctx: &Context, _vars: &()) -> DcService {
+        DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
+            .set_volumes(vec![format!(
+                "{}:{}",
+                DrupalVolumes::DB,
+                DbService::VOLUME_DATA
+            )])
+            .set_environment(vec![
+                format!("MYSQL_DATABASE={}", DbService::DB_NAME),
+                format!("MYSQL_USER={}", DbService::DB_USER),
+                format!("MYSQL_PASSWORD={}", DbService::DB_PASS),
+                format!("MYSQL_ROOT_PASSWORD={}", DbService::DB_PASS),
+            ])
+            .set_command("--default-authentication-plugin=mysql_native_password")
+            .set_ports(vec!["3306:3306"])
+            .set_restart("unless-stopped")
+            .finish()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::file::File;
+use crate::recipes::drupal::output_files::nginx::{DrupalNginxConf, DrupalNginxHost};
+use crate::recipes::drupal::services::drupal_php::DrupalPhpService;
+use crate::recipes::drupal::services::DrupalServices;
+use crate::recipes::local_domain::LocalDomain;
+use crate::services::nginx::NginxService;
+use crate::services::Service;
+
+pub struct DrupalNginxService;
+
+impl Service for DrupalNginxService {
+    const NAME: &'static str = NginxService::NAME;
+    const IMAGE: &'static str = NginxService::IMAGE;
+
+    fn dc_service(&self, ctx: &Context, _vars: &()) -> DcService {
+        let host_port = LocalDomain::port(&ctx);
+        DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
+            .set_depends_on(vec![DrupalPhpService::NAME])
+            .set_volumes(vec![
+                format!("{}:{}", ctx.cwd.display(), DrupalServices::ROOT),
+                format!(
+                    "{}:{}",
+                    ctx.output_file_path(DrupalNginxConf::HOST_OUTPUT_PATH)
+                        .display(),
+                    DrupalNginxConf::REMOTE
+                ),
+                format!(
+                    "{}:{}",
+                    ctx.output_file_path(DrupalNginxHost::HOST_OUTPUT_PATH)
+                        .display(),
+                    DrupalNginxHost::REMOTE
+                ),
+            ])
+            .set_working_dir(DrupalServices::ROOT)
+            .set_ports(vec![format!("{}:80", host_port)])
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drupal_nginx_port() {
+        let ctx = Context::new_from_str("{ recipe: Drupal, domains: [ 'localhost:8888' ] }")
+            .expect("test");
+        let actual_dc = (DrupalNginxService).dc_service(&ctx, &());
+        assert_eq!(actual_dc.ports, Some(vec![String::from("8888:80")]));
+    }
+}
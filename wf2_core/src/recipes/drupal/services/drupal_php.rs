@@ -0,0 +1,98 @@
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::file::File;
+use crate::recipes::drupal::output_files::settings_local::DrupalSettingsLocal;
+use crate::recipes::drupal::services::drupal_db::DrupalDbService;
+use crate::recipes::drupal::services::drupal_php_debug::DrupalPhpDebugService;
+use crate::recipes::drupal::services::{DrupalRecipeOptions, DrupalServices};
+use crate::recipes::local_domain::LocalDomain;
+use crate::recipes::m2::services::php::PhpService;
+use crate::services::mail::MailService;
+use crate::services::redis::RedisService;
+use crate::services::Service;
+
+pub struct DrupalPhpService;
+
+impl DrupalPhpService {
+    ///
+    /// Select either the regular PHP container, or the one with XDEBUG
+    /// enabled when `--debug` was given
+    ///
+    pub fn select(ctx: &Context) -> DcService {
+        let opts = DrupalRecipeOptions::from_ctx(&ctx).unwrap_or_default();
+        if ctx.debug {
+            (DrupalPhpDebugService).dc_service(ctx, &opts)
+        } else {
+            (DrupalPhpService).dc_service(ctx, &opts)
+        }
+    }
+}
+
+impl Service<DrupalRecipeOptions> for DrupalPhpService {
+    const NAME: &'static str = "php";
+    const IMAGE: &'static str = PhpService::IMAGE;
+
+    fn dc_service(&self, ctx: &Context, opts: &DrupalRecipeOptions) -> DcService {
+        let domain = LocalDomain::from_ctx(&ctx);
+        let php_image = (PhpService).select_image(&ctx);
+        let settings_local = format!(
+            "{}:{}",
+            ctx.output_file_path(DrupalSettingsLocal::HOST_OUTPUT_PATH)
+                .display(),
+            DrupalSettingsLocal::remote_path()
+        );
+        let mut service = DcService::new(ctx.name(), Self::NAME, php_image)
+            .set_volumes(vec![
+                format!("{}:{}", ctx.cwd.display(), DrupalServices::ROOT),
+                settings_local,
+            ])
+            .set_depends_on(vec![DrupalDbService::NAME])
+            .set_working_dir(DrupalServices::ROOT)
+            .set_environment(vec![
+                "XDEBUG_CONFIG=remote_host=host.docker.internal",
+                &format!("PHP_IDE_CONFIG=serverName={}", domain),
+                &format!("PHP_MEMORY_LIMIT=\"{}\"", "2G"),
+                &format!("MAIL_HOST={}", MailService::NAME),
+                "MAIL_PORT=1025",
+            ])
+            .finish();
+
+        if opts.redis_enabled() {
+            service.add_depends_on(vec![RedisService::NAME]);
+        }
+
+        service
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_service::DcService;
+
+    #[test]
+    fn test_drupal_php_service() {
+        let ctx = Context::new_from_str("recipe: Drupal").expect("test");
+        let actual_dc = (DrupalPhpService).dc_service(&ctx, &DrupalRecipeOptions::default());
+        let expected = r#"
+
+            name: php
+            container_name: wf2__wf2_default__php
+            image: "wearejh/php:7.3-m2"
+            volumes:
+              - ".:/var/www"
+              - "./.wf2_drupal_wf2_default/drupal/settings.local.php:/var/www/web/sites/default/settings.local.php"
+            depends_on:
+              - db
+            working_dir: /var/www
+            environment:
+              - XDEBUG_CONFIG=remote_host=host.docker.internal
+              - "PHP_IDE_CONFIG=serverName=localhost:8080"
+              - "PHP_MEMORY_LIMIT=\"2G\""
+              - MAIL_HOST=mail
+              - MAIL_PORT=1025
+        "#;
+        let expected_dc: DcService = serde_yaml::from_str(expected).expect("test yaml");
+        assert_eq!(actual_dc, expected_dc);
+    }
+}
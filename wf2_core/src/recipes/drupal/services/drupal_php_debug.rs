@@ -0,0 +1,22 @@
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::recipes::drupal::services::drupal_php::DrupalPhpService;
+use crate::recipes::drupal::services::DrupalRecipeOptions;
+use crate::recipes::m2::services::php::PhpService;
+use crate::services::Service;
+
+pub struct DrupalPhpDebugService;
+
+impl Service<DrupalRecipeOptions> for DrupalPhpDebugService {
+    const NAME: &'static str = "php-debug";
+    const IMAGE: &'static str = PhpService::IMAGE;
+
+    fn dc_service(&self, ctx: &Context, opts: &DrupalRecipeOptions) -> DcService {
+        (DrupalPhpService)
+            .dc_service(ctx, opts)
+            .set_container_name(ctx.name(), Self::NAME)
+            .set_name(Self::NAME)
+            .set_environment(vec!["XDEBUG_ENABLE=true"])
+            .finish()
+    }
+}
@@ -0,0 +1,126 @@
+//!
+//! Drupal Services
+//!
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::services::mail::MailService;
+use crate::services::redis::RedisService;
+use crate::services::{Service, Services};
+use failure::ResultExt;
+
+use drupal_db::DrupalDbService;
+use drupal_nginx::DrupalNginxService;
+use drupal_php::DrupalPhpService;
+use drupal_php_debug::DrupalPhpDebugService;
+
+pub mod drupal_db;
+pub mod drupal_nginx;
+pub mod drupal_php;
+pub mod drupal_php_debug;
+
+pub struct DrupalServices {
+    pub services: Vec<DcService>,
+}
+
+impl DrupalServices {
+    pub const ROOT: &'static str = "/var/www";
+
+    pub fn from_ctx(ctx: &Context) -> Result<Self, failure::Error> {
+        let opts = DrupalRecipeOptions::from_ctx(ctx)?;
+        let redis = opts.redis_enabled();
+        let mut services = vec![
+            (DrupalNginxService).dc_service(ctx, &()),
+            (DrupalPhpService).dc_service(ctx, &opts),
+            (DrupalPhpDebugService).dc_service(ctx, &opts),
+            (DrupalDbService).dc_service(ctx, &()),
+            (MailService).dc_service(ctx, &()),
+        ];
+
+        if redis {
+            services.push((RedisService).dc_service(ctx, &()));
+        }
+
+        Ok(Self { services })
+    }
+}
+
+impl Services for DrupalServices {
+    fn dc_services(&self) -> Vec<DcService> {
+        self.services.clone()
+    }
+}
+
+///
+/// These are the options that can be provided in the wf2 file
+/// under 'options.services.redis'
+///
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DrupalRedisOptions {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DrupalServicesOptions {
+    pub redis: Option<DrupalRedisOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DrupalRecipeOptions {
+    pub services: Option<DrupalServicesOptions>,
+}
+
+impl DrupalRecipeOptions {
+    pub fn from_ctx(ctx: &Context) -> Result<Self, failure::Error> {
+        if ctx.options.is_none() {
+            return Ok(DrupalRecipeOptions::default());
+        }
+        let opts = ctx
+            .parse_options::<DrupalRecipeOptions>()
+            .with_context(|e| format!("Couldn't parse options from wf2.yaml: {}", e))?;
+        Ok(opts)
+    }
+    pub fn redis_enabled(&self) -> bool {
+        self.services
+            .as_ref()
+            .and_then(|s| s.redis.as_ref())
+            .map_or(false, |r| r.enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drupal_services_without_redis() {
+        let ctx = Context::new_from_str("recipe: Drupal").expect("test");
+        let services = DrupalServices::from_ctx(&ctx).expect("test");
+        assert!(services.service_by_name(RedisService::NAME).is_none());
+        assert!(services.service_by_name(MailService::NAME).is_some());
+    }
+
+    #[test]
+    fn test_drupal_services_with_redis() {
+        let ctx_yaml = r#"
+        recipe: Drupal
+        options:
+          services:
+            redis:
+              enabled: true
+        "#;
+        let ctx = Context::new_from_str(ctx_yaml).expect("test");
+        let services = DrupalServices::from_ctx(&ctx).expect("test");
+        let php = services
+            .service_by_name(DrupalPhpService::NAME)
+            .expect("test");
+        assert!(services.service_by_name(RedisService::NAME).is_some());
+        assert_eq!(
+            php.depends_on.map(|d| d.names()),
+            Some(vec![
+                String::from(DrupalDbService::NAME),
+                String::from(RedisService::NAME)
+            ])
+        );
+    }
+}
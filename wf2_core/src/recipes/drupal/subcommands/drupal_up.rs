@@ -0,0 +1,114 @@
+//!
+//! Start the Drupal containers
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 up
+//! # "#;
+//! # let (commands, (_read, write, delete)) = Test::from_cmd(cmd)
+//! #   .with_recipe(RecipeKinds::DRUPAL_NAME)
+//! #   .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #   .file_ops_paths_commands();
+//! # assert_eq!(
+//! #     write,
+//! #     vec![
+//! #         "/users/shane/.wf2_drupal_shane/nginx/nginx.conf",
+//! #         "/users/shane/.wf2_drupal_shane/nginx/host.conf",
+//! #         "/users/shane/.wf2_drupal_shane/drupal/settings.local.php",
+//! #     ]
+//! # );
+//! # assert_eq!(delete, vec!["/users/shane/.wf2_drupal_shane"]);
+//! # assert_eq!(commands, vec!["docker-compose -f /users/shane/.wf2_drupal_shane/docker-compose.yml up -d"]);
+//! ```
+use crate::commands::CliCommand;
+use crate::context::Context;
+
+use crate::dc_tasks::DcTasksTrait;
+use crate::hosts::HostEntries;
+use crate::output_files::OutputFiles;
+use crate::proxy::SharedProxy;
+use crate::recipes::drupal::DrupalRecipe;
+use crate::recipes::validate::ValidateRecipe;
+use crate::task::Task;
+use crate::tasks::port_check::port_checked_up;
+use clap::{App, ArgMatches};
+use structopt::StructOpt;
+
+pub struct DrupalUp;
+
+impl DrupalUp {
+    const NAME: &'static str = "up";
+    const ABOUT: &'static str = "[drupal] Bring up Drupal containers";
+}
+
+#[derive(StructOpt)]
+struct Opts {
+    #[structopt(short, long)]
+    attached: bool,
+    #[structopt(short, long)]
+    clean: bool,
+    #[structopt(long)]
+    remap_ports: bool,
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for DrupalUp {
+    fn name(&self) -> String {
+        String::from(DrupalUp::NAME)
+    }
+    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        let opts: Opts = matches.map(Opts::from_clap).expect("guarded by Clap");
+        Some(
+            up(&ctx, opts.clean, opts.attached, opts.remap_ports)
+                .unwrap_or_else(Task::task_err_vec),
+        )
+    }
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(DrupalUp::NAME)
+            .about(DrupalUp::ABOUT)
+            .arg_from_usage("-a --attached 'Run in attached mode (streaming logs)'")
+            .arg_from_usage("-c --clean 'stop & remove other containers before starting new ones'")
+            .arg_from_usage("--remap-ports 'move services to free ports if theirs are taken'")]
+    }
+}
+
+fn up(
+    ctx: &Context,
+    clean: bool,
+    attached: bool,
+    remap_ports: bool,
+) -> Result<Vec<Task>, failure::Error> {
+    let recipe = DrupalRecipe;
+
+    let validate = vec![recipe.validate(&ctx)];
+    let clean_dir = vec![Task::dir_remove(
+        ctx.output_dir(),
+        "clean the output directory",
+    )];
+    let output_files = recipe.output_files(&ctx)?;
+    let (dc, dc_tasks) = recipe.dc_and_tasks(&ctx)?;
+    let clean_docker_containers_task = SharedProxy::clean_tasks(ctx, clean);
+    let proxy_network = SharedProxy::project_up_tasks(ctx);
+
+    let up_args = if attached { "up" } else { "up -d" };
+    let up_tasks = port_checked_up(ctx, &dc, &dc_tasks, up_args, remap_ports);
+
+    let hosts_task = if !attached {
+        HostEntries::from_ctx(ctx)?.warn_task(HostEntries::DEFAULT_PATH)
+    } else {
+        Task::Noop
+    };
+
+    Ok(vec![]
+        .into_iter()
+        .chain(validate.into_iter())
+        .chain(clean_dir.into_iter())
+        .chain(output_files.into_iter())
+        .chain(clean_docker_containers_task.into_iter())
+        .chain(proxy_network.into_iter())
+        .chain(up_tasks.into_iter())
+        .chain(vec![hosts_task].into_iter())
+        .collect())
+}
@@ -0,0 +1,33 @@
+use crate::commands::CliCommand;
+
+use crate::subcommands::db_dump::DbDump;
+use crate::subcommands::db_import::DbImport;
+use crate::subcommands::down::DcDown;
+use crate::subcommands::exec::Exec;
+use crate::subcommands::hosts::HostsCmd;
+use crate::subcommands::list_images::ListImages;
+use crate::subcommands::pull::Pull;
+use crate::subcommands::push::Push;
+use crate::subcommands::sql::SqlCmd;
+use crate::subcommands::stop::DcStop;
+use crate::subcommands::update_images::UpdateImages;
+use drupal_up::DrupalUp;
+
+pub mod drupal_up;
+
+pub fn drupal_recipe_subcommands<'a, 'b>() -> Vec<Box<dyn CliCommand<'a, 'b>>> {
+    vec![
+        Box::new(DrupalUp),
+        Box::new(DcStop),
+        Box::new(DcDown),
+        Box::new(DbImport),
+        Box::new(DbDump),
+        Box::new(SqlCmd),
+        Box::new(Exec),
+        Box::new(Pull),
+        Box::new(Push),
+        Box::new(ListImages),
+        Box::new(UpdateImages),
+        Box::new(HostsCmd),
+    ]
+}
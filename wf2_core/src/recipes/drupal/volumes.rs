@@ -0,0 +1,12 @@
+use crate::context::Context;
+use crate::dc_volume::DcVolume;
+
+pub struct DrupalVolumes;
+
+impl DrupalVolumes {
+    pub const DB: &'static str = "db-data";
+}
+
+pub fn get_volumes(ctx: &Context) -> Vec<DcVolume> {
+    vec![DcVolume::new(ctx.name(), DrupalVolumes::DB)]
+}
@@ -0,0 +1,44 @@
+//!
+//! The domain that recipes without Traefik (`Wp` + `Drupal`) are served on.
+//!
+//! nginx publishes the port itself, so the first entry in `domains` may contain one.
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::recipes::local_domain::LocalDomain;
+//! let ctx = Context::new_from_str("domains: [ acme.test:8081 ]").expect("can parse");
+//! assert_eq!(LocalDomain::from_ctx(&ctx), "acme.test:8081");
+//! assert_eq!(LocalDomain::port(&ctx), "8081");
+//!
+//! let ctx = Context::new_from_str("recipe: Drupal").expect("can parse");
+//! assert_eq!(LocalDomain::from_ctx(&ctx), "localhost:8080");
+//! ```
+//!
+use crate::context::Context;
+
+pub struct LocalDomain;
+
+impl LocalDomain {
+    pub const DEFAULT: &'static str = "localhost:8080";
+
+    ///
+    /// The first of `domains`, or [`LocalDomain::DEFAULT`]
+    ///
+    pub fn from_ctx(ctx: &Context) -> String {
+        ctx.domains
+            .first()
+            .cloned()
+            .unwrap_or_else(|| String::from(LocalDomain::DEFAULT))
+    }
+
+    ///
+    /// The port from the domain, or `80` when there isn't one
+    ///
+    pub fn port(ctx: &Context) -> String {
+        LocalDomain::from_ctx(ctx)
+            .split(':')
+            .nth(1)
+            .unwrap_or("80")
+            .to_string()
+    }
+}
@@ -2,20 +2,42 @@ use crate::context::Context;
 use crate::dc_tasks::DcTasksTrait;
 use crate::dc_volume::DcVolume;
 use crate::recipes::m2::m2_vars::M2Vars;
+use crate::recipes::m2::services::db::DbService;
+use crate::recipes::m2::services::redis::{M2RedisOptions, M2RedisService};
 use crate::recipes::m2::services::{M2RecipeOptions, M2Services};
 use crate::recipes::m2::M2Recipe;
-use crate::services::Services;
+use crate::services::elastic_search::ElasticSearchService;
+use crate::services::open_search::OpenSearchService;
+use crate::services::{Service, Services};
+use crate::versions::composer::COMPOSER;
+use crate::versions::search_engine::SearchEngine;
 use failure::ResultExt;
 
 impl DcTasksTrait for M2Recipe {
     fn volumes(&self, ctx: &Context) -> Vec<DcVolume> {
         let mut volumes = vec![
-            DcVolume::new(ctx.name(), M2Volumes::DB),
             DcVolume::new(ctx.name(), M2Volumes::APP),
-            DcVolume::new(ctx.name(), M2Volumes::COMPOSER_CACHE),
-            DcVolume::new(ctx.name(), M2Volumes::ELASTICSEARCH),
+            DcVolume::new(ctx.name(), COMPOSER::cache_volume_from_ctx(ctx)),
         ];
 
+        if !M2RecipeOptions::is_disabled(ctx, DbService::NAME) {
+            volumes.push(DcVolume::new(ctx.name(), M2Volumes::DB));
+        }
+
+        if SearchEngine::from_ctx(ctx).is_opensearch() {
+            if !M2RecipeOptions::is_disabled(ctx, OpenSearchService::NAME) {
+                volumes.push(DcVolume::new(ctx.name(), M2Volumes::OPENSEARCH));
+            }
+        } else if !M2RecipeOptions::is_disabled(ctx, ElasticSearchService::NAME) {
+            volumes.push(DcVolume::new(ctx.name(), M2Volumes::ELASTICSEARCH));
+        }
+
+        if !M2RecipeOptions::is_disabled(ctx, M2RedisService::NAME) {
+            for volume in M2RedisOptions::from_ctx(ctx).unwrap_or_default().volumes() {
+                volumes.push(DcVolume::new(ctx.name(), volume));
+            }
+        }
+
         if M2RecipeOptions::has_pwa_options(ctx) {
             volumes.push(DcVolume::new(ctx.name(), M2Volumes::PWA));
         }
@@ -29,7 +51,7 @@ impl DcTasksTrait for M2Recipe {
                 .parse_options::<M2RecipeOptions>()
                 .with_context(|e| format!("Couldn't parse options from wf2.yaml: {}", e))?;
         }
-        let m2_services = M2Services::from_ctx(ctx, &vars);
+        let m2_services = M2Services::from_ctx(ctx, &vars)?;
         Ok(Box::new(m2_services))
     }
 }
@@ -39,7 +61,7 @@ pub struct M2Volumes;
 impl M2Volumes {
     pub const APP: &'static str = "app-src";
     pub const DB: &'static str = "db-data";
-    pub const COMPOSER_CACHE: &'static str = "composer-cache";
     pub const ELASTICSEARCH: &'static str = "esdata";
+    pub const OPENSEARCH: &'static str = "osdata";
     pub const PWA: &'static str = "pwa-src";
 }
@@ -0,0 +1,179 @@
+//!
+//! Check the `php_version` against the Magento version installed in the project.
+//!
+//! The Magento version is read from `composer.lock`. When there isn't one yet,
+//! eg: before the first `composer install`, the check is skipped.
+//!
+//! ```txt
+//! Magento 2.3.5 supports PHP 7.1, 7.2, 7.3, but `php_version` is 8.1
+//! ```
+//!
+use crate::context::Context;
+use crate::php::PHP;
+use crate::task::Task;
+use futures::future::lazy;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Fail)]
+enum MagentoVersionError {
+    #[fail(
+        display = "Magento {} supports PHP {}, but `php_version` is {}",
+        magento, supported, php
+    )]
+    UnsupportedPhp {
+        magento: MagentoVersion,
+        supported: String,
+        php: PHP,
+    },
+}
+
+///
+/// `(minor, first patch, last patch)`
+///
+type PatchRange = (u32, u32, u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MagentoVersion {
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl MagentoVersion {
+    ///
+    /// Any of these packages give the installed version
+    ///
+    pub const PACKAGES: [&'static str; 3] = [
+        "magento/product-community-edition",
+        "magento/product-enterprise-edition",
+        "magento/magento2-base",
+    ];
+
+    ///
+    /// Which PHP versions each range of Magento versions supports
+    ///
+    const PHP_SUPPORT: [(PatchRange, &'static [PHP]); 6] = [
+        ((3, 0, 6), &[PHP::SevenOne, PHP::SevenTwo, PHP::SevenThree]),
+        ((3, 7, u32::MAX), &[PHP::SevenThree, PHP::SevenFour]),
+        ((4, 0, 3), &[PHP::SevenThree, PHP::SevenFour]),
+        ((4, 4, 5), &[PHP::SevenFour, PHP::EightOne]),
+        ((4, 6, 6), &[PHP::EightOne, PHP::EightTwo]),
+        ((4, 7, u32::MAX), &[PHP::EightTwo, PHP::EightThree]),
+    ];
+
+    ///
+    /// Parse `2.4.6`, `2.4.6-p3` or `v2.3.5`. Only Magento 2 is supported.
+    ///
+    pub fn parse(version: &str) -> Option<MagentoVersion> {
+        let mut parts = version
+            .trim_start_matches('v')
+            .split(|c| c == '.' || c == '-')
+            .map(|part| part.parse::<u32>().ok());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Some(2)), Some(Some(minor)), Some(Some(patch))) => {
+                Some(MagentoVersion { minor, patch })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn from_lock(lock: &str) -> Option<MagentoVersion> {
+        let lock: serde_json::Value = serde_json::from_str(lock).ok()?;
+        lock["packages"]
+            .as_array()?
+            .iter()
+            .find(|package| {
+                package["name"]
+                    .as_str()
+                    .map_or(false, |name| MagentoVersion::PACKAGES.contains(&name))
+            })
+            .and_then(|package| package["version"].as_str())
+            .and_then(MagentoVersion::parse)
+    }
+
+    ///
+    /// Empty for versions this table doesn't know about, which are never rejected
+    ///
+    pub fn supported_php(&self) -> &'static [PHP] {
+        MagentoVersion::PHP_SUPPORT
+            .iter()
+            .find(|((minor, from, to), _)| {
+                *minor == self.minor && self.patch >= *from && self.patch <= *to
+            })
+            .map_or(&[], |(_, php)| php)
+    }
+
+    pub fn check(&self, php: &PHP) -> Result<(), failure::Error> {
+        let supported = self.supported_php();
+        if supported.is_empty() || supported.contains(php) {
+            return Ok(());
+        }
+        Err(MagentoVersionError::UnsupportedPhp {
+            magento: self.clone(),
+            supported: supported
+                .iter()
+                .map(|php| php.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            php: php.clone(),
+        }
+        .into())
+    }
+
+    ///
+    /// Read `composer.lock` at run time, since it usually changes
+    /// between invocations
+    ///
+    pub fn php_check_task(ctx: &Context) -> Task {
+        let lock_path: PathBuf = ctx.cwd.join("composer.lock");
+        let php = ctx.php_version.clone();
+        Task::Exec {
+            description: Some(String::from("check php_version against composer.lock")),
+            exec: Box::new(lazy(move || {
+                match fs::read_to_string(&lock_path)
+                    .ok()
+                    .and_then(|lock| MagentoVersion::from_lock(&lock))
+                {
+                    Some(magento) => magento.check(&php),
+                    None => Ok(()),
+                }
+            })),
+        }
+    }
+}
+
+impl std::fmt::Display for MagentoVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "2.{}.{}", self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magento_php_check() {
+        let lock = r#"{
+            "packages": [
+                { "name": "laminas/laminas-code", "version": "3.4.1" },
+                { "name": "magento/product-community-edition", "version": "2.3.5-p2" }
+            ]
+        }"#;
+        let magento = MagentoVersion::from_lock(lock).expect("test");
+        assert_eq!(magento, MagentoVersion { minor: 3, patch: 5 });
+        assert!(magento.check(&PHP::SevenThree).is_ok());
+        assert_eq!(
+            magento.check(&PHP::EightOne).expect_err("test").to_string(),
+            "Magento 2.3.5 supports PHP 7.1, 7.2, 7.3, but `php_version` is 8.1"
+        );
+
+        let magento = MagentoVersion::parse("2.4.7").expect("test");
+        assert!(magento.check(&PHP::EightThree).is_ok());
+        assert!(magento.check(&PHP::SevenFour).is_err());
+
+        // unknown versions are never rejected
+        let magento = MagentoVersion::parse("2.5.0").expect("test");
+        assert!(magento.check(&PHP::EightZero).is_ok());
+    }
+}
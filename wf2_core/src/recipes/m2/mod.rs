@@ -1,16 +1,23 @@
 //!
 //!
 //!
+use crate::dc_service::DcService;
 use crate::file::File;
+use crate::recipes::app_services::{AppServices, DbCredentials};
+use crate::recipes::m2::magento_version::MagentoVersion;
 use crate::recipes::m2::output_files::auth::Auth;
 use crate::recipes::m2::output_files::composer::Composer;
-use crate::recipes::m2::services::M2RecipeOptions;
+use crate::recipes::m2::services::db::{DbService, DbServiceOptions};
+use crate::recipes::m2::services::php::PhpService;
+use crate::recipes::m2::services::{M2RecipeOptions, M2_ROOT};
 use crate::recipes::validate::ValidateRecipe;
+use crate::services::Service;
 use crate::{context::Context, recipes::Recipe, task::Task};
 
 pub mod dc_tasks;
 #[doc(hidden)]
 pub mod m2_vars;
+pub mod magento_version;
 pub mod multi_store;
 #[doc(hidden)]
 pub mod output_files;
@@ -22,11 +29,12 @@ pub mod services;
 pub mod subcommands;
 #[doc(hidden)]
 pub mod tasks;
+pub mod xdebug;
 
 ///
-/// PHP 7.1 + 7.2 + 7.3 Environments for use with Magento 2.
+/// PHP 7.1 - 8.3 Environments for use with Magento 2.
 ///
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct M2Recipe;
 
 impl<'a, 'b> Recipe<'a, 'b> for M2Recipe {}
@@ -34,7 +42,7 @@ impl<'a, 'b> Recipe<'a, 'b> for M2Recipe {}
 impl ValidateRecipe for M2Recipe {
     fn validate(&self, ctx: &Context) -> Task {
         let mut tasks = match (Composer::from_ctx(&ctx), Auth::from_ctx(&ctx)) {
-            (Ok(c), Ok(a)) => vec![c.exists_task(), a.exists_task()],
+            (Ok(c), Ok(a)) => vec![c.exists_task(), a.exists_task(), a.validate_task(ctx)],
             _ => vec![],
         };
 
@@ -43,6 +51,14 @@ impl ValidateRecipe for M2Recipe {
             .as_ref()
             .map_or(false, |opts| opts["services"]["pwa"].is_mapping());
 
+        if let Err(e) = DbServiceOptions::try_from_ctx(ctx)
+            .and_then(|db| db.validate(&ctx.php_version).map_err(failure::Error::from))
+        {
+            tasks.push(Task::notify_error(e.to_string()));
+        }
+
+        tasks.push(MagentoVersion::php_check_task(ctx));
+
         if attempt_pwa {
             if let Err(e) = ctx.parse_options::<M2RecipeOptions>() {
                 tasks.push(Task::notify_error(format!(
@@ -56,6 +72,31 @@ impl ValidateRecipe for M2Recipe {
     }
 }
 
+impl AppServices for M2Recipe {
+    fn app_service(&self, ctx: &Context) -> Result<DcService, failure::Error> {
+        PhpService::select(&ctx)
+    }
+    fn app_root(&self) -> &'static str {
+        M2_ROOT
+    }
+    fn synced_paths(&self) -> Vec<&'static str> {
+        vec!["app/"]
+    }
+    fn db_service(&self, ctx: &Context) -> Result<DbCredentials, failure::Error> {
+        let opts = DbServiceOptions::from_ctx(ctx);
+        DbService::from_ctx(ctx).map(|service| {
+            DbCredentials::new(
+                service,
+                DbService::DB_USER,
+                DbService::DB_PASS,
+                DbService::DB_NAME,
+            )
+            .set_clients(opts.client(), opts.dump_client())
+            .clone()
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::context::Context;
@@ -80,4 +121,59 @@ mod tests {
         let m2 = (M2Recipe).services(&ctx).expect("test services");
         assert!(m2.service_by_name(PwaService::NAME).is_some());
     }
+
+    #[test]
+    fn test_disable_optional_services() {
+        let ctx_yaml = r#"
+        recipe: M2
+        options:
+          services:
+            elasticsearch:
+              enabled: false
+            blackfire:
+              enabled: false
+        "#;
+        let ctx = Context::new_from_str(ctx_yaml).expect("test");
+        let dc = (M2Recipe).dc(&ctx).expect("test dc");
+        let names = dc.service_names().expect("test");
+        assert!(!names.contains(&String::from("elasticsearch")));
+        assert!(!names.contains(&String::from("blackfire")));
+        let volumes = dc.volumes.expect("test volumes");
+        assert!(!volumes.contains_key("esdata"));
+        assert!(volumes.contains_key("db-data"));
+    }
+
+    #[test]
+    fn test_disable_required_service() {
+        let ctx_yaml = r#"
+        recipe: M2
+        options:
+          services:
+            db:
+              enabled: false
+        "#;
+        let ctx = Context::new_from_str(ctx_yaml).expect("test");
+        let err = (M2Recipe).services(&ctx).err().expect("test");
+        assert_eq!(
+            err.to_string(),
+            "`php` depends on `db`, but `db` is disabled"
+        );
+    }
+
+    #[test]
+    fn test_disable_unknown_service() {
+        let ctx_yaml = r#"
+        recipe: M2
+        options:
+          services:
+            mongo:
+              enabled: false
+        "#;
+        let ctx = Context::new_from_str(ctx_yaml).expect("test");
+        let err = (M2Recipe).services(&ctx).err().expect("test");
+        assert_eq!(
+            err.to_string(),
+            "Cannot disable `mongo` since it's not a service in this recipe"
+        );
+    }
 }
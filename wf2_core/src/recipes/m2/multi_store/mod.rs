@@ -1,4 +1,74 @@
-#[derive(Clone, Debug, Deserialize)]
+//!
+//! Run several Magento stores or websites from one project
+//!
+//! Each store is found either by its own domain, or by a path prefix on the main domain.
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::m2::M2Recipe;
+//! # let input = r#"
+//! recipe: M2
+//! domains: [ acme.m2 ]
+//! stores:
+//!   ce.acme.m2:
+//!     mage_run_code: ce
+//!     mage_run_type: website
+//!   /de:
+//!     mage_run_code: de
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = (M2Recipe).dc(&ctx).expect("valid dc");
+//! # let varnish = dc.services.expect("services").get("varnish").cloned().expect("varnish");
+//! # assert_eq!(varnish.labels, Some(vec![String::from("traefik.frontend.rule=Host:acme.m2,ce.acme.m2")]));
+//! ```
+//!
+//! - `mage_run_code` is the store or website code
+//! - `mage_run_type` is either `store` (the default) or `website`
+//!
+//! The list form, with `domain` or `path_prefix` on each entry, is also supported
+//!
+//! ```yaml
+//! stores:
+//!   - domain: ce.acme.m2
+//!     mage_run_code: ce
+//!     mage_run_type: website
+//!   - path_prefix: /de
+//!     mage_run_code: de
+//!     mage_run_type: store
+//! ```
+//!
+//! Store domains are routed to the project along with `domains`, and the mapping is
+//! available to scripts in the `.docker.env` file, eg: `MAGE_STORES=ce.acme.m2=website:ce,/de=store:de`
+//!
+use crate::context::Context;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Debug, Fail)]
+pub enum StoreError {
+    #[fail(
+        display = "Store `{}` needs either `domain` or `path_prefix`, but not both",
+        _0
+    )]
+    DomainOrPath(String),
+    #[fail(display = "The `path_prefix` of store `{}` should start with `/`", _0)]
+    InvalidPath(String),
+    #[fail(
+        display = "Invalid `mage_run_code` `{}`, only lowercase letters, numbers & `_` are allowed",
+        _0
+    )]
+    InvalidCode(String),
+    #[fail(
+        display = "Invalid `mage_run_type` `{}` for store `{}`, expected either `store` or `website`",
+        _1, _0
+    )]
+    InvalidType(String, String),
+    #[fail(display = "`{}` is used by more than one store", _0)]
+    Duplicate(String),
+}
+
+#[derive(Clone, Debug)]
 pub struct Stores(pub Vec<Store>);
 
 ///
@@ -18,25 +88,320 @@ impl std::ops::DerefMut for Stores {
     }
 }
 
+///
+/// Allow either a list of stores, or a map keyed by domain or path
+///
+impl<'de> Deserialize<'de> for Stores {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match serde_yaml::Value::deserialize(deserializer)? {
+            serde_yaml::Value::Sequence(stores) => stores
+                .into_iter()
+                .map(|store| serde_yaml::from_value(store).map_err(D::Error::custom))
+                .collect::<Result<Vec<Store>, D::Error>>()
+                .map(Stores),
+            serde_yaml::Value::Mapping(stores) => stores
+                .into_iter()
+                .map(|(key, store)| {
+                    let key = key
+                        .as_str()
+                        .ok_or_else(|| D::Error::custom("store keys should be a domain or a path"))?
+                        .to_string();
+                    let store: Store = serde_yaml::from_value(store).map_err(D::Error::custom)?;
+                    if store.domain.is_some() || store.path_prefix.is_some() {
+                        return Err(D::Error::custom(format!(
+                            "store `{}` is already keyed, remove its `domain` or `path_prefix`",
+                            key
+                        )));
+                    }
+                    Ok(if key.starts_with('/') {
+                        Store {
+                            path_prefix: Some(key),
+                            ..store
+                        }
+                    } else {
+                        Store {
+                            domain: Some(key),
+                            ..store
+                        }
+                    })
+                })
+                .collect::<Result<Vec<Store>, D::Error>>()
+                .map(Stores),
+            _ => Err(D::Error::custom(
+                "expected a list of stores, or stores keyed by domain or path",
+            )),
+        }
+    }
+}
+
+impl Stores {
+    pub fn from_ctx(ctx: &Context) -> Stores {
+        ctx.stores.clone().unwrap_or_else(|| Stores(vec![]))
+    }
+
+    pub fn validate(&self) -> Result<(), StoreError> {
+        for (index, store) in self.iter().enumerate() {
+            store.validate()?;
+            let key = store.key();
+            if self[..index].iter().any(|prev| prev.key() == key) {
+                return Err(StoreError::Duplicate(key));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn domains(&self) -> Vec<String> {
+        self.iter()
+            .filter_map(|store| store.domain.clone())
+            .collect()
+    }
+
+    ///
+    /// Longest first, since nginx uses the first matching regex location
+    ///
+    pub fn path_stores(&self) -> Vec<&Store> {
+        let mut stores = self
+            .iter()
+            .filter(|store| store.path_prefix.is_some())
+            .collect::<Vec<&Store>>();
+        stores.sort_by_key(|store| std::cmp::Reverse(store.key().len()));
+        stores
+    }
+
+    pub fn domain_stores(&self) -> Vec<&Store> {
+        self.iter().filter(|store| store.domain.is_some()).collect()
+    }
+
+    ///
+    /// `domains` from the context, followed by any only given in `stores`
+    ///
+    pub fn all_domains(ctx: &Context) -> Vec<String> {
+        let mut domains = ctx.domains();
+        for domain in Stores::from_ctx(ctx).domains() {
+            if !domains.contains(&domain) {
+                domains.push(domain);
+            }
+        }
+        domains
+    }
+
+    ///
+    /// eg: `ce.acme.m2=website:ce,/de=store:de`
+    ///
+    pub fn env_mapping(&self) -> String {
+        self.iter()
+            .map(|store| {
+                format!(
+                    "{}={}:{}",
+                    store.key(),
+                    store.mage_run_type,
+                    store.mage_run_code
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    ///
+    /// `map` blocks that pick the run code & type from the request's host
+    ///
+    pub fn nginx_maps(&self) -> String {
+        let stores = self.domain_stores();
+        if stores.is_empty() {
+            return String::new();
+        }
+        let map = |variable: &str, default: &str, value: &dyn Fn(&Store) -> String| {
+            let entries = stores
+                .iter()
+                .map(|store| {
+                    format!(
+                        "    {} {};\n",
+                        store.domain.as_ref().expect("filtered above"),
+                        value(store)
+                    )
+                })
+                .collect::<String>();
+            format!(
+                "map $host {} {{\n    default {};\n{}}}\n",
+                variable, default, entries
+            )
+        };
+        format!(
+            "{}\n{}\n",
+            map("$MAGE_RUN_CODE", "\"\"", &|store| store
+                .mage_run_code
+                .clone()),
+            map("$MAGE_RUN_TYPE", "store", &|store| store
+                .mage_run_type
+                .clone())
+        )
+    }
+}
+
 ///
 /// Store struct represents a given store in M2
 ///
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct Store {
-    pub path_prefix: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub path_prefix: Option<String>,
     pub mage_run_code: String,
+    #[serde(default = "default_run_type")]
     pub mage_run_type: String,
 }
 
+fn default_run_type() -> String {
+    String::from(Store::RUN_TYPES[0])
+}
+
 ///
 /// Each store can be used to generate a partial nginx configuration file
 /// by replacing placeholders from a given template.
 ///
 impl Store {
+    pub const RUN_TYPES: [&'static str; 2] = ["store", "website"];
+
+    ///
+    /// The domain or path that selects this store
+    ///
+    pub fn key(&self) -> String {
+        self.domain
+            .clone()
+            .or_else(|| self.path_prefix.clone())
+            .unwrap_or_else(|| self.mage_run_code.clone())
+    }
+
+    pub fn validate(&self) -> Result<(), StoreError> {
+        match (&self.domain, &self.path_prefix) {
+            (Some(_), None) => {}
+            (None, Some(path)) if path.starts_with('/') && !path.trim_matches('/').is_empty() => {}
+            (None, Some(_)) => return Err(StoreError::InvalidPath(self.key())),
+            _ => return Err(StoreError::DomainOrPath(self.key())),
+        }
+        let valid_code = !self.mage_run_code.is_empty()
+            && self
+                .mage_run_code
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid_code {
+            return Err(StoreError::InvalidCode(self.mage_run_code.clone()));
+        }
+        if !Store::RUN_TYPES.contains(&self.mage_run_type.as_str()) {
+            return Err(StoreError::InvalidType(
+                self.key(),
+                self.mage_run_type.clone(),
+            ));
+        }
+        Ok(())
+    }
+
     pub fn process_template(&self, template: &str) -> String {
         template
-            .replace("{{path_prefix}}", &self.path_prefix.trim_end_matches('/'))
+            .replace(
+                "{{path_prefix}}",
+                self.path_prefix
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_end_matches('/'),
+            )
             .replace("{{mage_run_code}}", &self.mage_run_code)
             .replace("{{mage_run_type}}", &self.mage_run_type)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stores_from_file() {
+        let ctx = Context::new_from_file("../fixtures/config_multi-domain.yaml")
+            .expect("test")
+            .expect("test");
+        let stores = Stores::from_ctx(&ctx);
+        assert!(stores.validate().is_ok());
+        assert_eq!(
+            stores.domains(),
+            vec![String::from("ce.acme.m2"), String::from("ee.acme.m2")]
+        );
+        assert_eq!(
+            stores.env_mapping(),
+            "ce.acme.m2=website:ce,/de=store:de,/de/b2b=store:de_b2b,ee.acme.m2=website:ee"
+        );
+        let paths = stores
+            .path_stores()
+            .iter()
+            .map(|store| store.key())
+            .collect::<Vec<String>>();
+        assert_eq!(paths, vec!["/de/b2b", "/de"]);
+        assert_eq!(
+            Stores::all_domains(&ctx),
+            vec![
+                String::from("acme.m2"),
+                String::from("ce.acme.m2"),
+                String::from("ee.acme.m2")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stores_list() {
+        let ctx = Context::new_from_str(
+            r#"
+            stores:
+              - path_prefix: /en/
+                mage_run_code: en
+                mage_run_type: store
+            "#,
+        )
+        .expect("test");
+        let stores = Stores::from_ctx(&ctx);
+        assert!(stores.validate().is_ok());
+        assert_eq!(
+            stores[0].process_template("{{path_prefix}} {{mage_run_code}}"),
+            "/en en"
+        );
+    }
+
+    #[test]
+    fn test_stores_invalid() {
+        let invalid = |input: &str| {
+            let ctx = Context::new_from_str(input).expect("test");
+            Stores::from_ctx(&ctx)
+                .validate()
+                .expect_err("test")
+                .to_string()
+        };
+        assert_eq!(
+            invalid("stores: [ { mage_run_code: de } ]"),
+            "Store `de` needs either `domain` or `path_prefix`, but not both"
+        );
+        assert_eq!(
+            invalid("stores: { de: { mage_run_code: de }, /de: { mage_run_code: DE } }"),
+            "Invalid `mage_run_code` `DE`, only lowercase letters, numbers & `_` are allowed"
+        );
+        assert_eq!(
+            invalid("stores: { de.m2: { mage_run_code: de, mage_run_type: group } }"),
+            "Invalid `mage_run_type` `group` for store `de.m2`, expected either `store` or `website`"
+        );
+        assert_eq!(
+            invalid("stores: [ { path_prefix: de, mage_run_code: de_b2b } ]"),
+            "The `path_prefix` of store `de` should start with `/`"
+        );
+        assert_eq!(
+            invalid(
+                "stores: [ { path_prefix: /de, mage_run_code: de }, { path_prefix: /de, mage_run_code: at } ]"
+            ),
+            "`/de` is used by more than one store"
+        );
+        assert!(
+            Context::new_from_str("stores: { /de: { path_prefix: /at, mage_run_code: de } }")
+                .is_err()
+        );
+    }
+}
@@ -1,7 +1,25 @@
+//!
+//! `auth.json`, which must exist before `wf2 up`.
+//!
+//! Its shape is checked when the containers start, since Composer 1 rejects
+//! some entries that Composer 2 accepts, eg: a `gitlab-token` with a `username`.
+//!
 use crate::context::Context;
 use crate::file::File;
+use crate::task::Task;
+use crate::versions::composer::COMPOSER;
+use futures::future::lazy;
+use std::fs;
 use std::path::PathBuf;
 
+#[derive(Debug, Fail)]
+pub enum AuthError {
+    #[fail(display = "auth.json is not valid JSON\n{}", _0)]
+    Json(String),
+    #[fail(display = "auth.json: {}", _0)]
+    Invalid(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct Auth {
     file_path: PathBuf,
@@ -21,3 +39,222 @@ impl File<Auth> for Auth {
         self.file_path.clone()
     }
 }
+
+impl Auth {
+    pub const HTTP_BASIC: &'static str = "http-basic";
+
+    ///
+    /// Sections that map a host to a single token
+    ///
+    const TOKENS: [&'static str; 3] = ["github-oauth", "gitlab-oauth", "bearer"];
+
+    ///
+    /// The file is read at run time, a missing one is already
+    /// reported by [`File::exists_task`]
+    ///
+    pub fn validate_task(&self, ctx: &Context) -> Task {
+        let file_path = self.file_path();
+        let composer = ctx.composer_version.clone().unwrap_or_default();
+        Task::Exec {
+            description: Some(String::from("check the shape of auth.json")),
+            exec: Box::new(lazy(move || match fs::read_to_string(&file_path) {
+                Ok(content) => Auth::validate(&content, &composer).map_err(failure::Error::from),
+                Err(_) => Ok(()),
+            })),
+        }
+    }
+
+    pub fn validate(content: &str, composer: &COMPOSER) -> Result<(), AuthError> {
+        let json: serde_json::Value =
+            serde_json::from_str(content).map_err(|e| AuthError::Json(e.to_string()))?;
+        let sections = json
+            .as_object()
+            .ok_or_else(|| AuthError::Invalid(String::from("expected an object")))?;
+        for (section, hosts) in sections {
+            let hosts = hosts.as_object().ok_or_else(|| {
+                AuthError::Invalid(format!("`{}` should map hosts to credentials", section))
+            })?;
+            for (host, value) in hosts {
+                let path = format!("{}.{}", section, host);
+                match section.as_str() {
+                    "http-basic" => Auth::require_keys(&path, value, &["username", "password"])?,
+                    "bitbucket-oauth" => {
+                        Auth::require_keys(&path, value, &["consumer-key", "consumer-secret"])?
+                    }
+                    "gitlab-token" => match (value, composer) {
+                        (serde_json::Value::String(_), _) => {}
+                        (serde_json::Value::Object(_), COMPOSER::Two) => {
+                            Auth::require_keys(&path, value, &["username", "token"])?
+                        }
+                        (serde_json::Value::Object(_), COMPOSER::One) => {
+                            return Err(AuthError::Invalid(format!(
+                                "`{}` must be a token string for Composer 1",
+                                path
+                            )))
+                        }
+                        _ => {
+                            return Err(AuthError::Invalid(format!("`{}` should be a token", path)))
+                        }
+                    },
+                    section if Auth::TOKENS.contains(&section) && !value.is_string() => {
+                        return Err(AuthError::Invalid(format!(
+                            "`{}` should be a token string",
+                            path
+                        )));
+                    }
+                    // unknown sections are left for Composer to report
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Hosts with `http-basic` credentials, along with their usernames
+    ///
+    pub fn list(json: &serde_json::Value) -> Vec<(String, String)> {
+        json[Auth::HTTP_BASIC]
+            .as_object()
+            .map(|hosts| {
+                hosts
+                    .iter()
+                    .map(|(host, creds)| {
+                        (
+                            host.to_string(),
+                            creds["username"].as_str().unwrap_or_default().to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn credentials(json: &serde_json::Value, host: &str) -> Option<(String, String)> {
+        let creds = &json[Auth::HTTP_BASIC][host];
+        match (creds["username"].as_str(), creds["password"].as_str()) {
+            (Some(username), Some(password)) => Some((username.to_string(), password.to_string())),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Add or replace the `http-basic` credentials for `host`, keeping everything else
+    ///
+    pub fn set(
+        json: &mut serde_json::Value,
+        host: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<(), AuthError> {
+        if json.is_null() {
+            *json = serde_json::json!({});
+        }
+        let sections = json
+            .as_object_mut()
+            .ok_or_else(|| AuthError::Invalid(String::from("expected an object")))?;
+        let hosts = sections
+            .entry(Auth::HTTP_BASIC)
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(|| {
+                AuthError::Invalid(format!(
+                    "`{}` should map hosts to credentials",
+                    Auth::HTTP_BASIC
+                ))
+            })?;
+        hosts.insert(
+            host.to_string(),
+            serde_json::json!({ "username": username, "password": password }),
+        );
+        Ok(())
+    }
+
+    ///
+    /// `false` when there were no credentials for `host`
+    ///
+    pub fn remove(json: &mut serde_json::Value, host: &str) -> bool {
+        let removed = json
+            .get_mut(Auth::HTTP_BASIC)
+            .and_then(|hosts| hosts.as_object_mut())
+            .and_then(|hosts| hosts.remove(host))
+            .is_some();
+        let empty = json[Auth::HTTP_BASIC]
+            .as_object()
+            .map_or(false, |hosts| hosts.is_empty());
+        if let (true, Some(sections)) = (empty, json.as_object_mut()) {
+            sections.remove(Auth::HTTP_BASIC);
+        }
+        removed
+    }
+
+    fn require_keys(path: &str, value: &serde_json::Value, keys: &[&str]) -> Result<(), AuthError> {
+        match keys.iter().find(|key| !value[**key].is_string()) {
+            Some(key) => Err(AuthError::Invalid(format!(
+                "`{}` is missing `{}`",
+                path, key
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_auth() {
+        let valid = r#"{
+            "http-basic": {
+                "repo.magento.com": { "username": "public", "password": "private" }
+            },
+            "github-oauth": { "github.com": "abc" }
+        }"#;
+        assert!(Auth::validate(valid, &COMPOSER::One).is_ok());
+
+        let missing = r#"{ "http-basic": { "repo.magento.com": { "username": "public" } } }"#;
+        assert_eq!(
+            Auth::validate(missing, &COMPOSER::Two)
+                .expect_err("test")
+                .to_string(),
+            "auth.json: `http-basic.repo.magento.com` is missing `password`"
+        );
+
+        let gitlab =
+            r#"{ "gitlab-token": { "gitlab.com": { "username": "me", "token": "abc" } } }"#;
+        assert!(Auth::validate(gitlab, &COMPOSER::Two).is_ok());
+        assert_eq!(
+            Auth::validate(gitlab, &COMPOSER::One)
+                .expect_err("test")
+                .to_string(),
+            "auth.json: `gitlab-token.gitlab.com` must be a token string for Composer 1"
+        );
+
+        assert!(Auth::validate("{", &COMPOSER::Two).is_err());
+    }
+
+    #[test]
+    fn test_set_remove() {
+        let mut json = serde_json::Value::Null;
+        Auth::set(&mut json, "repo.magento.com", "public", "private").expect("test");
+        Auth::set(&mut json, "composer.example.com", "me", "secret").expect("test");
+        assert_eq!(
+            Auth::list(&json),
+            vec![
+                (String::from("composer.example.com"), String::from("me")),
+                (String::from("repo.magento.com"), String::from("public")),
+            ]
+        );
+        assert_eq!(
+            Auth::credentials(&json, "repo.magento.com"),
+            Some((String::from("public"), String::from("private")))
+        );
+        assert!(Auth::validate(&json.to_string(), &COMPOSER::Two).is_ok());
+
+        assert!(Auth::remove(&mut json, "repo.magento.com"));
+        assert!(!Auth::remove(&mut json, "repo.magento.com"));
+        assert!(Auth::remove(&mut json, "composer.example.com"));
+        assert_eq!(json, serde_json::json!({}));
+    }
+}
@@ -1,10 +1,12 @@
 use crate::context::Context;
 use crate::file::File;
+use crate::recipes::m2::services::db::{DbEngine, DbServiceOptions};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct DbConf {
     file_path: PathBuf,
+    mysql_8: bool,
 }
 
 impl File<DbConf> for DbConf {
@@ -14,6 +16,10 @@ impl File<DbConf> for DbConf {
     fn from_ctx(ctx: &Context) -> Result<DbConf, failure::Error> {
         Ok(DbConf {
             file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
+            mysql_8: match DbServiceOptions::from_ctx(ctx).engine_version() {
+                Some((DbEngine::Mysql, version)) => DbEngine::major(&version) == "8",
+                _ => false,
+            },
         })
     }
 
@@ -22,6 +28,28 @@ impl File<DbConf> for DbConf {
     }
 
     fn bytes(&self) -> Vec<u8> {
-        include_bytes!("./db/mysql.cnf").to_vec()
+        if self.mysql_8 {
+            include_bytes!("./db/mysql8.cnf").to_vec()
+        } else {
+            include_bytes!("./db/mysql.cnf").to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mysql_8_conf() {
+        let mysql_8 = |db: &str| {
+            let ctx_str = format!("recipe: M2\noptions:\n  services:\n    db: {}", db);
+            let ctx = Context::new_from_str(&ctx_str).expect("test");
+            DbConf::from_ctx(&ctx).expect("test").mysql_8
+        };
+        assert!(mysql_8("{ version: 8.0 }"));
+        assert!(mysql_8("{ image: \"mysql:8.0.36\" }"));
+        assert!(!mysql_8("{ image: \"mysql:5.7.29\" }"));
+        assert!(!mysql_8("{ engine: mariadb, version: 10.4 }"));
     }
 }
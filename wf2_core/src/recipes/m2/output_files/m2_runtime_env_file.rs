@@ -1,5 +1,14 @@
 use crate::context::Context;
 use crate::file::File;
+use crate::proxy::SharedProxy;
+use crate::recipes::m2::multi_store::Stores;
+use crate::recipes::m2::services::redis::M2RedisOptions;
+use crate::recipes::m2::xdebug::{XdebugHost, XdebugVersion};
+use crate::services::elastic_search::ElasticSearchService;
+use crate::services::mail::MailService;
+use crate::services::open_search::OpenSearchService;
+use crate::services::Service;
+use crate::versions::search_engine::SearchEngine;
 use env_proc::env_vars;
 use snailquote::escape;
 use std::collections::BTreeMap;
@@ -53,6 +62,7 @@ env_vars! {
     MAGE_ADMIN_LASTNAME="Bloggs"
     MAGE_ADMIN_EMAIL="magento@wearejh.com"
     MAGE_BACKEND_FRONTNAME="admin"
+    MAGE_STORES=""
     HTTPS="on"
 
     MYSQL_ROOT_PASSWORD="docker"
@@ -78,6 +88,8 @@ env_vars! {
     XDEBUG_PROFILE_TRIGGER="1"
     XDEBUG_IDE_KEY="PHPSTORM"
     XDEBUG_CONFIG="remote_host=docker.for.mac.host.internal"
+    XDEBUG_CLIENT_HOST="host.docker.internal"
+    XDEBUG_CLIENT_PORT="9003"
     PHP_IDE_CONFIG="serverName=local.m2"
 
     BLACKFIRE_CLIENT_ID="12"
@@ -86,6 +98,11 @@ env_vars! {
     BLACKFIRE_SERVER_TOKEN=""
     NODE_TLS_REJECT_UNAUTHORIZED="0"
     ELASTICSEARCH_HOST="elasticsearch"
+    SEARCH_ENGINE="elasticsearch7"
+    SEARCH_ENGINE_HOST="elasticsearch"
+    SEARCH_ENGINE_PORT="9200"
+    REDIS_HOST="redis"
+    REDIS_PORT="6379"
 }
 
 //
@@ -109,7 +126,86 @@ pub fn create_runtime_env(
     merged.insert(EnvVarKeys::MageHost, format!("https://{}", domain));
     merged.insert(EnvVarKeys::PhpIdeConfig, format!("serverName={}", domain));
 
-    Ok(print(merged))
+    // Used by `setup:install` for `--search-engine` & `--<engine>-host`,
+    // unless they've been set explicitly in `env`
+    let search_engine = SearchEngine::from_ctx(ctx);
+    let search_host = if search_engine.is_opensearch() {
+        OpenSearchService::NAME
+    } else {
+        ElasticSearchService::NAME
+    };
+    let explicit = |key: &str| {
+        input
+            .as_ref()
+            .map_or(false, |input| input.get(key).is_some())
+    };
+    if !explicit("SEARCH_ENGINE") {
+        merged.insert(EnvVarKeys::SearchEngine, search_engine.magento_engine(ctx));
+    }
+    if !explicit("SEARCH_ENGINE_HOST") {
+        merged.insert(EnvVarKeys::SearchEngineHost, search_host.to_string());
+    }
+    if !explicit("ELASTICSEARCH_HOST") {
+        merged.insert(EnvVarKeys::ElasticsearchHost, search_host.to_string());
+    }
+
+    // eg: `ce.acme.m2=website:ce,/de=store:de`, for scripts that
+    // need to know which store is served where
+    if !explicit("MAGE_STORES") {
+        merged.insert(EnvVarKeys::MageStores, Stores::from_ctx(ctx).env_mapping());
+    }
+    if merged
+        .get(&EnvVarKeys::MageStores)
+        .map_or(false, String::is_empty)
+    {
+        merged.remove(&EnvVarKeys::MageStores);
+    }
+
+    // MailHog lives in the shared proxy, reachable via the host
+    if SharedProxy::enabled(ctx)
+        && merged.get(&EnvVarKeys::MailHost).map(String::as_str) == Some(MailService::NAME)
+    {
+        merged.insert(EnvVarKeys::MailHost, SharedProxy::MAIL_HOST.to_string());
+    }
+
+    // Xdebug 3 renamed most settings, so only those for the version
+    // in the PHP image are given
+    let xdebug = XdebugVersion::from_php(&ctx.php_version);
+    let xdebug_host = match merged.get(&EnvVarKeys::XdebugClientHost) {
+        Some(host) if explicit("XDEBUG_CLIENT_HOST") => host.to_string(),
+        _ => XdebugHost::detected(ctx),
+    };
+    if !explicit("XDEBUG_CLIENT_PORT") {
+        merged.insert(
+            EnvVarKeys::XdebugClientPort,
+            xdebug.client_port().to_string(),
+        );
+    }
+    if !explicit("XDEBUG_CONFIG") {
+        merged.insert(EnvVarKeys::XdebugConfig, xdebug.config(&xdebug_host));
+    }
+    merged.insert(EnvVarKeys::XdebugClientHost, xdebug_host);
+    if xdebug == XdebugVersion::Three {
+        // replaced by `XDEBUG_MODE` on the `php-debug` service
+        merged.remove(&EnvVarKeys::XdebugProfiler);
+        merged.remove(&EnvVarKeys::XdebugProfileTrigger);
+        merged.remove(&EnvVarKeys::XdebugCoverage);
+    }
+
+    // With named redis instances, `REDIS_HOST` points at the first one
+    let redis = M2RedisOptions::from_ctx(ctx).unwrap_or_default();
+    if !explicit("REDIS_HOST") {
+        if let Some(first) = redis.service_names().first() {
+            merged.insert(EnvVarKeys::RedisHost, first.to_string());
+        }
+    }
+
+    // Instance names aren't known ahead of time, so they're appended
+    let mut bytes = print(merged);
+    for (key, value) in redis.env_vars() {
+        bytes.extend_from_slice(format!("{}={}\n", key, escape(value.as_str())).as_bytes());
+    }
+    Ok(bytes)
 }
 
 //
@@ -146,6 +242,68 @@ fn test_env_hash_with_overrides() {
     assert!(as_str.contains(r#"MAGE_ROOT_DIR=/var/www"#));
 }
 
+#[test]
+fn test_env_search_engine() {
+    let ctx =
+        Context::new_from_str("search_engine: { engine: opensearch, version: 2.5 }").expect("test");
+    let env = create_runtime_env(&ctx, &None, "local.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(as_str.contains("SEARCH_ENGINE=opensearch\n"));
+    assert!(as_str.contains("SEARCH_ENGINE_HOST=opensearch\n"));
+    assert!(as_str.contains("ELASTICSEARCH_HOST=opensearch\n"));
+
+    let env = create_runtime_env(&Context::default(), &None, "local.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(as_str.contains("SEARCH_ENGINE=elasticsearch7\n"));
+    assert!(as_str.contains("SEARCH_ENGINE_HOST=elasticsearch\n"));
+}
+
+#[test]
+fn test_env_redis_instances() {
+    let ctx =
+        Context::new_from_str("options: { services: { redis: { instances: [cache, session] } } }")
+            .expect("test");
+    let env = create_runtime_env(&ctx, &None, "local.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(as_str.contains("REDIS_HOST=redis-cache\n"));
+    assert!(as_str.contains("REDIS_SESSION_HOST=redis-session\n"));
+    assert!(as_str.contains("REDIS_SESSION_PORT=6379\n"));
+}
+
+#[test]
+fn test_env_xdebug() {
+    let ctx = Context::new_from_str("php_version: 7.3").expect("test");
+    let env = create_runtime_env(&ctx, &None, "local.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(as_str.contains("XDEBUG_CONFIG=remote_host="));
+    assert!(as_str.contains("XDEBUG_PROFILER=0\n"));
+
+    let ctx = Context::new_from_str("php_version: 8.1").expect("test");
+    let yaml: serde_yaml::Value =
+        serde_yaml::from_str("XDEBUG_CLIENT_HOST: 192.168.1.10").expect("test");
+    let env = create_runtime_env(&ctx, &Some(yaml), "local.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(as_str.contains("XDEBUG_CONFIG='client_host=192.168.1.10 client_port=9003'\n"));
+    assert!(as_str.contains("XDEBUG_CLIENT_PORT=9003\n"));
+    assert!(!as_str.contains("XDEBUG_PROFILER"));
+}
+
+#[test]
+fn test_env_stores() {
+    let ctx = Context::new_from_file("../fixtures/config_multi-domain.yaml")
+        .expect("test")
+        .expect("test");
+    let env = create_runtime_env(&ctx, &None, "acme.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(as_str.contains(
+        "MAGE_STORES=ce.acme.m2=website:ce,/de=store:de,/de/b2b=store:de_b2b,ee.acme.m2=website:ee\n"
+    ));
+
+    let env = create_runtime_env(&Context::default(), &None, "local.m2").expect("test");
+    let as_str = std::str::from_utf8(&env).expect("test");
+    assert!(!as_str.contains("MAGE_STORES"));
+}
+
 #[test]
 fn test_env_hash_without_overrides() {
     let yaml = None;
@@ -1,9 +1,17 @@
+use crate::certs::ProjectCert;
 use crate::context::Context;
 use crate::file::File;
+use crate::proxy::SharedProxy;
 
 use crate::output_files::OutputFiles;
+use crate::recipes::m2::services::db::DbService;
+use crate::recipes::m2::services::unison::UnisonService;
 use crate::recipes::m2::services::M2RecipeOptions;
 use crate::recipes::m2::M2Recipe;
+use crate::services::nginx::NginxService;
+use crate::services::traefik::{TraefikService, TraefikVersion};
+use crate::services::varnish::VarnishService;
+use crate::services::Service;
 use crate::task::Task;
 use db_conf::DbConf;
 use db_init::DbInit;
@@ -11,8 +19,9 @@ use m2_runtime_env_file::M2RuntimeEnvFile;
 use nginx_m2::NginxM2;
 use nginx_pwa::NginxPwa;
 use nginx_upstream::NginxUpstream;
-use traefik::TraefikFile;
+use traefik::{TraefikDynamicFile, TraefikFile};
 use unison::UnisonFile;
+use varnish_vcl::VarnishVcl;
 
 pub mod auth;
 pub mod composer;
@@ -24,21 +33,43 @@ pub mod nginx_pwa;
 pub mod nginx_upstream;
 pub mod traefik;
 pub mod unison;
-pub mod varnish_enabled;
+pub mod varnish_vcl;
 
 impl OutputFiles for M2Recipe {
     fn output_files(&self, ctx: &Context) -> Result<Vec<Task>, failure::Error> {
-        let mut files = vec![
-            M2RuntimeEnvFile::from_ctx(&ctx)?.write_task(),
-            UnisonFile::from_ctx(&ctx)?.write_task(),
-            TraefikFile::from_ctx(&ctx)?.write_task(),
-            NginxUpstream::from_ctx(&ctx)?.write_task(),
-            NginxM2::from_ctx(&ctx)?.write_task(),
-            DbConf::from_ctx(&ctx)?.write_task(),
-            DbInit::from_ctx(&ctx)?.write_task(),
-            // NginxPwa::from_ctx(&ctx)?.write_task(),
-            // VarnishEnabled::from_ctx(&ctx)?.write_task(),
-        ];
+        let mut files = vec![M2RuntimeEnvFile::from_ctx(&ctx)?.write_task()];
+
+        // only write files for services that are enabled
+        let enabled = |name: &str| !M2RecipeOptions::is_disabled(ctx, name);
+
+        if enabled(UnisonService::NAME) {
+            files.push(UnisonFile::from_ctx(&ctx)?.write_task());
+        }
+
+        if SharedProxy::enabled(ctx) {
+            files.extend(SharedProxy::from_home()?.project_tasks(ctx));
+        } else if enabled(TraefikService::NAME) {
+            files.push(TraefikFile::from_ctx(&ctx)?.write_task());
+            // only v2 is configured to serve the project's certificate
+            if TraefikVersion::from_ctx(ctx) == TraefikVersion::V2 {
+                files.push(TraefikDynamicFile::from_ctx(&ctx)?.write_task());
+                files.push(ProjectCert::from_ctx(ctx).write_task_from_home());
+            }
+        }
+
+        if enabled(NginxService::NAME) {
+            files.push(NginxUpstream::from_ctx(&ctx)?.write_task());
+            files.push(NginxM2::from_ctx(&ctx)?.write_task());
+        }
+
+        if enabled(VarnishService::NAME) {
+            files.push(VarnishVcl::from_ctx(ctx)?.write_task());
+        }
+
+        if enabled(DbService::NAME) {
+            files.push(DbConf::from_ctx(&ctx)?.write_task());
+            files.push(DbInit::from_ctx(&ctx)?.write_task());
+        }
 
         if M2RecipeOptions::has_pwa_options(ctx) {
             files.push(NginxPwa::from_ctx(&ctx)?.write_task())
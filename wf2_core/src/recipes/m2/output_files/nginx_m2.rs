@@ -17,7 +17,7 @@ impl File<NginxM2> for NginxM2 {
     fn from_ctx(ctx: &Context) -> Result<NginxM2, failure::Error> {
         Ok(NginxM2 {
             file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
-            server_name: ctx.domains().join(" "),
+            server_name: Stores::all_domains(ctx).join(" "),
             stores: ctx.stores.clone(),
         })
     }
@@ -29,17 +29,63 @@ impl File<NginxM2> for NginxM2 {
     fn bytes(&self) -> Vec<u8> {
         //build the multistore nginx config based on the stores found in the context object.
         let partial = include_str!("m2_store_partial.conf");
-        let stores_config = match &self.stores {
-            Some(ss) => ss.iter().fold(String::from(""), |acc, store| {
+        let stores = self.stores.clone().unwrap_or_else(|| Stores(vec![]));
+        let stores_config = stores
+            .path_stores()
+            .iter()
+            .fold(String::from(""), |acc, store| {
                 format!("{}{}", acc, store.process_template(partial))
-            }),
-            None => String::from(""),
+            });
+
+        // stores selected by domain get their run code & type from the `map` blocks
+        let store_params = if stores.domain_stores().is_empty() {
+            String::from("")
+        } else {
+            String::from(
+                "        fastcgi_param  MAGE_RUN_CODE  $MAGE_RUN_CODE if_not_empty;\n        fastcgi_param  MAGE_RUN_TYPE  $MAGE_RUN_TYPE if_not_empty;\n",
+            )
         };
 
         include_str!("m2.conf")
+            .replace("{{m2_store_map}}", &stores.nginx_maps())
             .replace("{{m2_server_name}}", &self.server_name)
             .replace("{{m2_multistore}}", &stores_config)
+            .replace("{{m2_store_params}}", &store_params)
             .bytes()
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nginx_multi_domain() {
+        let ctx = Context::new_from_file("../fixtures/config_multi-domain.yaml")
+            .expect("test")
+            .expect("test");
+        let nginx = NginxM2::from_ctx(&ctx).expect("test");
+        let output = String::from_utf8(nginx.bytes()).expect("test");
+        assert!(output.starts_with(
+            "map $host $MAGE_RUN_CODE {\n    default \"\";\n    ce.acme.m2 ce;\n    ee.acme.m2 ee;\n}\n"
+        ));
+        assert!(output.contains("    ce.acme.m2 website;\n"));
+        assert!(output.contains("server_name acme.m2 ce.acme.m2 ee.acme.m2;"));
+        assert!(output.contains("fastcgi_param  MAGE_RUN_CODE  $MAGE_RUN_CODE if_not_empty;"));
+        let b2b = output.find("location ~* ^/de/b2b/?").expect("test");
+        let de = output.find("location ~* ^/de/?").expect("test");
+        assert!(b2b < de, "longer prefixes should come first");
+        assert!(output.contains("fastcgi_param   MAGE_RUN_CODE de_b2b;"));
+    }
+
+    #[test]
+    fn test_nginx_no_stores() {
+        let ctx = Context::default();
+        let nginx = NginxM2::from_ctx(&ctx).expect("test");
+        let output = String::from_utf8(nginx.bytes()).expect("test");
+        assert!(output.starts_with("server {"));
+        assert!(!output.contains("MAGE_RUN_CODE"));
+        assert!(!output.contains("{{"));
+    }
+}
@@ -2,6 +2,7 @@ use crate::context::Context;
 use crate::file::File;
 use crate::recipes::m2::services::php::PhpService;
 use crate::recipes::m2::services::php_debug::PhpDebugService;
+use crate::recipes::m2::services::M2RecipeOptions;
 use crate::services::Service;
 use std::path::PathBuf;
 
@@ -21,10 +22,16 @@ impl File<NginxUpstream> for NginxUpstream {
     const HOST_OUTPUT_PATH: &'static str = "nginx/sites/upstream.conf";
 
     fn from_ctx(ctx: &Context) -> Result<NginxUpstream, failure::Error> {
+        // nginx refuses to start if an upstream host doesn't exist
+        let backend_debug = if M2RecipeOptions::is_disabled(ctx, PhpDebugService::NAME) {
+            PhpService::NAME
+        } else {
+            PhpDebugService::NAME
+        };
         Ok(NginxUpstream {
             file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
             backend: PhpService::NAME.to_string(),
-            backend_debug: PhpDebugService::NAME.to_string(),
+            backend_debug: backend_debug.to_string(),
         })
     }
 
@@ -90,3 +97,26 @@ upstream fastcgi_backend_debug {
     assert_eq!(expected, std::str::from_utf8(&us.bytes()).expect("test"));
     Ok(())
 }
+
+#[test]
+fn test_nginxupstream_php_debug_disabled() -> Result<(), failure::Error> {
+    let ctx = Context::new_from_str(
+        r#"
+        options:
+          services:
+            php-debug:
+              enabled: false
+        "#,
+    )
+    .expect("test");
+    let us = NginxUpstream::from_ctx(&ctx)?;
+    let expected = "upstream fastcgi_backend {
+  server php:9000;
+}
+
+upstream fastcgi_backend_debug {
+  server php:9000;
+}";
+    assert_eq!(expected, std::str::from_utf8(&us.bytes()).expect("test"));
+    Ok(())
+}
@@ -1,10 +1,19 @@
 use crate::context::Context;
 use crate::file::File;
+use crate::services::traefik::TraefikVersion;
 use std::path::PathBuf;
 
+///
+/// The static Traefik config, which differs between v1 + v2
+///
 #[derive(Debug, Clone)]
 pub struct TraefikFile {
     file_path: PathBuf,
+    version: TraefikVersion,
+}
+
+impl TraefikFile {
+    pub const CONTAINER_PATH: &'static str = "/etc/traefik/traefik.toml";
 }
 
 impl File<TraefikFile> for TraefikFile {
@@ -14,6 +23,41 @@ impl File<TraefikFile> for TraefikFile {
     fn from_ctx(ctx: &Context) -> Result<TraefikFile, failure::Error> {
         Ok(TraefikFile {
             file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
+            version: TraefikVersion::from_ctx(ctx),
+        })
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.file_path.clone()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        match self.version {
+            TraefikVersion::V1 => include_bytes!("./traefik.toml").to_vec(),
+            TraefikVersion::V2 => include_bytes!("./traefik_v2.toml").to_vec(),
+        }
+    }
+}
+
+///
+/// Traefik v2 only - the file provider config, used for TLS certificates
+///
+#[derive(Debug, Clone)]
+pub struct TraefikDynamicFile {
+    file_path: PathBuf,
+}
+
+impl TraefikDynamicFile {
+    pub const CONTAINER_PATH: &'static str = "/etc/traefik/dynamic.toml";
+}
+
+impl File<TraefikDynamicFile> for TraefikDynamicFile {
+    const DESCRIPTION: &'static str = "Writes the traefik dynamic config file";
+    const HOST_OUTPUT_PATH: &'static str = "traefik/dynamic.toml";
+
+    fn from_ctx(ctx: &Context) -> Result<TraefikDynamicFile, failure::Error> {
+        Ok(TraefikDynamicFile {
+            file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
         })
     }
 
@@ -22,6 +66,6 @@ impl File<TraefikFile> for TraefikFile {
     }
 
     fn bytes(&self) -> Vec<u8> {
-        include_bytes!("./traefik.toml").to_vec()
+        include_bytes!("./traefik_dynamic.toml").to_vec()
     }
 }
@@ -0,0 +1,74 @@
+use crate::context::Context;
+use crate::file::File;
+use crate::services::varnish::VarnishService;
+use crate::services::Service;
+use crate::task::Task;
+use crate::util::path_buf_to_string;
+use std::path::PathBuf;
+
+///
+/// The VCL loaded by `wf2 varnish enable`.
+///
+/// This is either the default Magento VCL, or the project's own file
+/// when `options.services.varnish.vcl` is given.
+///
+#[derive(Debug, Clone)]
+pub struct VarnishVcl {
+    file_path: PathBuf,
+    source: Option<PathBuf>,
+}
+
+impl VarnishVcl {
+    pub const CONTAINER_DIR: &'static str = "/etc/varnish/wf2";
+    pub const CONTAINER_PATH: &'static str = "/etc/varnish/wf2/default.vcl";
+
+    ///
+    /// The directory is mounted (rather than the file), so that
+    /// changes are always visible inside the running container
+    ///
+    pub fn volume(ctx: &Context) -> String {
+        let mut dir = ctx.output_file_path(VarnishVcl::HOST_OUTPUT_PATH);
+        dir.pop();
+        format!("{}:{}", path_buf_to_string(&dir), VarnishVcl::CONTAINER_DIR)
+    }
+
+    ///
+    /// The user-provided VCL, relative to the project
+    ///
+    pub fn source(ctx: &Context) -> Option<PathBuf> {
+        ctx.options
+            .as_ref()
+            .and_then(|opts| opts["services"][VarnishService::NAME]["vcl"].as_str())
+            .map(|path| ctx.cwd.join(path))
+    }
+}
+
+impl File<VarnishVcl> for VarnishVcl {
+    const DESCRIPTION: &'static str = "Writes the varnish VCL";
+    const HOST_OUTPUT_PATH: &'static str = "varnish/default.vcl";
+
+    fn from_ctx(ctx: &Context) -> Result<VarnishVcl, failure::Error> {
+        Ok(VarnishVcl {
+            file_path: ctx.output_file_path(Self::HOST_OUTPUT_PATH),
+            source: VarnishVcl::source(ctx),
+        })
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.file_path.clone()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        include_bytes!("enabled.vcl").to_vec()
+    }
+
+    fn write_task(&self) -> Task {
+        match &self.source {
+            Some(source) => Task::Seq(vec![
+                Task::file_exists(source, "The VCL given in options.services.varnish.vcl"),
+                Task::file_clone(source, self.file_path()),
+            ]),
+            None => Task::file_write(self.file_path(), Self::DESCRIPTION, self.bytes()),
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use crate::context::Context;
+use crate::dc_healthcheck::DcHealthcheck;
 use crate::dc_service::DcService;
 use crate::recipes::m2::dc_tasks::M2Volumes;
 use crate::recipes::m2::m2_vars::{M2Var, M2Vars};
 use crate::services::Service;
 
-use crate::recipes::m2::services::M2RecipeOptions;
+use crate::php::PHP;
+use crate::versions::mysql::{MARIADB, MYSQL};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
 
 pub struct DbService;
 
@@ -16,39 +20,367 @@ impl DbService {
     pub const VOLUME_DATA: &'static str = "/var/lib/mysql";
     pub const VOLUME_CONF: &'static str = "/etc/mysql/conf.d";
     pub const VOLUME_ENTRY: &'static str = "/docker-entrypoint-initdb.d";
+
+    ///
+    /// Connects over TCP, since the temporary server used during
+    /// initialisation only listens on the socket
+    ///
+    pub fn healthcheck(admin_client: &str) -> DcHealthcheck {
+        DcHealthcheck::cmd_shell(format!(
+            "{} ping -h 127.0.0.1 -u{} -p{} --silent",
+            admin_client,
+            DbService::DB_USER,
+            DbService::DB_PASS
+        ))
+        .set_interval("5s")
+        .set_timeout("5s")
+        .set_retries(20)
+        .set_start_period("20s")
+        .finish()
+    }
 }
 
 ///
-/// These are the options that can be provided in the wf2 file
-/// under 'options.services.db'
+/// The database server, set with `options.services.db.engine`
 ///
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct DbServiceOptions {
-    #[serde(default = "default_image")]
-    pub image: String,
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DbEngine {
+    Mysql,
+    Mariadb,
 }
 
-impl Default for DbServiceOptions {
+impl Default for DbEngine {
     fn default() -> Self {
-        DbServiceOptions {
-            image: default_image(),
+        DbEngine::Mysql
+    }
+}
+
+impl std::fmt::Display for DbEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbEngine::Mysql => write!(f, "mysql"),
+            DbEngine::Mariadb => write!(f, "mariadb"),
+        }
+    }
+}
+
+impl DbEngine {
+    pub fn versions(self) -> Vec<&'static str> {
+        match self {
+            DbEngine::Mysql => MYSQL::versions(),
+            DbEngine::Mariadb => MARIADB::versions(),
+        }
+    }
+
+    pub fn default_version(self) -> &'static str {
+        match self {
+            DbEngine::Mysql => MYSQL::default().version(),
+            DbEngine::Mariadb => MARIADB::default().version(),
+        }
+    }
+
+    ///
+    /// Read the engine + version back from an image like `mysql:8.0`.
+    ///
+    /// Tags are read as `major.minor`, so `mysql:5.7.29` is MySQL 5.7
+    ///
+    pub fn from_image(image: &str) -> Option<(DbEngine, String)> {
+        let mut parts = image.splitn(2, ':');
+        let engine = match parts.next() {
+            Some("mysql") => DbEngine::Mysql,
+            Some("mariadb") => DbEngine::Mariadb,
+            _ => return None,
+        };
+        parts.next().map(|tag| {
+            let version = tag.split('-').next().unwrap_or(tag);
+            let version = version.split('.').take(2).collect::<Vec<&str>>().join(".");
+            (engine, version)
+        })
+    }
+
+    ///
+    /// eg: `8` for `8.0`
+    ///
+    pub fn major(version: &str) -> &str {
+        version.split('.').next().unwrap_or(version)
+    }
+}
+
+///
+/// `options.services.db.version`, from either engine's supported versions
+///
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DbVersion {
+    Mysql(MYSQL),
+    Mariadb(MARIADB),
+}
+
+impl DbVersion {
+    pub fn engine(&self) -> DbEngine {
+        match self {
+            DbVersion::Mysql(_) => DbEngine::Mysql,
+            DbVersion::Mariadb(_) => DbEngine::Mariadb,
+        }
+    }
+}
+
+impl std::fmt::Display for DbVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbVersion::Mysql(version) => write!(f, "{}", version),
+            DbVersion::Mariadb(version) => write!(f, "{}", version),
         }
     }
 }
-fn default_image() -> String {
-    String::from("mysql:5.7")
+
+///
+/// The versions of each engine never overlap, so whichever one matches is used
+///
+impl<'de> Deserialize<'de> for DbVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_yaml::Value::deserialize(deserializer)?;
+        serde_yaml::from_value(value.clone())
+            .map(DbVersion::Mysql)
+            .or_else(|_| serde_yaml::from_value(value).map(DbVersion::Mariadb))
+            .map_err(|_| {
+                D::Error::custom(format!(
+                    "expected a MySQL version ({}) or a MariaDB version ({})",
+                    MYSQL::expected(),
+                    MARIADB::expected()
+                ))
+            })
+    }
+}
+
+///
+/// The versions Magento supports for each PHP version.
+///
+/// Magento 2.3 runs on PHP 7.1 - 7.3 and 2.4 on PHP 7.3 and later, which is
+/// why 7.3 allows both MySQL 5.6 and 8.0
+///
+const PHP_SUPPORT: [(PHP, DbEngine, &[&str]); 16] = [
+    (PHP::SevenOne, DbEngine::Mysql, &["5.6", "5.7"]),
+    (PHP::SevenOne, DbEngine::Mariadb, &["10.2"]),
+    (PHP::SevenTwo, DbEngine::Mysql, &["5.6", "5.7"]),
+    (PHP::SevenTwo, DbEngine::Mariadb, &["10.2", "10.3"]),
+    (PHP::SevenThree, DbEngine::Mysql, &["5.6", "5.7", "8.0"]),
+    (
+        PHP::SevenThree,
+        DbEngine::Mariadb,
+        &["10.2", "10.3", "10.4"],
+    ),
+    (PHP::SevenFour, DbEngine::Mysql, &["5.7", "8.0"]),
+    (PHP::SevenFour, DbEngine::Mariadb, &["10.2", "10.3", "10.4"]),
+    (PHP::EightZero, DbEngine::Mysql, &["8.0"]),
+    (PHP::EightZero, DbEngine::Mariadb, &["10.4"]),
+    (PHP::EightOne, DbEngine::Mysql, &["8.0"]),
+    (PHP::EightOne, DbEngine::Mariadb, &["10.4", "10.6"]),
+    (PHP::EightTwo, DbEngine::Mysql, &["8.0"]),
+    (PHP::EightTwo, DbEngine::Mariadb, &["10.6"]),
+    (PHP::EightThree, DbEngine::Mysql, &["8.0"]),
+    (PHP::EightThree, DbEngine::Mariadb, &["10.6"]),
+];
+
+#[derive(Debug, Fail)]
+pub enum DbServiceError {
+    #[fail(display = "Invalid db options\n{}", _0)]
+    Invalid(String),
+    #[fail(
+        display = "{} {} is not supported, expected one of: {}",
+        engine, version, supported
+    )]
+    UnsupportedVersion {
+        engine: DbEngine,
+        version: String,
+        supported: String,
+    },
+    #[fail(
+        display = "{} {} is not supported by Magento with PHP {}, expected one of: {}",
+        engine, version, php, supported
+    )]
+    UnsupportedWithPhp {
+        engine: DbEngine,
+        version: String,
+        php: PHP,
+        supported: String,
+    },
+}
+
+///
+/// These are the options that can be provided in the wf2 file
+/// under 'options.services.db'
+///
+/// ```
+/// # use wf2_core::context::Context;
+/// # use wf2_core::dc_tasks::DcTasksTrait;
+/// # use wf2_core::recipes::m2::M2Recipe;
+/// # let input = r#"
+/// recipe: M2
+/// php_version: 7.4
+/// options:
+///   services:
+///     db:
+///       engine: mariadb
+///       version: 10.4
+/// # "#;
+/// # let ctx = Context::new_from_str(input).expect("can parse");
+/// # let dc = (M2Recipe).dc(&ctx).expect("valid dc");
+/// # let db = dc.services.expect("services").get("db").cloned().expect("db");
+/// # assert_eq!(db.image, "mariadb:10.4");
+/// # assert_eq!(db.command, None);
+/// ```
+///
+/// `engine` can be left out when the version is enough to tell, eg: `version: 10.4` is MariaDB.
+///
+/// `image` can still be given to use a custom image, in which case the engine + version are read
+/// from it when possible.
+///
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DbServiceOptions {
+    pub engine: Option<DbEngine>,
+    pub version: Option<DbVersion>,
+    pub image: Option<String>,
 }
 
 impl DbServiceOptions {
     pub fn from_ctx(ctx: &Context) -> Self {
-        if let Ok(opts) = ctx.parse_options::<M2RecipeOptions>() {
-            if let Some(services) = opts.services {
-                if let Some(db) = services.db {
-                    return db;
+        DbServiceOptions::try_from_ctx(ctx).unwrap_or_default()
+    }
+
+    pub fn try_from_ctx(ctx: &Context) -> Result<Self, failure::Error> {
+        match ctx.options.as_ref().map(|opts| &opts["services"]["db"]) {
+            Some(db) if !db.is_null() => serde_yaml::from_value(db.clone())
+                .map_err(|e| DbServiceError::Invalid(e.to_string()).into()),
+            _ => Ok(DbServiceOptions::default()),
+        }
+    }
+
+    ///
+    /// `None` only when a custom image is used, like `percona:5.7`
+    ///
+    pub fn engine_version(&self) -> Option<(DbEngine, String)> {
+        match (self.engine, &self.version, &self.image) {
+            (None, None, Some(image)) => DbEngine::from_image(image),
+            (engine, version, _) => {
+                let engine = engine
+                    .or_else(|| version.as_ref().map(DbVersion::engine))
+                    .unwrap_or_default();
+                let version = version.as_ref().map_or_else(
+                    || engine.default_version().to_string(),
+                    |version| version.to_string(),
+                );
+                Some((engine, version))
+            }
+        }
+    }
+
+    pub fn image(&self) -> String {
+        match (&self.image, self.engine_version()) {
+            (Some(image), _) => image.clone(),
+            (None, Some((engine, version))) => format!("{}:{}", engine, version),
+            (None, None) => MYSQL::default().image(),
+        }
+    }
+
+    pub fn is_mariadb(&self) -> bool {
+        matches!(self.engine_version(), Some((DbEngine::Mariadb, _)))
+    }
+
+    ///
+    /// MariaDB 10.5+ renamed every client, eg: `mysqldump` -> `mariadb-dump`,
+    /// and 11 removed the old names.
+    ///
+    /// Tags like `latest` or `lts` are always a recent release.
+    ///
+    fn renamed_clients(&self) -> bool {
+        match self.engine_version() {
+            Some((DbEngine::Mariadb, version)) => {
+                let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+                match (parts.next().flatten(), parts.next().flatten()) {
+                    (Some(major), minor) => (major, minor.unwrap_or(0)) >= (10, 5),
+                    (None, _) => true,
                 }
             }
+            _ => false,
+        }
+    }
+
+    pub fn client(&self) -> &'static str {
+        if self.renamed_clients() {
+            "mariadb"
+        } else {
+            "mysql"
+        }
+    }
+
+    pub fn dump_client(&self) -> &'static str {
+        if self.renamed_clients() {
+            "mariadb-dump"
+        } else {
+            "mysqldump"
+        }
+    }
+
+    pub fn admin_client(&self) -> &'static str {
+        if self.renamed_clients() {
+            "mariadb-admin"
+        } else {
+            "mysqladmin"
+        }
+    }
+
+    ///
+    /// MySQL 8 defaults to `caching_sha2_password`, which older PHP clients can't use.
+    /// MariaDB doesn't have the option at all, and would refuse to start with it.
+    ///
+    pub fn command(&self) -> Option<&'static str> {
+        if self.is_mariadb() {
+            None
+        } else {
+            Some("--default-authentication-plugin=mysql_native_password")
+        }
+    }
+
+    ///
+    /// Reject engine + version combinations that don't exist, or that Magento
+    /// doesn't support with the project's PHP version.
+    ///
+    /// A custom `image` on its own is trusted as-is, since its tag can be anything
+    /// (like `mysql:latest`)
+    ///
+    pub fn validate(&self, php: &PHP) -> Result<(), DbServiceError> {
+        if self.engine.is_none() && self.version.is_none() && self.image.is_some() {
+            return Ok(());
+        }
+        let (engine, version) = match self.engine_version() {
+            Some(engine_version) => engine_version,
+            None => return Ok(()),
+        };
+        if !engine.versions().contains(&version.as_str()) {
+            return Err(DbServiceError::UnsupportedVersion {
+                engine,
+                version,
+                supported: engine.versions().join(", "),
+            });
         }
-        DbServiceOptions::default()
+        let supported = PHP_SUPPORT
+            .iter()
+            .find(|(p, e, _)| p == php && *e == engine)
+            .map_or(&[][..], |(_, _, versions)| *versions);
+        if !supported.contains(&version.as_str()) {
+            return Err(DbServiceError::UnsupportedWithPhp {
+                engine,
+                version,
+                php: php.clone(),
+                supported: supported.join(", "),
+            });
+        }
+        Ok(())
     }
 }
 
@@ -57,8 +389,9 @@ impl Service<M2Vars> for DbService {
     const IMAGE: &'static str = "mysql:5.6";
 
     fn dc_service(&self, ctx: &Context, vars: &M2Vars) -> DcService {
-        let opts = DbServiceOptions::from_ctx(&ctx);
-        DcService::new(ctx.name(), Self::NAME, opts.image)
+        let opts = DbServiceOptions::from_ctx(ctx);
+        let mut service = DcService::new(ctx.name(), Self::NAME, opts.image());
+        service
             .set_volumes(vec![
                 format!("{}:{}", M2Volumes::DB, DbService::VOLUME_DATA),
                 format!(
@@ -72,12 +405,15 @@ impl Service<M2Vars> for DbService {
                     DbService::VOLUME_ENTRY
                 ),
             ])
-            .set_command("--default-authentication-plugin=mysql_native_password")
             .set_ports(vec!["3306:3306"])
             .set_restart("unless-stopped")
             .set_env_file(vec![vars.content[&M2Var::EnvFile].to_string()])
             .set_labels(vec![Self::TRAEFIK_DISABLE_LABEL.to_string()])
-            .finish()
+            .set_healthcheck(DbService::healthcheck(opts.admin_client()));
+        if let Some(command) = opts.command() {
+            service.set_command(command);
+        }
+        service.finish()
     }
 
     fn from_ctx(ctx: &Context) -> Result<DcService, failure::Error> {
@@ -122,9 +458,109 @@ mod tests {
             ports:
               - "3306:3306"
             command: "--default-authentication-plugin=mysql_native_password"
+            healthcheck:
+              test: ["CMD-SHELL", "mysqladmin ping -h 127.0.0.1 -udocker -pdocker --silent"]
+              interval: 5s
+              timeout: 5s
+              retries: 20
+              start_period: 20s
 
         "#;
         let expected_dc: DcService = serde_yaml::from_str(expected).expect("test yaml");
         assert_eq!(actual_dc, expected_dc);
     }
+
+    #[test]
+    fn test_db_validate() {
+        let cases = vec![
+            ("7.3", "{ image: \"mysql:8.0\" }", None),
+            ("7.4", "{ engine: mariadb, version: 10.4 }", None),
+            ("7.4", "{ version: 8 }", None),
+            ("7.4", "{ image: \"percona:5.7\" }", None),
+            ("7.4", "{ image: \"mysql:5.7.29\" }", None),
+            ("8.1", "{ image: \"mysql:latest\" }", None),
+            (
+                "7.1",
+                "{ version: 8.0 }",
+                Some("mysql 8.0 is not supported by Magento with PHP 7.1, expected one of: 5.6, 5.7"),
+            ),
+            (
+                "7.4",
+                "{ engine: mariadb, version: \"10.6\" }",
+                Some("mariadb 10.6 is not supported by Magento with PHP 7.4, expected one of: 10.2, 10.3, 10.4"),
+            ),
+            (
+                "7.3",
+                "{ engine: mysql, version: 10.4 }",
+                Some("mysql 10.4 is not supported, expected one of: 5.6, 5.7, 8.0"),
+            ),
+            (
+                "7.3",
+                "{ engine: mysql, version: 5.5 }",
+                Some("Invalid db options\nexpected a MySQL version (`5.6`, `5.7` or `8.0`) or a MariaDB version (`10.2`, `10.3`, `10.4` or `10.6`)"),
+            ),
+        ];
+        for (php, db, expected) in cases {
+            let ctx_str = format!(
+                "recipe: M2\nphp_version: {}\noptions:\n  services:\n    db: {}",
+                php, db
+            );
+            let ctx = Context::new_from_str(&ctx_str).expect("test context");
+            let actual = DbServiceOptions::try_from_ctx(&ctx)
+                .and_then(|db| db.validate(&ctx.php_version).map_err(failure::Error::from))
+                .err()
+                .map(|e| e.to_string());
+            assert_eq!(actual.as_deref(), expected, "{}", db);
+        }
+    }
+
+    #[test]
+    fn test_mariadb_clients() {
+        let opts = DbServiceOptions {
+            engine: Some(DbEngine::Mariadb),
+            version: Some(DbVersion::Mariadb(MARIADB::TenSix)),
+            image: None,
+        };
+        assert_eq!(opts.image(), "mariadb:10.6");
+        assert_eq!(opts.command(), None);
+        assert_eq!(opts.client(), "mariadb");
+        assert_eq!(opts.dump_client(), "mariadb-dump");
+
+        let opts = DbServiceOptions {
+            version: Some(DbVersion::Mariadb(MARIADB::TenThree)),
+            ..opts
+        };
+        assert_eq!(opts.dump_client(), "mysqldump");
+
+        let opts = DbServiceOptions {
+            version: Some(DbVersion::Mariadb(MARIADB::TenFour)),
+            engine: None,
+            image: None,
+        };
+        assert_eq!(opts.image(), "mariadb:10.4");
+
+        let from_image = |image: &str| DbServiceOptions {
+            engine: None,
+            version: None,
+            image: Some(image.to_string()),
+        };
+        assert_eq!(from_image("mariadb:11.4").client(), "mariadb");
+        assert_eq!(from_image("mariadb:11").dump_client(), "mariadb-dump");
+        assert_eq!(from_image("mariadb:10.4.32").client(), "mysql");
+        assert_eq!(from_image("mariadb:latest").client(), "mariadb");
+        assert_eq!(from_image("mysql:8.0").client(), "mysql");
+    }
+
+    #[test]
+    fn test_from_image() {
+        assert_eq!(
+            DbEngine::from_image("mysql:8.0.36"),
+            Some((DbEngine::Mysql, String::from("8.0")))
+        );
+        assert_eq!(
+            DbEngine::from_image("mariadb:10.6.4-focal"),
+            Some((DbEngine::Mariadb, String::from("10.6")))
+        );
+        assert_eq!(DbEngine::from_image("percona:5.7"), None);
+    }
 }
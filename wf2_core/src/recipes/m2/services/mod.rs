@@ -1,16 +1,47 @@
 //!
 //! M2 Services
 //!
+//! Any service can be removed from a project with `enabled: false`
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::m2::M2Recipe;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     elasticsearch:
+//!       enabled: false
+//!     rabbitmq:
+//!       enabled: false
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let services = (M2Recipe).services(&ctx).expect("valid services");
+//! # assert!(services.service_by_name("elasticsearch").is_none());
+//! # assert!(services.service_by_name("rabbitmq").is_none());
+//! # assert!(services.service_by_name("php").is_some());
+//! ```
+//!
+//! The service, its volumes and any output files that only it uses are removed.
+//! References to it in `depends_on` are removed too, but only for services
+//! that nothing else requires, eg: you cannot disable `db`, since `php`
+//! still needs it.
+//!
 use crate::context::Context;
 use crate::dc_service::DcService;
 use crate::recipes::m2::m2_vars::M2Vars;
+use crate::recipes::m2::multi_store::Stores;
 
 use crate::services::elastic_search::ElasticSearchService;
 use crate::services::mail::MailService;
+use crate::services::open_search::OpenSearchService;
 use crate::services::pwa::{PwaService, PwaServiceOptions};
 use crate::services::traefik::TraefikService;
 use crate::services::varnish::VarnishService;
 use crate::services::{Service, Services};
+use crate::versions::node::NodeVersion;
+use crate::versions::search_engine::SearchEngine;
 
 use crate::recipes::m2::services::db::DbServiceOptions;
 use blackfire::M2BlackfireService;
@@ -20,7 +51,7 @@ use node::M2NodeService;
 use php::PhpService;
 use php_debug::PhpDebugService;
 use rabbit_mq::M2RabbitMqService;
-use redis::M2RedisService;
+use redis::{M2RedisOptions, M2RedisService};
 use unison::{UnisonOptions, UnisonService};
 
 pub const M2_ROOT: &str = "/var/www";
@@ -30,7 +61,30 @@ pub struct M2Services {
 }
 
 impl M2Services {
-    pub fn from_ctx(ctx: &Context, vars: &M2Vars) -> Self {
+    ///
+    /// Services that can be removed without breaking anything else.
+    /// Any `depends_on` references to these are dropped when they're disabled.
+    ///
+    pub const OPTIONAL: [&'static str; 8] = [
+        PhpDebugService::NAME,
+        MailService::NAME,
+        M2BlackfireService::NAME,
+        ElasticSearchService::NAME,
+        OpenSearchService::NAME,
+        M2NodeService::NAME,
+        M2RedisService::NAME,
+        M2RabbitMqService::NAME,
+    ];
+
+    pub fn from_ctx(ctx: &Context, vars: &M2Vars) -> Result<Self, failure::Error> {
+        let search_engine = SearchEngine::from_ctx(ctx);
+        search_engine.validate(ctx)?;
+        Stores::from_ctx(ctx).validate()?;
+
+        if !M2RecipeOptions::is_disabled(ctx, M2NodeService::NAME) {
+            NodeVersion::from_ctx(ctx)?;
+        }
+
         let mut services = vec![
             (UnisonService).dc_service(ctx, vars),
             (TraefikService).dc_service(ctx, &()),
@@ -40,18 +94,71 @@ impl M2Services {
             (DbService).dc_service(ctx, vars),
             (MailService).dc_service(ctx, &()),
             (M2BlackfireService).dc_service(ctx, vars),
-            (ElasticSearchService).dc_service(ctx, &()),
+            if search_engine.is_opensearch() {
+                (OpenSearchService).dc_service(ctx, &())
+            } else {
+                (ElasticSearchService).dc_service(ctx, &())
+            },
             (M2NginxService).dc_service(ctx, vars),
             (M2NodeService).dc_service(ctx, vars),
-            (M2RedisService).dc_service(ctx, vars),
             (M2RabbitMqService).dc_service(ctx, vars),
         ];
 
+        services.extend(M2RedisService::dc_services(ctx, vars)?);
+
         if let Some(pwa_opts) = M2RecipeOptions::get_pwa_options(ctx) {
             services.push((PwaService).dc_service(ctx, &pwa_opts))
         }
 
-        Self { services }
+        let disabled = M2RecipeOptions::disabled_services(ctx);
+
+        if let Some(unknown) = disabled
+            .iter()
+            .find(|name| !services.iter().any(|service| &service.name == *name))
+        {
+            return Err(M2ServiceError::UnknownDisabled(unknown.to_string()).into());
+        }
+
+        let services = services
+            .into_iter()
+            .filter(|service| !disabled.contains(&service.name))
+            .map(|mut service| {
+                if let Some(depends_on) = service.depends_on.as_mut() {
+                    depends_on.retain(|name| {
+                        !(disabled.iter().any(|d| d == name)
+                            && M2Services::OPTIONAL.contains(&name))
+                    });
+                }
+                service
+            })
+            .collect::<Vec<DcService>>();
+
+        let m2_services = Self { services };
+        m2_services.check_dependencies()?;
+        Ok(m2_services)
+    }
+
+    ///
+    /// Ensure every `depends_on` entry still points to a service
+    /// that exists, after any have been disabled
+    ///
+    pub fn check_dependencies(&self) -> Result<(), failure::Error> {
+        for service in &self.services {
+            let missing = service.depends_on.as_ref().and_then(|depends_on| {
+                depends_on
+                    .names()
+                    .into_iter()
+                    .find(|name| self.service_by_name(name).is_none())
+            });
+            if let Some(missing) = missing {
+                return Err(M2ServiceError::MissingDependency {
+                    service: service.name.clone(),
+                    dependency: missing,
+                }
+                .into());
+            }
+        }
+        Ok(())
     }
 }
 
@@ -65,6 +172,16 @@ impl Services for M2Services {
 pub enum M2ServiceError {
     #[fail(display = "{} method not implemented", _0)]
     NotImplemented(String),
+    #[fail(
+        display = "Cannot disable `{}` since it's not a service in this recipe",
+        _0
+    )]
+    UnknownDisabled(String),
+    #[fail(
+        display = "`{}` depends on `{}`, but `{}` is disabled",
+        service, dependency, dependency
+    )]
+    MissingDependency { service: String, dependency: String },
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -72,6 +189,7 @@ pub struct M2ServicesOptions {
     pub unison: Option<UnisonOptions>,
     pub pwa: Option<PwaServiceOptions>,
     pub db: Option<DbServiceOptions>,
+    pub redis: Option<M2RedisOptions>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -86,6 +204,33 @@ impl M2RecipeOptions {
     pub fn has_pwa_options(ctx: &Context) -> bool {
         M2RecipeOptions::get_pwa_options(ctx).is_some()
     }
+    ///
+    /// The names of any services with `enabled: false`, eg:
+    ///
+    /// ```yaml
+    /// options:
+    ///   services:
+    ///     blackfire:
+    ///       enabled: false
+    /// ```
+    ///
+    pub fn disabled_services(ctx: &Context) -> Vec<String> {
+        ctx.options
+            .as_ref()
+            .and_then(|opts| opts["services"].as_mapping())
+            .map_or(vec![], |services| {
+                services
+                    .iter()
+                    .filter(|(_, opts)| opts["enabled"].as_bool() == Some(false))
+                    .filter_map(|(name, _)| name.as_str().map(String::from))
+                    .collect()
+            })
+    }
+    pub fn is_disabled(ctx: &Context, name: &str) -> bool {
+        M2RecipeOptions::disabled_services(ctx)
+            .iter()
+            .any(|disabled| disabled == name)
+    }
 }
 
 pub mod blackfire;
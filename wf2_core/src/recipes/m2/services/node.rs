@@ -6,6 +6,7 @@ use crate::recipes::m2::services::M2_ROOT;
 use crate::services::Service;
 
 use crate::services::node::NodeService;
+use crate::versions::node::NodeVersion;
 
 pub struct M2NodeService;
 
@@ -16,10 +17,21 @@ impl Service<M2Vars> for M2NodeService {
     fn dc_service(&self, ctx: &Context, vars: &M2Vars) -> DcService {
         (NodeService)
             .dc_service(ctx, &())
+            .set_image(self.select_image(ctx))
             .set_working_dir(M2_ROOT)
             .set_init(true)
             .set_volumes(vec![format!("{}:{}", M2Volumes::APP, M2_ROOT)])
             .set_env_file(vec![vars.content[&M2Var::EnvFile].to_string()])
             .finish()
     }
+
+    ///
+    /// An unsupported `.nvmrc` is reported by [`M2Services::from_ctx`](super::M2Services::from_ctx)
+    ///
+    fn select_image(&self, ctx: &Context) -> String {
+        NodeVersion::from_ctx(ctx)
+            .map(|node_version| node_version.node)
+            .unwrap_or_default()
+            .image()
+    }
 }
@@ -1,20 +1,17 @@
 use crate::context::Context;
 use crate::dc_service::DcService;
-use crate::php::PHP;
 use crate::recipes::m2::dc_tasks::M2Volumes;
 use crate::recipes::m2::m2_vars::{M2Var, M2Vars};
 use crate::recipes::m2::services::db::DbService;
 use crate::recipes::m2::services::php_debug::PhpDebugService;
 use crate::recipes::m2::services::M2_ROOT;
 use crate::services::Service;
+use crate::versions::composer::COMPOSER;
 
 pub struct PhpService;
 
 impl PhpService {
-    pub const IMAGE_7_1: &'static str = "wearejh/php:7.1-m2";
-    pub const IMAGE_7_2: &'static str = "wearejh/php:7.2-m2";
-    pub const IMAGE_7_3: &'static str = "wearejh/php:7.3-m2";
-    pub const IMAGE_7_4: &'static str = "wearejh/php:7.4-m2";
+    pub const COMPOSER_HOME: &'static str = "/home/www-data/.composer";
     pub const COMPOSER_CACHE_PATH: &'static str = "/home/www-data/.composer/cache";
 
     pub fn select(ctx: &Context) -> Result<DcService, failure::Error> {
@@ -33,7 +30,7 @@ impl PhpService {
 
 impl Service<M2Vars> for PhpService {
     const NAME: &'static str = "php";
-    const IMAGE: &'static str = PhpService::IMAGE_7_3;
+    const IMAGE: &'static str = "wearejh/php:7.3-m2";
 
     fn dc_service(&self, ctx: &Context, vars: &M2Vars) -> DcService {
         let image = &vars.content[&M2Var::PhpImage].clone();
@@ -42,7 +39,7 @@ impl Service<M2Vars> for PhpService {
                 format!("{}:{}", M2Volumes::APP, M2_ROOT),
                 format!(
                     "{}:{}",
-                    M2Volumes::COMPOSER_CACHE,
+                    COMPOSER::cache_volume_from_ctx(ctx),
                     PhpService::COMPOSER_CACHE_PATH,
                 ),
             ])
@@ -54,13 +51,7 @@ impl Service<M2Vars> for PhpService {
             .finish()
     }
     fn select_image(&self, ctx: &Context) -> String {
-        match ctx.php_version {
-            PHP::SevenOne => PhpService::IMAGE_7_1,
-            PHP::SevenTwo => PhpService::IMAGE_7_2,
-            PHP::SevenThree => PhpService::IMAGE_7_3,
-            PHP::SevenFour => PhpService::IMAGE_7_4,
-        }
-        .to_string()
+        ctx.php_version.image()
     }
 }
 
@@ -83,7 +74,7 @@ mod tests {
         let php = (PhpService).dc_service(&ctx, &vars);
         assert_eq!(php.container_name, "wf2__acme__php");
         assert_eq!(php.name, PhpService::NAME);
-        assert_eq!(php.image, PhpService::IMAGE_7_2);
+        assert_eq!(php.image, PHP::SevenTwo.image());
         Ok(())
     }
 }
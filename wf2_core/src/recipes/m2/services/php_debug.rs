@@ -2,13 +2,40 @@ use crate::context::Context;
 use crate::dc_service::DcService;
 use crate::recipes::m2::m2_vars::M2Vars;
 use crate::recipes::m2::services::php::PhpService;
+use crate::recipes::m2::xdebug::{XdebugHost, XdebugMode, XdebugVersion};
 use crate::services::Service;
 
 pub struct PhpDebugService;
 
+impl PhpDebugService {
+    pub fn environment(ctx: &Context, mode: &str) -> Vec<String> {
+        let mut environment = vec![String::from("XDEBUG_ENABLE=true")];
+        if XdebugVersion::from_php(&ctx.php_version) == XdebugVersion::Three {
+            environment.push(format!("XDEBUG_MODE={}", mode));
+        }
+        environment
+    }
+
+    ///
+    /// Profile every request, writing the results to `dir`
+    ///
+    pub fn profiler_environment(ctx: &Context, dir: &str) -> Vec<String> {
+        let xdebug = XdebugVersion::from_php(&ctx.php_version);
+        let mut environment = PhpDebugService::environment(ctx, "profile");
+        if xdebug == XdebugVersion::Two {
+            environment.push(String::from("XDEBUG_PROFILER=1"));
+        }
+        environment.push(format!(
+            "XDEBUG_CONFIG={}",
+            xdebug.profiler_config(&XdebugHost::from_ctx(ctx), dir)
+        ));
+        environment
+    }
+}
+
 impl Service<M2Vars> for PhpDebugService {
     const NAME: &'static str = "php-debug";
-    const IMAGE: &'static str = PhpService::IMAGE_7_3;
+    const IMAGE: &'static str = PhpService::IMAGE;
     ///
     /// The PHP Debug service is a clone of the regular PHP service
     /// with the following docker compose modifications:
@@ -17,13 +44,17 @@ impl Service<M2Vars> for PhpDebugService {
     /// container_name: "wf2__wf2_default__php-debug"
     /// environment:
     ///   - "XDEBUG_ENABLE=true"
+    ///   - "XDEBUG_MODE=debug" (Xdebug 3 only)
     ///
     fn dc_service(&self, ctx: &Context, vars: &M2Vars) -> DcService {
         (PhpService)
             .dc_service(ctx, vars)
             .set_container_name(ctx.name(), Self::NAME)
             .set_name(Self::NAME)
-            .set_environment(vec!["XDEBUG_ENABLE=true"])
+            .set_environment(PhpDebugService::environment(
+                ctx,
+                &XdebugMode::from_ctx(ctx),
+            ))
             .finish()
     }
 }
@@ -44,7 +75,7 @@ mod tests {
         let php_debug = (PhpDebugService).dc_service(&ctx, &vars);
         assert_eq!(php_debug.container_name, "wf2__acme__php-debug");
         assert_eq!(php_debug.name, PhpDebugService::NAME);
-        assert_eq!(php_debug.image, PhpService::IMAGE_7_2);
+        assert_eq!(php_debug.image, PHP::SevenTwo.image());
         Ok(())
     }
 }
@@ -1,19 +1,256 @@
+//!
+//! Redis, configured under `options.services.redis`
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::m2::M2Recipe;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     redis:
+//!       version: 6.2
+//!       persistence: true
+//!       instances: [cache, fpc, session]
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = (M2Recipe).dc(&ctx).expect("valid dc");
+//! # let services = dc.services.expect("services");
+//! # assert!(services.get("redis").is_none());
+//! # let session = services.get("redis-session").expect("session");
+//! # assert_eq!(session.image, "redis:6.2-alpine");
+//! # assert_eq!(session.command, Some(String::from("redis-server --appendonly yes")));
+//! # assert_eq!(session.volumes, Some(vec![String::from("redis-session-data:/data")]));
+//! # let volumes = dc.volumes.expect("volumes");
+//! # assert!(volumes.contains_key("redis-fpc-data"));
+//! ```
+//!
+//! - `version` picks the image, see [`REDIS`] for the supported versions
+//! - `persistence` enables AOF, with the data kept in a volume
+//! - `instances` replaces the single `redis` service with one per name, eg: `redis-cache`.
+//!
+//! Every instance is added to the `.docker.env` file, so `env.php` templates
+//! can reference them, eg: `REDIS_SESSION_HOST=redis-session` & `REDIS_SESSION_PORT=6379`
+//!
 use crate::context::Context;
 use crate::dc_service::DcService;
 use crate::recipes::m2::m2_vars::{M2Var, M2Vars};
+use crate::recipes::m2::services::M2RecipeOptions;
 use crate::services::redis::RedisService;
 use crate::services::Service;
+use crate::versions::redis::REDIS;
 
 pub struct M2RedisService;
 
+impl M2RedisService {
+    pub const PORT: &'static str = "6379";
+    pub const VOLUME_DATA: &'static str = "/data";
+
+    ///
+    /// Either the single `redis` service, or one for each named instance
+    ///
+    pub fn dc_services(ctx: &Context, vars: &M2Vars) -> Result<Vec<DcService>, failure::Error> {
+        let opts = M2RedisOptions::from_ctx(ctx)?;
+        // a disabled `redis` is removed by name, like any other service
+        if opts.instances.is_empty() || M2RecipeOptions::is_disabled(ctx, Self::NAME) {
+            return Ok(vec![M2RedisService::instance(ctx, vars, &opts, Self::NAME)]);
+        }
+        Ok(opts
+            .service_names()
+            .iter()
+            .map(|name| M2RedisService::instance(ctx, vars, &opts, name))
+            .collect())
+    }
+
+    fn instance(ctx: &Context, vars: &M2Vars, opts: &M2RedisOptions, name: &str) -> DcService {
+        let mut service = DcService::new(ctx.name(), name, opts.image());
+        service
+            .set_labels(vec![Self::TRAEFIK_DISABLE_LABEL.to_string()])
+            .set_healthcheck(RedisService::healthcheck())
+            .set_env_file(vec![vars.content[&M2Var::EnvFile].to_string()]);
+        if opts.persistence {
+            service
+                .set_command("redis-server --appendonly yes")
+                .set_volumes(vec![format!(
+                    "{}:{}",
+                    M2RedisOptions::volume_name(name),
+                    M2RedisService::VOLUME_DATA
+                )]);
+        }
+        service.finish()
+    }
+}
+
 impl Service<M2Vars> for M2RedisService {
     const NAME: &'static str = RedisService::NAME;
     const IMAGE: &'static str = RedisService::IMAGE;
 
     fn dc_service(&self, ctx: &Context, vars: &M2Vars) -> DcService {
-        (RedisService)
-            .dc_service(&ctx, &())
-            .set_env_file(vec![vars.content[&M2Var::EnvFile].to_string()])
-            .finish()
+        let opts = M2RedisOptions::from_ctx(ctx).unwrap_or_default();
+        M2RedisService::instance(ctx, vars, &opts, Self::NAME)
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum M2RedisError {
+    #[fail(display = "Invalid redis options\n{}", _0)]
+    Invalid(String),
+    #[fail(
+        display = "Invalid redis instance name `{}`, only lowercase letters, numbers & `-` are allowed",
+        _0
+    )]
+    InvalidInstance(String),
+    #[fail(display = "The redis instance `{}` is listed more than once", _0)]
+    DuplicateInstance(String),
+}
+
+///
+/// These are the options that can be provided in the wf2 file
+/// under 'options.services.redis'
+///
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct M2RedisOptions {
+    pub version: Option<REDIS>,
+    #[serde(default)]
+    pub persistence: bool,
+    #[serde(default)]
+    pub instances: Vec<String>,
+}
+
+impl M2RedisOptions {
+    pub fn from_ctx(ctx: &Context) -> Result<Self, failure::Error> {
+        let opts: M2RedisOptions = match ctx.options.as_ref().map(|opts| &opts["services"]["redis"])
+        {
+            Some(redis) if !redis.is_null() => serde_yaml::from_value(redis.clone())
+                .map_err(|e| M2RedisError::Invalid(e.to_string()))?,
+            _ => M2RedisOptions::default(),
+        };
+        opts.validate()?;
+        Ok(opts)
+    }
+
+    pub fn validate(&self) -> Result<(), M2RedisError> {
+        for (index, name) in self.instances.iter().enumerate() {
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+            if !valid {
+                return Err(M2RedisError::InvalidInstance(name.clone()));
+            }
+            if self.instances[..index].contains(name) {
+                return Err(M2RedisError::DuplicateInstance(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn image(&self) -> String {
+        self.version.clone().unwrap_or_default().image()
+    }
+
+    ///
+    /// eg: `[redis-cache, redis-session]`, or just `[redis]`
+    ///
+    pub fn service_names(&self) -> Vec<String> {
+        if self.instances.is_empty() {
+            return vec![M2RedisService::NAME.to_string()];
+        }
+        self.instances
+            .iter()
+            .map(|name| format!("{}-{}", M2RedisService::NAME, name))
+            .collect()
+    }
+
+    pub fn volume_name(service_name: &str) -> String {
+        format!("{}-data", service_name)
+    }
+
+    ///
+    /// The volumes needed for persistence, if it's enabled
+    ///
+    pub fn volumes(&self) -> Vec<String> {
+        if !self.persistence {
+            return vec![];
+        }
+        self.service_names()
+            .iter()
+            .map(|name| M2RedisOptions::volume_name(name))
+            .collect()
+    }
+
+    ///
+    /// `REDIS_<NAME>_HOST` & `REDIS_<NAME>_PORT` for every named instance
+    ///
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        self.instances
+            .iter()
+            .flat_map(|name| {
+                let prefix = format!("REDIS_{}", name.to_uppercase().replace('-', "_"));
+                vec![
+                    (
+                        format!("{}_HOST", prefix),
+                        format!("{}-{}", M2RedisService::NAME, name),
+                    ),
+                    (format!("{}_PORT", prefix), M2RedisService::PORT.to_string()),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redis_options() {
+        let ctx = Context::new_from_str(
+            r#"
+            options:
+              services:
+                redis:
+                  instances: [cache, page-cache]
+            "#,
+        )
+        .expect("test");
+        let opts = M2RedisOptions::from_ctx(&ctx).expect("test");
+        assert_eq!(opts.image(), "redis:3-alpine");
+        assert_eq!(opts.volumes(), Vec::<String>::new());
+        assert_eq!(
+            opts.env_vars(),
+            vec![
+                (
+                    String::from("REDIS_CACHE_HOST"),
+                    String::from("redis-cache")
+                ),
+                (String::from("REDIS_CACHE_PORT"), String::from("6379")),
+                (
+                    String::from("REDIS_PAGE_CACHE_HOST"),
+                    String::from("redis-page-cache")
+                ),
+                (String::from("REDIS_PAGE_CACHE_PORT"), String::from("6379")),
+            ]
+        );
+
+        let invalid = |input: &str| {
+            let ctx = Context::new_from_str(input).expect("test");
+            M2RedisOptions::from_ctx(&ctx)
+                .expect_err("test")
+                .to_string()
+        };
+        assert_eq!(
+            invalid("options: { services: { redis: { instances: [cache, cache] } } }"),
+            "The redis instance `cache` is listed more than once"
+        );
+        assert_eq!(
+            invalid("options: { services: { redis: { instances: [Cache] } } }"),
+            "Invalid redis instance name `Cache`, only lowercase letters, numbers & `-` are allowed"
+        );
+        assert!(
+            invalid("options: { services: { redis: { version: 2.8 } } }")
+                .contains("expected either `3`, `5`, `6.0`, `6.2`, `7.0` or `7.2`")
+        );
     }
 }
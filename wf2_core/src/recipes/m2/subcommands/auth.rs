@@ -0,0 +1,499 @@
+//!
+//! Manage the Composer credentials in `auth.json`
+//!
+//! Only `http-basic` credentials are managed, which is what `repo.magento.com` uses.
+//!
+//! # Example: add your Magento keys to the project
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 auth set repo.magento.com --username 1234 --password 5678
+//! # "#;
+//! # let (_commands, (_read, write, _delete)) = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .file_ops_paths_commands();
+//! # assert_eq!(write, vec!["/users/shane/auth.json"]);
+//! ```
+//!
+//! Without `--username` & `--password`, the keys saved by `wf2 m2-playground` are used
+//! for `repo.magento.com`.
+//!
+//! # Example: the global composer home
+//!
+//! `--global` updates the `auth.json` in the composer home inside the PHP container
+//! instead. It's kept until the container is recreated.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 auth set repo.magento.com --username 1234 --password 5678 --global
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert_eq!(commands, vec![
+//! #     "docker exec -u www-data wf2__shane__php composer config --global 'http-basic.repo.magento.com' '1234' '5678'"
+//! # ]);
+//! ```
+//!
+//! # Example: list & remove
+//!
+//! Only usernames are shown.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 auth list
+//! # "#;
+//! # let _tasks = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .tasks();
+//! ```
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 auth remove composer.example.com --global
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert_eq!(commands, vec![
+//! #     "docker exec -u www-data wf2__shane__php composer config --global --unset 'http-basic.composer.example.com'"
+//! # ]);
+//! ```
+//!
+//! # Example: check your keys
+//!
+//! This makes a request to the repository with the credentials from `auth.json`,
+//! so expired or mistyped keys are found before `composer install`.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 auth check
+//! # "#;
+//! # let _tasks = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .tasks();
+//! ```
+//!
+//! Other repositories can be checked with `--url`, eg:
+//!
+//! ```shell
+//! wf2 auth check --url https://composer.example.com/packages.json
+//! ```
+//!
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::file::File;
+use crate::recipes::m2::output_files::auth::Auth;
+use crate::recipes::m2::services::php::PhpService;
+use crate::recipes::m2::subcommands::m2_playground::M2Playground;
+use crate::task::Task;
+use crate::util::{shell_quote, two_col};
+use crate::versions::composer::COMPOSER;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use futures::future::lazy;
+use reqwest::header::{AUTHORIZATION, USER_AGENT};
+use reqwest::StatusCode;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Debug, Fail)]
+pub enum AuthCmdError {
+    #[fail(
+        display = "Missing credentials for {}, use `--username` & `--password`",
+        _0
+    )]
+    MissingCredentials(String),
+    #[fail(display = "Invalid repository URL `{}`", _0)]
+    InvalidUrl(String),
+    #[fail(
+        display = "{} rejected the credentials for `{}`, they may have expired",
+        _0, _1
+    )]
+    Rejected(String, String),
+    #[fail(display = "Could not check {}, status code: {}", _0, _1)]
+    Unexpected(String, StatusCode),
+}
+
+#[doc_link::doc_link("/recipes/m2/subcommands/auth")]
+pub struct AuthCmd;
+
+impl AuthCmd {
+    const NAME: &'static str = "auth";
+    const ABOUT: &'static str = "Manage the Composer credentials in auth.json";
+
+    const LIST: &'static str = "list";
+    const SET: &'static str = "set";
+    const REMOVE: &'static str = "remove";
+    const CHECK: &'static str = "check";
+
+    pub const MAGENTO_REPO: &'static str = "repo.magento.com";
+    pub const DEFAULT_CHECK_URL: &'static str = "https://repo.magento.com/packages.json";
+
+    const NO_CREDENTIALS: &'static str = "No http-basic credentials found";
+
+    fn list(ctx: &Context, global: bool) -> Result<Vec<Task>, failure::Error> {
+        if global {
+            return AuthCmd::list_global(ctx).map(|task| vec![task]);
+        }
+        let auth = Auth::from_ctx(ctx)?;
+        if !auth.file_path().exists() {
+            return Ok(vec![Task::notify_info(format!(
+                "{} doesn't exist yet",
+                auth.file_path_string()
+            ))]);
+        }
+        match AuthCmd::list_rows(&AuthCmd::read(&auth.file_path())?) {
+            Some(rows) => Ok(vec![Task::notify(rows)]),
+            None => Ok(vec![Task::notify_info(AuthCmd::NO_CREDENTIALS)]),
+        }
+    }
+
+    ///
+    /// The file is read from the container at run time, but only
+    /// hosts & usernames are printed, just like the project's `auth.json`
+    ///
+    fn list_global(ctx: &Context) -> Result<Task, failure::Error> {
+        let php = PhpService::select(ctx)?;
+        let container = php.container_name;
+        let path = format!("{}/auth.json", PhpService::COMPOSER_HOME);
+        Ok(Task::Exec {
+            description: Some(format!("list the credentials in {}", path)),
+            exec: Box::new(lazy(move || {
+                let output = Command::new("docker")
+                    .args(&["exec", "-u", "www-data", &container, "cat", &path])
+                    .output()?;
+                if !output.status.success() {
+                    println!("{} doesn't exist yet", path);
+                    return Ok(());
+                }
+                let json = serde_json::from_slice(&output.stdout)?;
+                println!(
+                    "{}",
+                    AuthCmd::list_rows(&json)
+                        .unwrap_or_else(|| AuthCmd::NO_CREDENTIALS.to_string())
+                );
+                Ok(())
+            })),
+        })
+    }
+
+    ///
+    /// Hosts & usernames, never passwords
+    ///
+    fn list_rows(json: &serde_json::Value) -> Option<String> {
+        let rows = Auth::list(json);
+        if rows.is_empty() {
+            return None;
+        }
+        Some(two_col(rows))
+    }
+
+    fn set(
+        ctx: &Context,
+        host: &str,
+        username: Option<&str>,
+        password: Option<&str>,
+        global: bool,
+    ) -> Result<Vec<Task>, failure::Error> {
+        let (username, password) = match (username, password) {
+            (Some(username), Some(password)) => (username.to_string(), password.to_string()),
+            _ => AuthCmd::playground_credentials(host)
+                .ok_or_else(|| AuthCmdError::MissingCredentials(host.to_string()))?,
+        };
+        if global {
+            let key = format!("{}.{}", Auth::HTTP_BASIC, host);
+            return AuthCmd::composer_config(ctx, None, &[&key, &username, &password]);
+        }
+        let auth = Auth::from_ctx(ctx)?;
+        let mut json = AuthCmd::read(&auth.file_path())?;
+        Auth::set(&mut json, host, &username, &password)?;
+        Ok(vec![
+            AuthCmd::write(&auth, &json)?,
+            Task::notify_info(format!("Saved the credentials for {}", host)),
+        ])
+    }
+
+    fn remove(ctx: &Context, host: &str, global: bool) -> Result<Vec<Task>, failure::Error> {
+        if global {
+            let key = format!("{}.{}", Auth::HTTP_BASIC, host);
+            return AuthCmd::composer_config(ctx, Some("--unset"), &[&key]);
+        }
+        let auth = Auth::from_ctx(ctx)?;
+        let mut json = AuthCmd::read(&auth.file_path())?;
+        if !Auth::remove(&mut json, host) {
+            return Ok(vec![Task::notify_info(format!(
+                "There were no credentials for {}",
+                host
+            ))]);
+        }
+        Ok(vec![
+            AuthCmd::write(&auth, &json)?,
+            Task::notify_info(format!("Removed the credentials for {}", host)),
+        ])
+    }
+
+    ///
+    /// Credentials are resolved now, but the request is made at run time
+    ///
+    fn check(ctx: &Context, url: &str) -> Result<Vec<Task>, failure::Error> {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .ok_or_else(|| AuthCmdError::InvalidUrl(url.to_string()))?;
+        let auth = Auth::from_ctx(ctx)?;
+        let (username, password) = Auth::credentials(&AuthCmd::read(&auth.file_path())?, &host)
+            .or_else(|| AuthCmd::playground_credentials(&host))
+            .ok_or_else(|| AuthCmdError::MissingCredentials(host.clone()))?;
+        let url = url.to_string();
+        Ok(vec![Task::Exec {
+            description: Some(format!("check the credentials for {}", host)),
+            exec: Box::new(lazy(move || {
+                let res = reqwest::Client::new()
+                    .get(&url)
+                    .header(USER_AGENT, "composer")
+                    .header(
+                        AUTHORIZATION,
+                        format!(
+                            "Basic {}",
+                            base64::encode(&format!("{}:{}", username, password))
+                        ),
+                    )
+                    .send()?;
+                match res.status() {
+                    StatusCode::OK => {
+                        println!("The credentials for {} are valid", host);
+                        Ok(())
+                    }
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                        Err(AuthCmdError::Rejected(host, username).into())
+                    }
+                    status => Err(AuthCmdError::Unexpected(host, status).into()),
+                }
+            })),
+        }])
+    }
+
+    ///
+    /// The keys saved by `wf2 m2-playground`, which are only for `repo.magento.com`
+    ///
+    fn playground_credentials(host: &str) -> Option<(String, String)> {
+        if host != AuthCmd::MAGENTO_REPO {
+            return None;
+        }
+        M2Playground::from_file().map(|pg| (pg.username, pg.password))
+    }
+
+    ///
+    /// A missing file is the same as an empty one, but anything else
+    /// (like a permissions problem) shouldn't lead to it being overwritten
+    ///
+    fn read(path: &Path) -> Result<serde_json::Value, failure::Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(serde_json::json!({})),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write(auth: &Auth, json: &serde_json::Value) -> Result<Task, failure::Error> {
+        let mut bytes = serde_json::to_vec_pretty(json)?;
+        bytes.push(b'\n');
+        Ok(Task::file_write(
+            auth.file_path(),
+            "Writes auth.json",
+            bytes,
+        ))
+    }
+
+    ///
+    /// Each argument is quoted, since credentials can contain anything
+    ///
+    fn composer_config(
+        ctx: &Context,
+        flag: Option<&str>,
+        args: &[&str],
+    ) -> Result<Vec<Task>, failure::Error> {
+        let args = flag
+            .map(String::from)
+            .into_iter()
+            .chain(args.iter().map(|arg| shell_quote(arg)))
+            .collect::<Vec<String>>();
+        let command = format!(
+            "{} config --global {}",
+            COMPOSER::binary_from_ctx(ctx),
+            args.join(" ")
+        );
+        AuthCmd::global(ctx, &command).map(|task| vec![task])
+    }
+
+    ///
+    /// Run a command as `www-data`, so it uses the composer home
+    ///
+    fn global(ctx: &Context, command: &str) -> Result<Task, failure::Error> {
+        let php = PhpService::select(ctx)?;
+        Ok(Task::simple_command(format!(
+            "docker exec -u www-data {} {}",
+            php.container_name, command
+        )))
+    }
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for AuthCmd {
+    fn name(&self) -> String {
+        String::from(AuthCmd::NAME)
+    }
+
+    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        let tasks = match matches.map(|m| m.subcommand()) {
+            Some((AuthCmd::LIST, Some(m))) => AuthCmd::list(ctx, m.is_present("global")),
+            Some((AuthCmd::SET, Some(m))) => AuthCmd::set(
+                ctx,
+                m.value_of("host").expect("guarded by clap"),
+                m.value_of("username"),
+                m.value_of("password"),
+                m.is_present("global"),
+            ),
+            Some((AuthCmd::REMOVE, Some(m))) => AuthCmd::remove(
+                ctx,
+                m.value_of("host").expect("guarded by clap"),
+                m.is_present("global"),
+            ),
+            Some((AuthCmd::CHECK, Some(m))) => {
+                AuthCmd::check(ctx, m.value_of("url").unwrap_or(AuthCmd::DEFAULT_CHECK_URL))
+            }
+            _ => Ok(vec![Task::notify_error(
+                "missing `list`, `set`, `remove` or `check`",
+            )]),
+        };
+        Some(tasks.unwrap_or_else(Task::task_err_vec))
+    }
+
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        let global = || {
+            Arg::with_name("global")
+                .long("global")
+                .help("Use the composer home inside the PHP container")
+        };
+        let host = || {
+            Arg::with_name("host")
+                .required(true)
+                .help("eg: repo.magento.com")
+        };
+        vec![App::new(AuthCmd::NAME)
+            .about(AuthCmd::ABOUT)
+            .after_help(AuthCmd::DOC_LINK)
+            .subcommands(vec![
+                SubCommand::with_name(AuthCmd::LIST)
+                    .display_order(0)
+                    .about("List the hosts with credentials")
+                    .arg(global()),
+                SubCommand::with_name(AuthCmd::SET)
+                    .display_order(0)
+                    .about("Add or replace the credentials for a host")
+                    .arg(host())
+                    .arg(
+                        Arg::with_name("username")
+                            .long("username")
+                            .takes_value(true)
+                            .requires("password"),
+                    )
+                    .arg(
+                        Arg::with_name("password")
+                            .long("password")
+                            .takes_value(true)
+                            .requires("username"),
+                    )
+                    .arg(global()),
+                SubCommand::with_name(AuthCmd::REMOVE)
+                    .display_order(0)
+                    .about("Remove the credentials for a host")
+                    .arg(host())
+                    .arg(global()),
+                SubCommand::with_name(AuthCmd::CHECK)
+                    .display_order(0)
+                    .about("Check the credentials against a repository")
+                    .arg(
+                        Arg::with_name("url")
+                            .long("url")
+                            .takes_value(true)
+                            .help("defaults to https://repo.magento.com/packages.json"),
+                    ),
+            ])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::cli_input::CLIInput;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::test::Test;
+
+    #[test]
+    fn test_list_global_hides_passwords() {
+        let commands = Test::from_cmd("wf2 auth list --global")
+            .with_recipe(RecipeKinds::M2_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/shane"))
+            .commands();
+        assert!(commands.is_empty());
+
+        let json = serde_json::json!({
+            "http-basic": {
+                "repo.magento.com": { "username": "public", "password": "private" }
+            }
+        });
+        let rows = AuthCmd::list_rows(&json).expect("test");
+        assert!(rows.contains("repo.magento.com"));
+        assert!(rows.contains("public"));
+        assert!(!rows.contains("private"));
+        assert_eq!(AuthCmd::list_rows(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn test_set_requires_both_credentials() {
+        let ctx = Context::default();
+        let app = App::new("wf2").subcommands((AuthCmd).subcommands(&ctx));
+        let matches = |args: &[&str]| app.clone().get_matches_from_safe(args);
+        assert!(matches(&[
+            "wf2",
+            "auth",
+            "set",
+            "repo.magento.com",
+            "--username",
+            "1234"
+        ])
+        .is_err());
+        assert!(matches(&[
+            "wf2",
+            "auth",
+            "set",
+            "repo.magento.com",
+            "--password",
+            "5678"
+        ])
+        .is_err());
+        assert!(matches(&["wf2", "auth", "set", "repo.magento.com"]).is_ok());
+    }
+}
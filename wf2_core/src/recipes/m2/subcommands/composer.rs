@@ -20,9 +20,30 @@
 //! # let expected = "docker exec -it -u www-data wf2__shane__php composer install -vvv";
 //! # assert_eq!(commands, vec![expected]);
 //! ```
+//!
+//! # Example: Composer 1
+//!
+//! With `composer_version: 1` in your wf2.yml, the `composer1` binary is used instead
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 composer install
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_file("../fixtures/config_composer_1.yaml")
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # let expected = "docker exec -it -u www-data wf2__shane__php composer1 install";
+//! # assert_eq!(commands, vec![expected]);
+//! ```
 use crate::context::Context;
 use crate::recipes::m2::services::php::PhpService;
 use crate::task::Task;
+use crate::versions::composer::COMPOSER;
 
 pub struct ComposerPassThru;
 
@@ -36,7 +57,7 @@ pub fn composer(ctx: &Context, trailing: &[String]) -> Vec<Task> {
             let exec_command = format!(
                 r#"docker exec -it -u www-data {container_name} {trailing_args}"#,
                 container_name = service.container_name,
-                trailing_args = trailing.join(" ")
+                trailing_args = COMPOSER::replace_binary(ctx, &trailing.join(" "))
             );
             vec![Task::simple_command(exec_command)]
         })
@@ -19,6 +19,7 @@ use crate::recipes::m2::tasks::env_php::EnvPhp;
 use crate::recipes::m2::services::unison::UnisonService;
 use crate::services::Service;
 use crate::task::Task;
+use crate::tasks::compose_version::compose_version_task;
 use clap::{App, ArgMatches};
 
 #[doc_link::doc_link("/recipes/m2/subcommands/doctor")]
@@ -45,9 +46,11 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2Doctor {
 }
 
 ///
-/// Try to fix common issues, for now just the unison thing
+/// Try to fix common issues, for now just the unison thing,
+/// and check that docker-compose is recent enough
 ///
 fn doctor(ctx: &Context) -> Vec<Task> {
+    let compose = vec![compose_version_task()];
     let unison = unison_fix(&ctx);
     let php_env = vec![EnvPhp::comparison_task(&ctx)];
     let notify = vec![Task::notify(
@@ -56,6 +59,7 @@ fn doctor(ctx: &Context) -> Vec<Task> {
 
     vec![]
         .into_iter()
+        .chain(compose.into_iter())
         .chain(unison.into_iter())
         .chain(php_env.into_iter())
         .chain(notify.into_iter())
@@ -2,35 +2,36 @@
 //! All of the Magento 2 specific sub-commands
 //!
 use crate::commands::{CliCommand, Commands};
-use db_dump::M2DbDump;
-use db_import::M2DbImport;
 use doctor::M2Doctor;
 
 use eject::M2Eject;
-use exec::M2Exec;
-use list_images::M2ListImages;
 use m2_playground_cmd::M2PlaygroundCmd;
-use pull::M2Pull;
-use push::M2Push;
-use sql::SqlCmd;
 
 use crate::context::Context;
 use crate::recipes::m2::M2Recipe;
+use crate::subcommands::certs::CertsCmd;
+use crate::subcommands::db_dump::DbDump;
+use crate::subcommands::db_import::DbImport;
 use crate::subcommands::down::DcDown;
+use crate::subcommands::exec::Exec;
+use crate::subcommands::hosts::HostsCmd;
+use crate::subcommands::list_images::ListImages;
+use crate::subcommands::pull::Pull;
+use crate::subcommands::push::Push;
+use crate::subcommands::sql::SqlCmd;
 use crate::subcommands::stop::DcStop;
+use crate::subcommands::update_images::UpdateImages;
+use auth::AuthCmd;
+use profile::ProfileCmd;
 use up::M2Up;
-use update_images::M2UpdateImages;
 use varnish::VarnishCmd;
 use xdebug::XdebugCmd;
 
+pub mod auth;
 pub mod composer;
-pub mod db_dump;
-pub mod db_import;
 pub mod doctor;
 pub mod down;
 pub mod eject;
-pub mod exec;
-pub mod list_images;
 pub mod m;
 pub mod m2_playground;
 #[doc(hidden)]
@@ -39,14 +40,11 @@ pub mod m2_playground_cmd;
 pub mod m2_playground_help;
 pub mod n98;
 pub mod node;
-pub mod pull;
-pub mod push;
-pub mod sql;
+pub mod profile;
 pub mod stop;
 pub mod up;
 #[doc(hidden)]
 pub mod up_help;
-pub mod update_images;
 pub mod varnish;
 pub mod xdebug;
 
@@ -56,18 +54,22 @@ impl<'a, 'b> Commands<'a, 'b> for M2Recipe {
             Box::new(M2Up),
             Box::new(DcDown),
             Box::new(DcStop),
-            Box::new(M2DbImport),
-            Box::new(M2DbDump),
+            Box::new(DbImport),
+            Box::new(DbDump),
             Box::new(M2Doctor),
-            Box::new(M2Push),
+            Box::new(Push),
             Box::new(M2Eject),
-            Box::new(M2Exec),
-            Box::new(M2Pull),
+            Box::new(Exec),
+            Box::new(Pull),
             Box::new(VarnishCmd),
-            Box::new(M2ListImages),
-            Box::new(M2UpdateImages),
+            Box::new(ListImages),
+            Box::new(UpdateImages),
             Box::new(XdebugCmd),
+            Box::new(ProfileCmd),
+            Box::new(AuthCmd),
             Box::new(SqlCmd),
+            Box::new(CertsCmd),
+            Box::new(HostsCmd),
         ]
     }
     fn global_subcommands(&self) -> Vec<Box<dyn CliCommand<'a, 'b>>> {
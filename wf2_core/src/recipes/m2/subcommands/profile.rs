@@ -0,0 +1,391 @@
+//!
+//! Collect Xdebug or Blackfire profiles
+//!
+//! Profiling runs on the `php-debug` container, so `start` also sends every request
+//! there, just like `wf2 xdebug enable`.
+//!
+//! # Example: profile with Xdebug
+//!
+//! `php-debug` is recreated with the profiler switched on, writing cachegrind
+//! files to `/tmp/profiles` inside the container.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 profile start
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert!(commands[0].ends_with("docker-compose.yml up -d php-debug"));
+//! # assert_eq!(commands[1], "docker exec wf2__shane__php-debug mkdir -p -m 0777 /tmp/profiles");
+//! # assert!(commands[2].ends_with("docker-compose.yml exec nginx nginx -s reload"));
+//! ```
+//!
+//! Then, once you've made the requests you're interested in, copy the
+//! profiles to a `profiles` folder in your project
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 profile pull
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert_eq!(commands, vec![
+//! #     "docker exec wf2__shane__php-debug test -e /tmp/profiles",
+//! #     "docker cp wf2__shane__php-debug:/tmp/profiles /users/shane",
+//! # ]);
+//! ```
+//!
+//! `stop` pulls the profiles one last time, since they're lost when `php-debug`
+//! is recreated without the profiler. Xdebug is then left enabled or disabled,
+//! just as it was before `start`.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 profile stop
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert_eq!(commands[1], "docker cp wf2__shane__php-debug:/tmp/profiles /users/shane");
+//! # assert!(commands[2].ends_with("docker-compose.yml up -d php-debug"));
+//! ```
+//!
+//! # Example: profile a URL with Blackfire
+//!
+//! This needs your Blackfire credentials under `env` in your wf2.yml
+//!
+//! ```yaml
+//! env:
+//!   BLACKFIRE_CLIENT_ID: "..."
+//!   BLACKFIRE_CLIENT_TOKEN: "..."
+//!   BLACKFIRE_SERVER_ID: "..."
+//!   BLACKFIRE_SERVER_TOKEN: "..."
+//! ```
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 profile start --blackfire https://local.m2/checkout
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_file("../fixtures/config_blackfire.yaml")
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert_eq!(commands, vec!["docker exec wf2__shane__blackfire blackfire curl 'https://local.m2/checkout'"]);
+//! ```
+//!
+//! The URL is requested from inside the `blackfire` container.
+//!
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::dc_service::DcService;
+use crate::file::File;
+use crate::recipes::m2::m2_vars::M2Vars;
+use crate::recipes::m2::output_files::nginx_upstream::NginxUpstream;
+use crate::recipes::m2::services::blackfire::M2BlackfireService;
+use crate::recipes::m2::services::php_debug::PhpDebugService;
+use crate::recipes::m2::services::M2RecipeOptions;
+use crate::recipes::m2::subcommands::xdebug::XdebugCmd;
+use crate::recipes::m2::xdebug::XdebugMode;
+use crate::services::Service;
+use crate::subcommands::pull::pull;
+use crate::task::Task;
+use crate::util::shell_quote;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs;
+
+#[derive(Debug, Fail)]
+pub enum ProfileError {
+    #[fail(display = "Profiling needs `php-debug`, but it's disabled in your wf2.yml")]
+    PhpDebugDisabled,
+    #[fail(display = "`blackfire` is disabled in your wf2.yml")]
+    BlackfireDisabled,
+    #[fail(
+        display = "Missing Blackfire credentials, add the following under `env` in your wf2.yml: {}",
+        _0
+    )]
+    MissingBlackfireCredentials(String),
+}
+
+#[doc_link::doc_link("/recipes/m2/subcommands/profile")]
+pub struct ProfileCmd;
+
+impl ProfileCmd {
+    const NAME: &'static str = "profile";
+    const ABOUT: &'static str = "Collect Xdebug or Blackfire profiles";
+
+    const START: &'static str = "start";
+    const STOP: &'static str = "stop";
+    const PULL: &'static str = "pull";
+
+    ///
+    /// Where Xdebug writes profiles inside `php-debug`. It's pulled
+    /// to `profiles` in the project
+    ///
+    pub const CONTAINER_ROOT: &'static str = "/tmp";
+    pub const PROFILES: &'static str = "profiles";
+
+    ///
+    /// Whether Xdebug was enabled before `start`, so that `stop` can put it back
+    ///
+    pub const STATE_PATH: &'static str = "xdebug/before-profile";
+
+    pub const BLACKFIRE_CREDENTIALS: [&'static str; 4] = [
+        "BLACKFIRE_CLIENT_ID",
+        "BLACKFIRE_CLIENT_TOKEN",
+        "BLACKFIRE_SERVER_ID",
+        "BLACKFIRE_SERVER_TOKEN",
+    ];
+
+    fn start(ctx: &Context) -> Result<Vec<Task>, failure::Error> {
+        let php_debug = ProfileCmd::php_debug(ctx)?;
+        let dir = ProfileCmd::container_dir();
+        let upstream = NginxUpstream::from_ctx(ctx)?.file_path();
+        let was_enabled = fs::read_to_string(upstream)
+            .map(|content| XdebugCmd::is_enabled(&content))
+            .unwrap_or(false);
+        let before = vec![
+            Task::file_write(
+                ctx.output_file_path(ProfileCmd::STATE_PATH),
+                "Writes whether Xdebug was enabled",
+                if was_enabled { "enabled" } else { "disabled" },
+            ),
+            Task::notify_info(format!("recreating php-debug, writing profiles to {}", dir)),
+            XdebugCmd::php_debug_up(ctx, PhpDebugService::profiler_environment(ctx, &dir))?,
+            Task::simple_command(format!(
+                "docker exec {} mkdir -p -m 0777 {}",
+                php_debug.container_name, dir
+            )),
+        ];
+        Ok(XdebugCmd::toggle(ctx, true, before))
+    }
+
+    fn stop(ctx: &Context) -> Result<Vec<Task>, failure::Error> {
+        let was_enabled = fs::read_to_string(ctx.output_file_path(ProfileCmd::STATE_PATH))
+            .map(|state| state.trim() == "enabled")
+            .unwrap_or(false);
+        let mut before = ProfileCmd::pull(ctx)?;
+        before.extend(vec![
+            Task::notify_info("recreating php-debug without the profiler"),
+            XdebugCmd::php_debug_up(
+                ctx,
+                PhpDebugService::environment(ctx, &XdebugMode::from_ctx(ctx)),
+            )?,
+        ]);
+        Ok(XdebugCmd::toggle(ctx, was_enabled, before))
+    }
+
+    fn pull(ctx: &Context) -> Result<Vec<Task>, failure::Error> {
+        let php_debug = ProfileCmd::php_debug(ctx)?;
+        Ok(pull(
+            ctx,
+            php_debug,
+            ProfileCmd::CONTAINER_ROOT,
+            vec![ProfileCmd::PROFILES.to_string()],
+        ))
+    }
+
+    fn blackfire(ctx: &Context, url: &str) -> Result<Vec<Task>, failure::Error> {
+        ProfileCmd::validate_blackfire(ctx)?;
+        let vars = M2Vars::from_ctx(ctx)?;
+        let blackfire = (M2BlackfireService).dc_service(ctx, &vars);
+        Ok(vec![Task::simple_command(format!(
+            "docker exec {} blackfire curl {}",
+            blackfire.container_name,
+            shell_quote(url)
+        ))])
+    }
+
+    ///
+    /// Every credential must be given in `env`, since the defaults are placeholders
+    ///
+    pub fn validate_blackfire(ctx: &Context) -> Result<(), ProfileError> {
+        if M2RecipeOptions::is_disabled(ctx, M2BlackfireService::NAME) {
+            return Err(ProfileError::BlackfireDisabled);
+        }
+        let missing = ProfileCmd::BLACKFIRE_CREDENTIALS
+            .iter()
+            .filter(|key| ProfileCmd::env_value(ctx, key).is_none())
+            .cloned()
+            .collect::<Vec<&str>>();
+        if !missing.is_empty() {
+            return Err(ProfileError::MissingBlackfireCredentials(
+                missing.join(", "),
+            ));
+        }
+        Ok(())
+    }
+
+    ///
+    /// A non-empty value from `env`
+    ///
+    fn env_value(ctx: &Context, key: &str) -> Option<String> {
+        ctx.env
+            .as_ref()
+            .and_then(|env| env.get(key))
+            .and_then(|value| match value {
+                serde_yaml::Value::String(s) => Some(s.trim().to_string()),
+                serde_yaml::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .filter(|value| !value.is_empty())
+    }
+
+    fn php_debug(ctx: &Context) -> Result<DcService, failure::Error> {
+        if M2RecipeOptions::is_disabled(ctx, PhpDebugService::NAME) {
+            return Err(ProfileError::PhpDebugDisabled.into());
+        }
+        let vars = M2Vars::from_ctx(ctx)?;
+        Ok((PhpDebugService).dc_service(ctx, &vars))
+    }
+
+    fn container_dir() -> String {
+        format!("{}/{}", ProfileCmd::CONTAINER_ROOT, ProfileCmd::PROFILES)
+    }
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for ProfileCmd {
+    fn name(&self) -> String {
+        String::from(ProfileCmd::NAME)
+    }
+
+    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        let tasks = match matches.map(|m| m.subcommand()) {
+            Some((ProfileCmd::START, m)) => match m.and_then(|m| m.value_of("blackfire")) {
+                Some(url) => ProfileCmd::blackfire(ctx, url),
+                None => ProfileCmd::start(ctx),
+            },
+            Some((ProfileCmd::STOP, _)) => ProfileCmd::stop(ctx),
+            Some((ProfileCmd::PULL, _)) => ProfileCmd::pull(ctx),
+            _ => Ok(vec![Task::notify_error(
+                "missing `start`, `stop` or `pull`",
+            )]),
+        };
+        Some(tasks.unwrap_or_else(Task::task_err_vec))
+    }
+
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(ProfileCmd::NAME)
+            .about(ProfileCmd::ABOUT)
+            .after_help(ProfileCmd::DOC_LINK)
+            .subcommands(vec![
+                SubCommand::with_name(ProfileCmd::START)
+                    .display_order(0)
+                    .about("Start profiling every request with Xdebug")
+                    .arg(
+                        Arg::with_name("blackfire")
+                            .long("blackfire")
+                            .takes_value(true)
+                            .value_name("url")
+                            .help("Profile a single URL with `blackfire curl` instead"),
+                    ),
+                SubCommand::with_name(ProfileCmd::STOP)
+                    .display_order(0)
+                    .about("Pull the profiles, then stop profiling"),
+                SubCommand::with_name(ProfileCmd::PULL)
+                    .display_order(0)
+                    .about("Copy the profiles to `profiles` in the project"),
+            ])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::cli_input::CLIInput;
+    use crate::file_op::FileOp;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::test::Test;
+
+    #[test]
+    fn test_stop_restores_xdebug() -> Result<(), failure::Error> {
+        let upstream = |cwd: &std::path::Path| {
+            Test::from_cmd("wf2 profile stop")
+                .with_recipe(RecipeKinds::M2_NAME)
+                .with_cli_input(CLIInput::from_cwd(cwd))
+                .file_ops()
+                .into_iter()
+                .find_map(|op| match op {
+                    FileOp::Write { path, content } if path.ends_with("upstream.conf") => {
+                        Some(String::from_utf8_lossy(&content).to_string())
+                    }
+                    _ => None,
+                })
+                .expect("test")
+        };
+        let dir = tempfile::tempdir()?;
+        assert!(!XdebugCmd::is_enabled(&upstream(dir.path())));
+
+        let ctx = Context {
+            recipe: Some(RecipeKinds::M2),
+            ..Context::new(dir.path())
+        };
+        let state = ctx.output_file_path(ProfileCmd::STATE_PATH);
+        fs::create_dir_all(state.parent().expect("test"))?;
+        fs::write(&state, "enabled")?;
+        assert!(XdebugCmd::is_enabled(&upstream(dir.path())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blackfire_url_is_quoted() {
+        let commands = Test::from_cmd("wf2 profile start --blackfire https://acme.m2/?a=1&b=2")
+            .with_recipe(RecipeKinds::M2_NAME)
+            .with_file("../fixtures/config_blackfire.yaml")
+            .with_cli_input(CLIInput::from_cwd("/users/shane"))
+            .commands();
+        assert_eq!(
+            commands,
+            vec!["docker exec wf2__shane__blackfire blackfire curl 'https://acme.m2/?a=1&b=2'"]
+        );
+    }
+
+    #[test]
+    fn test_validate_blackfire() {
+        let ctx = Context::new_from_str(
+            r#"
+            env:
+              BLACKFIRE_CLIENT_ID: abc
+              BLACKFIRE_CLIENT_TOKEN: ""
+            "#,
+        )
+        .expect("test");
+        assert_eq!(
+            ProfileCmd::validate_blackfire(&ctx).expect_err("test").to_string(),
+            "Missing Blackfire credentials, add the following under `env` in your wf2.yml: BLACKFIRE_CLIENT_TOKEN, BLACKFIRE_SERVER_ID, BLACKFIRE_SERVER_TOKEN"
+        );
+
+        let ctx = Context::new_from_str(
+            r#"
+            options:
+              services:
+                blackfire:
+                  enabled: false
+            "#,
+        )
+        .expect("test");
+        assert_eq!(
+            ProfileCmd::validate_blackfire(&ctx)
+                .expect_err("test")
+                .to_string(),
+            "`blackfire` is disabled in your wf2.yml"
+        );
+    }
+}
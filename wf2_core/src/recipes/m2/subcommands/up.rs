@@ -23,6 +23,7 @@
 //! #         "/users/shane/.wf2_m2_shane/traefik/traefik.toml",
 //! #         "/users/shane/.wf2_m2_shane/nginx/sites/upstream.conf",
 //! #         "/users/shane/.wf2_m2_shane/nginx/sites/site.conf",
+//! #         "/users/shane/.wf2_m2_shane/varnish/default.vcl",
 //! #         "/users/shane/.wf2_m2_shane/mysql/mysqlconf/mysql.cnf",
 //! #         "/users/shane/.wf2_m2_shane/mysql/init-scripts/init-db.sh",
 //! #     ]
@@ -128,6 +129,79 @@
 //! # ]);
 //! ```
 //!
+//! ## wait for services to be ready `--wait`
+//!
+//! `docker-compose up -d` returns before MySQL, Elasticsearch etc are
+//! accepting connections. Add `--wait` to block until every service is
+//! healthy (or running, if it has no healthcheck). `--timeout` defaults to 180 seconds.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::task::Task;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # let cmd = r#"
+//! wf2 up --wait --timeout 300
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd)
+//! #   .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #   .with_file("../fixtures/config_01.yaml")
+//! #   .tasks();
+//! # let has_wait = tasks.iter().any(|t| match t {
+//! #     Task::Exec { description: Some(d), .. } => d == "wait up to 300s for services to be ready",
+//! #     _ => false,
+//! # });
+//! # assert!(has_wait);
+//! ```
+//!
+//! ## published ports
+//!
+//! Before starting, every published host port (such as `3306` for the database) is checked.
+//! If one is already in use, by a local MySQL or another project, `up` stops with a suggestion.
+//!
+//! Either change the port for that service in `wf2.yml`
+//!
+//! ```yaml
+//! options:
+//!   services:
+//!     db:
+//!       ports: [ "3307:3306" ]
+//! ```
+//!
+//! or add `--remap-ports` to have free ports picked automatically. Traefik's `80` and `443`
+//! are never moved, since every domain is routed through them. The picked ports are saved
+//! in `.wf2_ports.yml` and kept by every later command, delete it to go back to the defaults.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::task::Task;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # let cmd = r#"
+//! wf2 up --remap-ports
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd)
+//! #   .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #   .with_file("../fixtures/config_01.yaml")
+//! #   .tasks();
+//! # let has_check = tasks.iter().any(|t| match t {
+//! #     Task::Exec { description: Some(d), .. } => d == "check published ports are available",
+//! #     _ => false,
+//! # });
+//! # assert!(has_check);
+//! ```
+//!
+//! ## running several projects at once
+//!
+//! By default every project starts its own Traefik on ports 80/443. To run more than one
+//! project at a time, use the shared proxy instead
+//!
+//! ```yaml
+//! options:
+//!   proxy:
+//!     shared: true
+//! ```
+//!
+//! Then run `wf2 proxy up` once, and `wf2 up` in each project. `--clean` is skipped in this mode.
+//!
 //! ## sync folders from `vendor`
 //!
 //! Sometimes you'll need or want to edit files that would normally be accessible in your local
@@ -180,7 +254,7 @@
 //!
 //! - ## Step 3 (optional): import your DB
 //!     If it's the first time you've ran this project, you'll need to grab a Database.
-//!     Once you have it, import with [`db-import`](../subcommands/db_import/index.html)
+//!     Once you have it, import with [`db-import`](../../../../subcommands/db_import/index.html)
 //!
 //!     ```sh
 //!     wf2 db-import ~/Downloads/dump.sql
@@ -206,17 +280,21 @@ use crate::recipes::m2::subcommands::m2_playground_help;
 use crate::recipes::m2::subcommands::up_help::up_help;
 
 use crate::dc_volume::DcVolume;
+use crate::hosts::HostEntries;
+use crate::proxy::SharedProxy;
 use crate::recipes::m2::dc_tasks::M2Volumes;
 use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::services::nginx::NginxService;
 use crate::services::pwa::PwaService;
 use crate::services::Service;
-use crate::tasks::docker_clean::docker_clean;
+use crate::tasks::port_check::port_checked_up;
+use crate::tasks::wait_for_healthy::{wait_for_healthy, WaitFor};
 use crate::{context::Context, task::Task};
 use ansi_term::Colour::{Cyan, Green};
 use clap::{App, ArgMatches};
 use doc_link::doc_link;
 use std::path::PathBuf;
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[doc_link("/recipes/m2/subcommands/up")]
@@ -225,10 +303,12 @@ pub struct M2Up;
 impl M2Up {
     pub const NAME: &'static str = "up";
     pub const ABOUT: &'static str = "Bring up containers";
+    pub const DEFAULT_WAIT_TIMEOUT: u64 = 180;
 
     ///
     /// Bring the project up using given templates
     ///
+    #[allow(clippy::too_many_arguments)]
     pub fn up(
         &self,
         ctx: &Context,
@@ -236,6 +316,8 @@ impl M2Up {
         attached: bool,
         build: bool,
         syncing: bool,
+        wait: Option<Duration>,
+        remap_ports: bool,
     ) -> Result<Vec<Task>, failure::Error> {
         //
         // Display which config file (if any) is being used.
@@ -304,12 +386,17 @@ impl M2Up {
         //
         // Docker compose tasks for this recipe
         //
-        let dc_tasks = recipe.dc_tasks(&ctx)?;
+        let (dc, dc_tasks) = recipe.dc_and_tasks(&ctx)?;
 
         //
         // Stop & remove docker containers before starting new ones
         //
-        let clean_docker_containers_task = if clean { docker_clean() } else { vec![] };
+        let clean_docker_containers_task = SharedProxy::clean_tasks(ctx, clean);
+
+        //
+        // With the shared proxy, the network must exist before this project can start
+        //
+        let proxy_network = SharedProxy::project_up_tasks(ctx);
 
         //
         // The final DC task, either in detached mode (default)
@@ -317,11 +404,23 @@ impl M2Up {
         //
         let mut base_cmd = vec![Some("up"), if build { Some("--build") } else { None }];
 
-        let up = if attached {
-            dc_tasks.cmd_task(base_cmd.into_iter().filter_map(|x| x).collect())
-        } else {
+        if !attached {
             base_cmd.push(Some("-d"));
-            dc_tasks.cmd_task(base_cmd.into_iter().filter_map(|x| x).collect())
+        }
+        let base_cmd: Vec<&str> = base_cmd.into_iter().filter_map(|x| x).collect();
+
+        //
+        // Fail early if any published ports are already in use, or with `--remap-ports`
+        // move any non-essential services away from them first
+        //
+        let up = port_checked_up(ctx, &dc, &dc_tasks, &base_cmd.join(" "), remap_ports);
+
+        //
+        // Optionally block until every service is ready
+        //
+        let wait_task = match wait {
+            Some(timeout) if !attached => wait_for_healthy(WaitFor::from_dc(&dc), timeout),
+            _ => Task::Noop,
         };
 
         //
@@ -341,6 +440,15 @@ impl M2Up {
             Task::Noop
         };
 
+        //
+        // Warn about any domains missing from /etc/hosts
+        //
+        let hosts_task = if !attached {
+            HostEntries::from_ctx(ctx)?.warn_task(HostEntries::DEFAULT_PATH)
+        } else {
+            Task::Noop
+        };
+
         let pwa_cleanup_tasks = if M2RecipeOptions::has_pwa_options(ctx) {
             pwa_cleanup(&ctx)
         } else {
@@ -357,8 +465,11 @@ impl M2Up {
             .chain(output_files.into_iter())
             .chain(pwa_cleanup_tasks.into_iter())
             .chain(clean_docker_containers_task.into_iter())
-            .chain(vec![up].into_iter())
+            .chain(proxy_network.into_iter())
+            .chain(up.into_iter())
+            .chain(vec![wait_task].into_iter())
             .chain(vec![up_help_task].into_iter())
+            .chain(vec![hosts_task].into_iter())
             .collect())
     }
 }
@@ -373,6 +484,12 @@ struct Opts {
     build: bool,
     #[structopt(short, long)]
     sync: Option<Vec<PathBuf>>,
+    #[structopt(short, long)]
+    wait: bool,
+    #[structopt(long)]
+    timeout: Option<u64>,
+    #[structopt(long)]
+    remap_ports: bool,
 }
 
 impl<'a, 'b> CliCommand<'a, 'b> for M2Up {
@@ -405,9 +522,25 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2Up {
             }
         }
 
+        let wait = if opts.wait {
+            Some(Duration::from_secs(
+                opts.timeout.unwrap_or(M2Up::DEFAULT_WAIT_TIMEOUT),
+            ))
+        } else {
+            None
+        };
+
         Some(
-            self.up(&next_ctx, opts.clean, opts.attached, opts.build, syncing)
-                .unwrap_or_else(Task::task_err_vec),
+            self.up(
+                &next_ctx,
+                opts.clean,
+                opts.attached,
+                opts.build,
+                syncing,
+                wait,
+                opts.remap_ports,
+            )
+            .unwrap_or_else(Task::task_err_vec),
         )
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
@@ -417,6 +550,11 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2Up {
             .arg_from_usage("-c --clean 'stop & remove other containers before starting new ones'")
             .arg_from_usage("-s --sync [paths]... 'apply additional sync folders'")
             .arg_from_usage("-b --build 'rebuild docker container if there are any'")
+            .arg_from_usage("-w --wait 'wait until all services are healthy before exiting'")
+            .arg_from_usage("--timeout [seconds] 'how long to --wait for (default 180)'")
+            .arg_from_usage(
+                "--remap-ports 'move non-essential services to free ports if theirs are taken'",
+            )
             .after_help(M2Up::DOC_LINK)]
     }
 }
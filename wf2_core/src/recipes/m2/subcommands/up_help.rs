@@ -6,7 +6,7 @@ use ansi_term::Color::{Cyan, Green};
 
 pub fn up_help(ctx: &Context) -> String {
     // An iterator over just the domains
-    let all_domains = ctx.domains.clone().into_iter().chain(
+    let all_domains = ctx.domains().into_iter().chain(
         vec![
             MailService::DOMAIN.to_string(),
             RabbitMqService::DOMAIN.to_string(),
@@ -73,7 +73,7 @@ pub fn up_help(ctx: &Context) -> String {
 fn site(ctx: &Context) -> String {
     format!(
         "    The site should be running at: {}",
-        Cyan.paint(urls(ctx.domains.clone()))
+        Cyan.paint(urls(ctx.domains()))
     )
 }
 
@@ -106,7 +106,10 @@ mod test {
             recipe: Some(RecipeKinds::M2),
             ..Context::default()
         };
-        println!("{}", up_help(&ctx))
+        // without a wf2.yml, `local.m2` is still used
+        let help = up_help(&ctx);
+        assert!(help.contains("https://local.m2"));
+        assert!(help.contains("0.0.0.0 local.m2"));
     }
 
     #[test]
@@ -1,11 +1,11 @@
 //!
-//! Enable and disable Varnish
+//! Enable, disable and manage Varnish
 //!
 //! Varnish is not 'enabled' by default - the service starts everytime
 //! which is why you may of seen it when running `docker ps`, but it's running
 //! in pass-through mode, meaning it will never cache anything.
 //!
-//! You need to `enable` it manually
+//! You need to `enable` it manually, which loads the project's VCL through `varnishadm vcl.load`.
 //!
 //! # Example: enable varnish
 //!
@@ -16,12 +16,13 @@
 //! # let cmd = r#"
 //! wf2 varnish enable
 //! # "#;
-//! # let commands = Test::from_cmd(cmd)
+//! # let (commands, (_read, write, _delete)) = Test::from_cmd(cmd)
 //! #     .with_recipe(RecipeKinds::M2_NAME)
 //! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
-//! #     .commands();
-//! # let expected = "docker-compose -f /users/shane/.wf2_m2_shane/docker-compose.yml exec varnish varnishadm vcl.use boot0";
-//! # assert_eq!(commands, vec![expected])
+//! #     .file_ops_paths_commands();
+//! # let expected = "docker-compose -f /users/shane/.wf2_m2_shane/docker-compose.yml exec varnish sh -c 'name=wf2_$(date +%s) && varnishadm vcl.load $name /etc/varnish/wf2/default.vcl && varnishadm vcl.use $name'";
+//! # assert_eq!(commands, vec![expected]);
+//! # assert_eq!(write, vec!["/users/shane/.wf2_m2_shane/varnish/default.vcl"]);
 //! ```
 //!
 //! # Example: disable varnish
@@ -40,31 +41,148 @@
 //! # let expected = "docker-compose -f /users/shane/.wf2_m2_shane/docker-compose.yml exec varnish varnishadm vcl.use boot";
 //! # assert_eq!(commands, vec![expected])
 //! ```
+//!
+//! # Example: purge a single URL, or every URL matching a pattern
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 varnish purge https://local.m2/women.html
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # let expected = "docker-compose -f /users/shane/.wf2_m2_shane/docker-compose.yml exec varnish varnishadm 'ban req.http.host == local.m2 && req.url == /women.html'";
+//! # assert_eq!(commands, vec![expected]);
+//! # let cmd = r#"
+//! wf2 varnish purge ^/women
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # let expected = "docker-compose -f /users/shane/.wf2_m2_shane/docker-compose.yml exec varnish varnishadm 'ban req.url ~ ^/women'";
+//! # assert_eq!(commands, vec![expected]);
+//! ```
+//!
+//! # Example: ban with any expression
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 varnish ban "obj.http.X-Magento-Tags ~ cat_c_12"
+//! # "#;
+//! # let commands = Test::from_skipped(cmd, 3)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # let expected = "docker-compose -f /users/shane/.wf2_m2_shane/docker-compose.yml exec varnish varnishadm 'ban obj.http.X-Magento-Tags ~ cat_c_12'";
+//! # assert_eq!(commands, vec![expected]);
+//! ```
+//!
+//! # Example: show cache hits, misses and objects
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 varnish stats
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert!(commands[0].ends_with("exec varnish varnishstat -1 -f MAIN.cache_hit -f MAIN.cache_miss -f MAIN.n_object -f MAIN.bans -f MAIN.n_lru_nuked"));
+//! ```
+//!
 //! ## Further reading
 //!
 //! See the [Varnish Service](../../services/varnish/index.html) for more information about
-//! enabling Varnish within Magento, debugging tricks and more.
+//! enabling Varnish within Magento, using your own VCL, debugging tricks and more.
 //!
 use crate::commands::CliCommand;
 use crate::context::Context;
+use crate::file::File;
+use crate::recipes::m2::output_files::varnish_vcl::VarnishVcl;
 use crate::scripts::service_cmd::ServiceCmd;
 use crate::services::varnish::VarnishService;
 use crate::services::Service;
 use crate::task::Task;
-use clap::{App, ArgMatches, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use snailquote::escape;
 
 #[doc_link::doc_link("/recipes/m2/subcommands/varnish")]
 pub struct VarnishCmd;
 
 impl VarnishCmd {
     const NAME: &'static str = "varnish";
-    const ABOUT: &'static str = "Enable or disable Varnish for M2";
+    const ABOUT: &'static str = "Enable, disable or purge Varnish for M2";
 
     const ENABLE: &'static str = "enable";
     const DISABLE: &'static str = "disable";
+    const PURGE: &'static str = "purge";
+    const BAN: &'static str = "ban";
+    const STATS: &'static str = "stats";
 
-    const ENABLE_CMD: &'static str = "varnishadm vcl.use boot0";
     const DISABLE_CMD: &'static str = "varnishadm vcl.use boot";
+
+    ///
+    /// The counters shown by `wf2 varnish stats`
+    ///
+    const STATS_FIELDS: [&'static str; 5] = [
+        "MAIN.cache_hit",
+        "MAIN.cache_miss",
+        "MAIN.n_object",
+        "MAIN.bans",
+        "MAIN.n_lru_nuked",
+    ];
+
+    ///
+    /// Each load needs a unique name, otherwise `vcl.load` fails
+    /// when enabling a second time
+    ///
+    pub fn enable_cmd() -> String {
+        format!(
+            "sh -c 'name=wf2_$(date +%s) && varnishadm vcl.load $name {} && varnishadm vcl.use $name'",
+            VarnishVcl::CONTAINER_PATH
+        )
+    }
+
+    pub fn ban_cmd(expr: &str) -> String {
+        format!("varnishadm {}", escape(&format!("ban {}", expr.trim())))
+    }
+
+    ///
+    /// A full URL bans that exact page, anything else is treated
+    /// as a regex against the path
+    ///
+    pub fn purge_expr(target: &str) -> String {
+        let without_scheme = target
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        if without_scheme.len() == target.len() {
+            return format!("req.url ~ {}", target);
+        }
+        let (host, path) = match without_scheme.find('/') {
+            Some(index) => without_scheme.split_at(index),
+            None => (without_scheme, "/"),
+        };
+        format!("req.http.host == {} && req.url == {}", host, path)
+    }
+
+    pub fn stats_cmd() -> String {
+        let fields = VarnishCmd::STATS_FIELDS
+            .iter()
+            .map(|field| format!("-f {}", field))
+            .collect::<Vec<String>>();
+        format!("varnishstat -1 {}", fields.join(" "))
+    }
 }
 
 impl<'a, 'b> CliCommand<'a, 'b> for VarnishCmd {
@@ -73,21 +191,34 @@ impl<'a, 'b> CliCommand<'a, 'b> for VarnishCmd {
     }
 
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
-        matches
-            .map(|m| m.subcommand_name())
-            .and_then(|n| match n {
-                Some(VarnishCmd::ENABLE) => Some(VarnishCmd::ENABLE_CMD),
-                Some(VarnishCmd::DISABLE) => Some(VarnishCmd::DISABLE_CMD),
-                _ => None,
-            })
-            .map(|cmd| -> Task {
-                let as_cmd = ServiceCmd::running_cmd(VarnishService::NAME, cmd, ctx);
-                as_cmd.into()
-            })
-            .map_or(
-                Some(vec![Task::notify_error("missing `enable` or `disable`")]),
-                |t| Some(vec![t]),
-            )
+        let running = |cmd: String| -> Task {
+            ServiceCmd::running_cmd(VarnishService::NAME, cmd, ctx).into()
+        };
+        let value = |m: Option<&ArgMatches>, name: &str| -> String {
+            m.and_then(|m| m.value_of(name))
+                .map(String::from)
+                .expect("guarded by Clap")
+        };
+        let tasks = match matches.map(|m| m.subcommand()) {
+            Some((VarnishCmd::ENABLE, _)) => match VarnishVcl::from_ctx(ctx) {
+                Ok(vcl) => vec![vcl.write_task(), running(VarnishCmd::enable_cmd())],
+                Err(e) => Task::task_err_vec(e),
+            },
+            Some((VarnishCmd::DISABLE, _)) => {
+                vec![running(String::from(VarnishCmd::DISABLE_CMD))]
+            }
+            Some((VarnishCmd::PURGE, m)) => vec![running(VarnishCmd::ban_cmd(
+                &VarnishCmd::purge_expr(&value(m, "target")),
+            ))],
+            Some((VarnishCmd::BAN, m)) => {
+                vec![running(VarnishCmd::ban_cmd(&value(m, "expression")))]
+            }
+            Some((VarnishCmd::STATS, _)) => vec![running(VarnishCmd::stats_cmd())],
+            _ => vec![Task::notify_error(
+                "missing `enable`, `disable`, `purge`, `ban` or `stats`",
+            )],
+        };
+        Some(tasks)
     }
 
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
@@ -97,10 +228,51 @@ impl<'a, 'b> CliCommand<'a, 'b> for VarnishCmd {
             .subcommands(vec![
                 SubCommand::with_name(VarnishCmd::ENABLE)
                     .display_order(0)
-                    .about("Enable Varnish"),
+                    .about("Enable Varnish, loading the project's VCL"),
                 SubCommand::with_name(VarnishCmd::DISABLE)
                     .display_order(0)
                     .about("Disable Varnish"),
+                SubCommand::with_name(VarnishCmd::PURGE)
+                    .display_order(1)
+                    .about("Purge a URL, or every path matching a pattern")
+                    .arg(
+                        Arg::with_name("target")
+                            .help("eg: https://local.m2/women.html or ^/women")
+                            .required(true),
+                    ),
+                SubCommand::with_name(VarnishCmd::BAN)
+                    .display_order(1)
+                    .about("Ban cached objects with any ban expression")
+                    .arg(
+                        Arg::with_name("expression")
+                            .help("eg: \"obj.http.X-Magento-Tags ~ cat_c_12\"")
+                            .required(true),
+                    ),
+                SubCommand::with_name(VarnishCmd::STATS)
+                    .display_order(1)
+                    .about("Show cache hits, misses and objects"),
             ])]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_expr() {
+        assert_eq!(
+            VarnishCmd::purge_expr("http://local.m2"),
+            "req.http.host == local.m2 && req.url == /"
+        );
+        assert_eq!(
+            VarnishCmd::purge_expr("https://local.m2/women.html?p=2"),
+            "req.http.host == local.m2 && req.url == /women.html?p=2"
+        );
+        assert_eq!(VarnishCmd::purge_expr("\\.css$"), "req.url ~ \\.css$");
+        assert_eq!(
+            VarnishCmd::ban_cmd("req.url ~ \\.css$"),
+            "varnishadm \"ban req.url ~ \\\\.css\\$\""
+        );
+    }
+}
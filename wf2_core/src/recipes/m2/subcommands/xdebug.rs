@@ -50,6 +50,54 @@
 //! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
 //! #     .tasks();
 //! ```
+//! # Example: enable xdebug with profiling (Xdebug 3)
+//!
+//! PHP 7.4+ images use Xdebug 3, where `--mode` sets `xdebug.mode`. The `php-debug`
+//! container is recreated with the new mode, which is remembered for the next `wf2 up`.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 xdebug enable --mode debug,profile
+//! # "#;
+//! # let (commands, (_read, write, _delete)) = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_file("../fixtures/config_php_74.yaml")
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .file_ops_paths_commands();
+//! # assert!(write.contains(&String::from("/users/shane/.wf2_m2_shane/xdebug/mode")));
+//! # assert!(commands[0].ends_with("docker-compose.yml up -d php-debug"));
+//! # assert!(commands[1].ends_with("docker-compose.yml exec nginx nginx -s reload"));
+//! ```
+//!
+//! # Example: show the current settings
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 xdebug status
+//! # "#;
+//! # let _tasks = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::M2_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .tasks();
+//! ```
+//!
+//! ```txt
+//! enabled        yes
+//! version        3
+//! mode           debug,profile
+//! client host    172.17.0.1
+//! client port    9003
+//! ```
+//!
+//! On Linux, the client host is the address of the `docker0` bridge. It can be
+//! changed with `XDEBUG_CLIENT_HOST` under `env` in your wf2.yml
+//!
 //! ## Further reading
 //!
 //! See the [Xdebug Service](../../services/xdebug/index.html) for more information.
@@ -57,12 +105,20 @@
 use crate::commands::CliCommand;
 use crate::context::Context;
 
+use crate::dc_tasks::DcTasks;
 use crate::file::File;
 use crate::task::Task;
-use clap::{App, ArgMatches, SubCommand};
+use crate::util::two_col;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use futures::future::lazy;
+use std::fs;
 
 use crate::recipes::m2::output_files::nginx_upstream::NginxUpstream;
+use crate::recipes::m2::services::php_debug::PhpDebugService;
+use crate::recipes::m2::services::M2RecipeOptions;
+use crate::recipes::m2::xdebug::{XdebugError, XdebugHost, XdebugMode, XdebugVersion};
 use crate::recipes::recipe_kinds::RecipeKinds;
+use crate::services::Service;
 
 #[doc_link::doc_link("/recipes/m2/subcommands/xdebug")]
 #[derive(Default)]
@@ -74,26 +130,20 @@ impl XdebugCmd {
 
     const ENABLE: &'static str = "enable";
     const DISABLE: &'static str = "disable";
-}
-
-impl<'a, 'b> CliCommand<'a, 'b> for XdebugCmd {
-    fn name(&self) -> String {
-        String::from(XdebugCmd::NAME)
-    }
+    const STATUS: &'static str = "status";
 
-    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
-        let enabled = matches.map(|m| m.subcommand_name()).and_then(|n| match n {
-            Some(XdebugCmd::ENABLE) => Some(true),
-            Some(XdebugCmd::DISABLE) => Some(false),
-            _ => None,
-        });
-
-        if enabled.is_none() {
-            return Some(vec![Task::notify_error("missing `enable` or `disable`")]);
+    ///
+    /// Route requests to `php-debug`, or back to `php`.
+    ///
+    /// `before` only runs once it's known that Xdebug can be toggled
+    ///
+    pub fn toggle(ctx: &Context, enabled: bool, before: Vec<Task>) -> Vec<Task> {
+        if enabled && M2RecipeOptions::is_disabled(ctx, PhpDebugService::NAME) {
+            return vec![Task::notify_error(
+                "XDebug cannot be enabled since `php-debug` is disabled in your wf2.yml",
+            )];
         }
 
-        let enabled = enabled.expect("guarded");
-
         let msg = if enabled {
             "XDebug Enabled"
         } else {
@@ -103,7 +153,7 @@ impl<'a, 'b> CliCommand<'a, 'b> for XdebugCmd {
         let nginx_upstream = NginxUpstream::from_ctx(ctx);
 
         if let Err(e) = nginx_upstream {
-            return Some(Task::task_err_vec(e));
+            return Task::task_err_vec(e);
         }
 
         let mut nginx_upstream = nginx_upstream.expect("guarded");
@@ -112,22 +162,149 @@ impl<'a, 'b> CliCommand<'a, 'b> for XdebugCmd {
         let dc = recipe.dc_tasks(ctx);
 
         if let Err(_e) = dc {
-            return Some(vec![Task::notify_error(
+            return vec![Task::notify_error(
                 "couldn't create the docker-compose task",
-            )]);
+            )];
         }
 
         let dc = dc.expect("guarded above");
 
-        Some(vec![
+        let mut tasks = before;
+        tasks.extend(vec![
             Task::notify_info("updating upstream.conf"),
             nginx_upstream.toggle_xdebug(enabled).write_task(),
             Task::notify_info("reloading nginx conf"),
             dc.cmd_task(vec!["exec", "nginx", "nginx", "-s", "reload"]),
             Task::notify_info(msg),
+        ]);
+        tasks
+    }
+
+    ///
+    /// With `--mode`, `php-debug` is recreated first so the new mode is used
+    ///
+    fn enable(ctx: &Context, mode: Option<&str>) -> Vec<Task> {
+        let mode = match mode {
+            Some(mode) => mode,
+            None => return XdebugCmd::toggle(ctx, true, vec![]),
+        };
+        if XdebugVersion::from_php(&ctx.php_version) == XdebugVersion::Two {
+            return Task::task_err_vec(
+                XdebugError::ModeNeedsXdebug3(ctx.php_version.clone()).into(),
+            );
+        }
+        match XdebugMode::parse(mode)
+            .map_err(failure::Error::from)
+            .and_then(|mode| XdebugCmd::recreate_php_debug(ctx, &mode))
+        {
+            Ok(before) => XdebugCmd::toggle(ctx, true, before),
+            Err(e) => Task::task_err_vec(e),
+        }
+    }
+
+    fn recreate_php_debug(ctx: &Context, mode: &str) -> Result<Vec<Task>, failure::Error> {
+        Ok(vec![
+            Task::file_write(
+                XdebugMode::state_path(ctx),
+                "Writes the Xdebug mode",
+                mode.as_bytes().to_vec(),
+            ),
+            Task::notify_info(format!("recreating php-debug with XDEBUG_MODE={}", mode)),
+            XdebugCmd::php_debug_up(ctx, PhpDebugService::environment(ctx, mode))?,
         ])
     }
 
+    ///
+    /// Recreate `php-debug` with a different `environment`.
+    ///
+    /// Anything not also saved to disk is lost on the next `wf2 up`, which
+    /// is why `recreate_php_debug` writes the mode to the `xdebug/mode` state file first
+    ///
+    pub fn php_debug_up(ctx: &Context, environment: Vec<String>) -> Result<Task, failure::Error> {
+        let recipe = RecipeKinds::select(ctx.recipe.expect("recipe is always resolved here"));
+        let (mut dc, _) = recipe.dc_and_tasks(ctx)?;
+        if let Some(php_debug) = dc
+            .services
+            .as_mut()
+            .and_then(|services| services.get_mut(PhpDebugService::NAME))
+        {
+            php_debug.set_environment(environment);
+        }
+        let dc_tasks = DcTasks::from_ctx(ctx, dc.to_bytes());
+        Ok(dc_tasks.cmd_task(vec!["up", "-d", PhpDebugService::NAME]))
+    }
+
+    ///
+    /// The upstream file is read at run time, since it's what
+    /// decides where requests are sent
+    ///
+    fn status_task(ctx: &Context) -> Task {
+        let upstream = match NginxUpstream::from_ctx(ctx) {
+            Ok(upstream) => upstream.file_path(),
+            Err(e) => return Task::notify_error(e.to_string()),
+        };
+        let version = XdebugVersion::from_php(&ctx.php_version);
+        let mode = XdebugMode::from_ctx(ctx);
+        let host = XdebugHost::from_ctx(ctx);
+        Task::Exec {
+            description: Some(String::from("show the xdebug status")),
+            exec: Box::new(lazy(move || {
+                let enabled = match fs::read_to_string(&upstream) {
+                    Ok(content) if XdebugCmd::is_enabled(&content) => "yes",
+                    Ok(_) => "no",
+                    Err(_) => "unknown, run `wf2 up` first",
+                };
+                let mut rows = vec![
+                    (String::from("enabled"), enabled.to_string()),
+                    (String::from("version"), version.to_string()),
+                ];
+                if version == XdebugVersion::Three {
+                    rows.push((String::from("mode"), mode));
+                }
+                rows.push((String::from("client host"), host));
+                rows.push((
+                    String::from("client port"),
+                    version.client_port().to_string(),
+                ));
+                println!("{}", two_col(rows));
+                Ok(())
+            })),
+        }
+    }
+
+    ///
+    /// Whether `fastcgi_backend` points at `php-debug`
+    ///
+    pub fn is_enabled(upstream: &str) -> bool {
+        upstream
+            .split("upstream fastcgi_backend {")
+            .nth(1)
+            .and_then(|block| block.split('}').next())
+            .map_or(false, |block| {
+                block.contains(&format!("server {}:", PhpDebugService::NAME))
+            })
+    }
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for XdebugCmd {
+    fn name(&self) -> String {
+        String::from(XdebugCmd::NAME)
+    }
+
+    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        let tasks = match matches.map(|m| m.subcommand()) {
+            Some((XdebugCmd::ENABLE, m)) => {
+                XdebugCmd::enable(ctx, m.and_then(|m| m.value_of("mode")))
+            }
+            Some((XdebugCmd::DISABLE, _)) => XdebugCmd::toggle(ctx, false, vec![]),
+            Some((XdebugCmd::STATUS, _)) => vec![XdebugCmd::status_task(ctx)],
+            _ => vec![Task::notify_error(
+                "missing `enable`, `disable` or `status`",
+            )],
+        };
+        Some(tasks)
+    }
+
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
         vec![App::new(XdebugCmd::NAME)
             .about(XdebugCmd::ABOUT)
@@ -135,10 +312,34 @@ impl<'a, 'b> CliCommand<'a, 'b> for XdebugCmd {
             .subcommands(vec![
                 SubCommand::with_name(XdebugCmd::ENABLE)
                     .display_order(0)
-                    .about("Enable XDebug"),
+                    .about("Enable XDebug")
+                    .arg(
+                        Arg::with_name("mode")
+                            .long("mode")
+                            .takes_value(true)
+                            .help("Xdebug 3 only, eg: debug,profile,coverage"),
+                    ),
                 SubCommand::with_name(XdebugCmd::DISABLE)
                     .display_order(0)
                     .about("Disable XDebug"),
+                SubCommand::with_name(XdebugCmd::STATUS)
+                    .display_order(1)
+                    .about("Show whether XDebug is enabled, and its settings"),
             ])]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_enabled() {
+        let ctx = Context::new("/users/shane/acme");
+        let mut upstream = NginxUpstream::from_ctx(&ctx).expect("test");
+        let disabled = String::from_utf8(upstream.bytes()).expect("test");
+        assert!(!XdebugCmd::is_enabled(&disabled));
+        let enabled = String::from_utf8(upstream.toggle_xdebug(true).bytes()).expect("test");
+        assert!(XdebugCmd::is_enabled(&enabled));
+    }
+}
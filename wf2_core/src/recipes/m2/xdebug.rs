@@ -0,0 +1,268 @@
+//!
+//! Xdebug settings that depend on the PHP version.
+//!
+//! The PHP 7.1 - 7.3 images ship Xdebug 2, everything newer ships Xdebug 3,
+//! which renamed most settings, eg: `remote_host` became `client_host` and
+//! the profiler & coverage switches were replaced by `xdebug.mode`.
+//!
+//! The mode is chosen with `wf2 xdebug enable --mode debug,profile` and
+//! remembered for the next `wf2 up`.
+//!
+use crate::context::Context;
+use crate::php::PHP;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+lazy_static! {
+    ///
+    /// Only detected the first time it's needed, since it runs `ip`
+    ///
+    static ref DETECTED_HOST: String = XdebugHost::detect();
+}
+
+#[derive(Debug, Fail)]
+pub enum XdebugError {
+    #[fail(
+        display = "Unknown Xdebug mode `{}`, expected a comma-separated list of: {}",
+        _0, _1
+    )]
+    UnknownMode(String, String),
+    #[fail(
+        display = "`--mode` needs Xdebug 3, but PHP {} uses Xdebug 2. Use `php_version: 7.4` or later",
+        _0
+    )]
+    ModeNeedsXdebug3(PHP),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XdebugVersion {
+    Two,
+    Three,
+}
+
+impl XdebugVersion {
+    pub fn from_php(php: &PHP) -> XdebugVersion {
+        match php {
+            PHP::SevenOne | PHP::SevenTwo | PHP::SevenThree => XdebugVersion::Two,
+            _ => XdebugVersion::Three,
+        }
+    }
+
+    pub fn client_port(self) -> &'static str {
+        match self {
+            XdebugVersion::Two => "9000",
+            XdebugVersion::Three => "9003",
+        }
+    }
+
+    ///
+    /// The value for `XDEBUG_CONFIG`
+    ///
+    pub fn config(self, host: &str) -> String {
+        match self {
+            XdebugVersion::Two => format!("remote_host={}", host),
+            XdebugVersion::Three => {
+                format!("client_host={} client_port={}", host, self.client_port())
+            }
+        }
+    }
+
+    ///
+    /// `XDEBUG_CONFIG` with the profiler writing to `dir`
+    ///
+    pub fn profiler_config(self, host: &str, dir: &str) -> String {
+        match self {
+            XdebugVersion::Two => format!("{} profiler_output_dir={}", self.config(host), dir),
+            XdebugVersion::Three => format!("{} output_dir={}", self.config(host), dir),
+        }
+    }
+}
+
+impl std::fmt::Display for XdebugVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XdebugVersion::Two => write!(f, "2"),
+            XdebugVersion::Three => write!(f, "3"),
+        }
+    }
+}
+
+///
+/// `xdebug.mode` for Xdebug 3, eg: `debug,profile`
+///
+pub struct XdebugMode;
+
+impl XdebugMode {
+    pub const DEFAULT: &'static str = "debug";
+    pub const MODES: [&'static str; 7] = [
+        "off", "develop", "coverage", "debug", "gcstats", "profile", "trace",
+    ];
+    pub const STATE_PATH: &'static str = "xdebug/mode";
+
+    pub fn parse(input: &str) -> Result<String, XdebugError> {
+        let modes = input
+            .split(',')
+            .map(str::trim)
+            .filter(|mode| !mode.is_empty())
+            .collect::<Vec<&str>>();
+        if let Some(unknown) = modes.iter().find(|mode| !XdebugMode::MODES.contains(mode)) {
+            return Err(XdebugError::UnknownMode(
+                unknown.to_string(),
+                XdebugMode::MODES.join(", "),
+            ));
+        }
+        if modes.is_empty() {
+            return Ok(XdebugMode::DEFAULT.to_string());
+        }
+        Ok(modes.join(","))
+    }
+
+    pub fn state_path(ctx: &Context) -> PathBuf {
+        ctx.output_file_path(XdebugMode::STATE_PATH)
+    }
+
+    ///
+    /// The last mode given to `wf2 xdebug enable --mode`, or `debug`
+    ///
+    pub fn from_ctx(ctx: &Context) -> String {
+        fs::read_to_string(XdebugMode::state_path(ctx))
+            .ok()
+            .and_then(|mode| XdebugMode::parse(&mode).ok())
+            .unwrap_or_else(|| XdebugMode::DEFAULT.to_string())
+    }
+}
+
+///
+/// Where Xdebug should connect back to
+///
+pub struct XdebugHost;
+
+impl XdebugHost {
+    ///
+    /// Docker Desktop provides this name on Mac + Windows
+    ///
+    pub const DOCKER_DESKTOP: &'static str = "host.docker.internal";
+    ///
+    /// The usual address of the `docker0` bridge on Linux
+    ///
+    pub const LINUX_FALLBACK: &'static str = "172.17.0.1";
+
+    ///
+    /// `XDEBUG_CLIENT_HOST` from `env` in the wf2 file, or the detected host
+    ///
+    pub fn from_ctx(ctx: &Context) -> String {
+        XdebugHost::explicit(ctx).unwrap_or_else(|| XdebugHost::detected(ctx))
+    }
+
+    ///
+    /// The host found by [`XdebugHost::detect`], unless the context already gives one
+    ///
+    pub fn detected(ctx: &Context) -> String {
+        ctx.xdebug_host
+            .clone()
+            .unwrap_or_else(|| DETECTED_HOST.clone())
+    }
+
+    pub fn fallback() -> String {
+        if cfg!(target_os = "linux") {
+            XdebugHost::LINUX_FALLBACK.to_string()
+        } else {
+            XdebugHost::DOCKER_DESKTOP.to_string()
+        }
+    }
+
+    pub fn explicit(ctx: &Context) -> Option<String> {
+        ctx.env
+            .as_ref()
+            .and_then(|env| env.get("XDEBUG_CLIENT_HOST"))
+            .and_then(|host| host.as_str())
+            .map(String::from)
+    }
+
+    ///
+    /// On Linux there's no `host.docker.internal` by default, so
+    /// the address of the `docker0` bridge is used instead.
+    ///
+    /// This runs `ip`, so use [`XdebugHost::detected`] which only calls it once,
+    /// and only for commands that need the host
+    ///
+    pub fn detect() -> String {
+        if !cfg!(target_os = "linux") {
+            return XdebugHost::DOCKER_DESKTOP.to_string();
+        }
+        Command::new("ip")
+            .args(&["-4", "-o", "addr", "show", "docker0"])
+            .output()
+            .ok()
+            .and_then(|output| XdebugHost::parse_ip_addr(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_else(XdebugHost::fallback)
+    }
+
+    ///
+    /// eg: `3: docker0    inet 172.17.0.1/16 brd 172.17.255.255 scope global docker0`
+    ///
+    pub fn parse_ip_addr(output: &str) -> Option<String> {
+        let mut words = output.split_whitespace();
+        words.find(|word| *word == "inet")?;
+        words
+            .next()
+            .and_then(|cidr| cidr.split('/').next())
+            .map(String::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xdebug_version() {
+        let v2 = XdebugVersion::from_php(&PHP::SevenThree);
+        assert_eq!(v2.config("172.17.0.1"), "remote_host=172.17.0.1");
+        let v3 = XdebugVersion::from_php(&PHP::SevenFour);
+        assert_eq!(
+            v3.config("host.docker.internal"),
+            "client_host=host.docker.internal client_port=9003"
+        );
+        assert_eq!(
+            v2.profiler_config("172.17.0.1", "/tmp/profiles"),
+            "remote_host=172.17.0.1 profiler_output_dir=/tmp/profiles"
+        );
+    }
+
+    #[test]
+    fn test_xdebug_mode() {
+        assert_eq!(
+            XdebugMode::parse("debug, profile").expect("test"),
+            "debug,profile"
+        );
+        assert_eq!(XdebugMode::parse("").expect("test"), "debug");
+        assert_eq!(
+            XdebugMode::parse("debug,remote").expect_err("test").to_string(),
+            "Unknown Xdebug mode `remote`, expected a comma-separated list of: off, develop, coverage, debug, gcstats, profile, trace"
+        );
+    }
+
+    #[test]
+    fn test_xdebug_host() {
+        let ctx = Context {
+            xdebug_host: Some(String::from("172.18.0.1")),
+            ..Context::default()
+        };
+        assert_eq!(XdebugHost::from_ctx(&ctx), "172.18.0.1");
+        let ctx = Context::new_from_str("env: { XDEBUG_CLIENT_HOST: 10.0.0.2 }").expect("test");
+        assert_eq!(XdebugHost::from_ctx(&ctx), "10.0.0.2");
+    }
+
+    #[test]
+    fn test_parse_ip_addr() {
+        let output =
+            "3: docker0    inet 172.17.0.1/16 brd 172.17.255.255 scope global docker0\\       valid_lft forever preferred_lft forever";
+        assert_eq!(
+            XdebugHost::parse_ip_addr(output),
+            Some(String::from("172.17.0.1"))
+        );
+        assert_eq!(XdebugHost::parse_ip_addr(""), None);
+    }
+}
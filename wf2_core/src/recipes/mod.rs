@@ -29,10 +29,23 @@
 //! # let _ctx: Context = serde_yaml::from_str(input).expect("Can parse wp yaml example");
 //! ```
 //!
+//! ...and the [Drupal Recipe](drupal/index.html)
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # let input = r#"
+//! recipe: Drupal
+//! # "#;
+//! # let _ctx: Context = serde_yaml::from_str(input).expect("Can parse drupal yaml example");
+//! ```
+//!
 //! ## Recipes
 //!
 //! - [Magento 2](m2/index.html)
 //! - [Wordpress](wp/index.html)
+//! - [Drupal](drupal/index.html)
+//!
+//! Other crates can provide their own recipes too, see [`RecipeKinds::register`](recipe_kinds/struct.RecipeKinds.html#method.register)
 //!
 use crate::commands::Commands;
 use crate::context::Context;
@@ -40,25 +53,40 @@ use crate::scripts::script::ResolveScript;
 
 use crate::dc_tasks::DcTasksTrait;
 use crate::subcommands::PassThru;
-use m2::M2Recipe;
 
 use crate::output_files::OutputFiles;
+use crate::recipes::app_services::AppServices;
 use crate::recipes::validate::ValidateRecipe;
-use wp::WpRecipe;
+use recipe_kinds::RecipeKinds;
 
+pub mod app_services;
+pub mod drupal;
+pub mod local_domain;
 pub mod m2;
 pub mod recipe_kinds;
 pub mod validate;
 pub mod wp;
 
 pub trait Recipe<'a, 'b>:
-    Commands<'a, 'b> + PassThru + DcTasksTrait + OutputFiles + ResolveScript + ValidateRecipe
+    Commands<'a, 'b>
+    + PassThru
+    + DcTasksTrait
+    + OutputFiles
+    + ResolveScript
+    + ValidateRecipe
+    + AppServices
 {
     fn default_help(&self, _ctx: &Context) -> Result<String, failure::Error> {
         Ok(String::from("default_help not implemented"))
     }
 }
 
+///
+/// Every recipe, including any registered with [`RecipeKinds::register`]
+///
 pub fn available_recipes<'a, 'b>() -> Vec<Box<dyn Recipe<'a, 'b>>> {
-    vec![Box::new(M2Recipe), Box::new(WpRecipe)]
+    RecipeKinds::all()
+        .into_iter()
+        .map(RecipeKinds::select)
+        .collect()
 }
@@ -1,10 +1,57 @@
+//!
+//! A way to determine which Recipe is being used.
+//!
+//! The built-in recipes (`M2`, `Wp` and `Drupal`) are always available, but
+//! crates that depend on `wf2_core` can register their own recipes at startup,
+//! before any CLI input is parsed.
+//!
+//! ```
+//! use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! use wf2_core::recipes::wp::WpRecipe;
+//!
+//! // a downstream crate would provide its own type that implements `Recipe`
+//! let acme = RecipeKinds::new("Acme", &WpRecipe);
+//!
+//! RecipeKinds::register(acme).expect("first registration succeeds");
+//!
+//! assert!(RecipeKinds::names().contains(&"Acme"));
+//! assert_eq!("acme".parse::<RecipeKinds>().expect("registered"), acme);
+//! assert_eq!(acme.to_string(), "acme");
+//!
+//! // names must be unique
+//! assert!(RecipeKinds::register(RecipeKinds::new("M2", &WpRecipe)).is_err());
+//! ```
+//!
+use crate::recipes::drupal::DrupalRecipe;
 use crate::recipes::m2::M2Recipe;
 
 use crate::context::Context;
 use crate::recipes::wp::WpRecipe;
 use crate::recipes::Recipe;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::RwLock;
+
+///
+/// Anything that can produce a boxed [`Recipe`]
+///
+/// This is implemented for every `Clone` type that implements [`Recipe`]
+/// so you should never need to implement it yourself.
+///
+pub trait RecipeFactory: Sync {
+    fn create<'a, 'b>(&self) -> Box<dyn Recipe<'a, 'b>>;
+}
+
+impl<R> RecipeFactory for R
+where
+    R: for<'a, 'b> Recipe<'a, 'b> + Clone + Sync + 'static,
+{
+    fn create<'a, 'b>(&self) -> Box<dyn Recipe<'a, 'b>> {
+        Box::new(self.clone())
+    }
+}
 
 ///
 /// A way to determine with Recipe is being used.
@@ -12,10 +59,17 @@ use std::str::FromStr;
 /// Once you have this [`RecipeKinds`], you can convert
 /// a [`Context`] + [`Cmd`] into a `Vec` of [`Task`]
 ///
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
-pub enum RecipeKinds {
-    M2,
-    Wp,
+#[derive(Clone, Copy)]
+pub struct RecipeKinds {
+    name: &'static str,
+    recipe: &'static dyn RecipeFactory,
+}
+
+lazy_static! {
+    ///
+    /// Recipes registered at runtime, in addition to [`RecipeKinds::BUILT_IN`]
+    ///
+    static ref REGISTERED: RwLock<Vec<RecipeKinds>> = RwLock::new(vec![]);
 }
 
 impl Default for RecipeKinds {
@@ -28,37 +82,111 @@ impl Default for RecipeKinds {
 enum RecipeKindsError {
     #[fail(display = "Not a valid recipe {}", _0)]
     Unknown(String),
+    #[fail(display = "A recipe named {} is already registered", _0)]
+    AlreadyRegistered(String),
 }
 
+#[allow(non_upper_case_globals)]
 impl<'a, 'b> RecipeKinds {
     pub const M2_NAME: &'static str = "M2";
     pub const WP_NAME: &'static str = "Wp";
+    pub const DRUPAL_NAME: &'static str = "Drupal";
     // pub const PWA_NAME: &'static str = "Pwa";
-    pub fn select(kind: RecipeKinds) -> Box<dyn Recipe<'a, 'b>> {
-        match kind {
-            RecipeKinds::M2 => Box::new(M2Recipe),
-            RecipeKinds::Wp => Box::new(WpRecipe),
+
+    pub const M2: RecipeKinds = RecipeKinds {
+        name: RecipeKinds::M2_NAME,
+        recipe: &M2Recipe,
+    };
+    pub const Wp: RecipeKinds = RecipeKinds {
+        name: RecipeKinds::WP_NAME,
+        recipe: &WpRecipe,
+    };
+    pub const Drupal: RecipeKinds = RecipeKinds {
+        name: RecipeKinds::DRUPAL_NAME,
+        recipe: &DrupalRecipe,
+    };
+
+    ///
+    /// The recipes that ship with wf2_core
+    ///
+    pub const BUILT_IN: [RecipeKinds; 3] = [RecipeKinds::M2, RecipeKinds::Wp, RecipeKinds::Drupal];
+
+    pub fn new(name: &'static str, recipe: &'static dyn RecipeFactory) -> RecipeKinds {
+        RecipeKinds { name, recipe }
+    }
+
+    ///
+    /// Make a recipe available to the CLI + `wf2.yml` files under its name.
+    ///
+    /// Names are compared case-insensitively, since the lowercase version
+    /// is used for things like the output directory.
+    ///
+    pub fn register(kind: RecipeKinds) -> Result<(), failure::Error> {
+        // checked + pushed under the same lock, so two threads can't both register a name
+        let mut registered = REGISTERED
+            .write()
+            .expect("recipe registry lock is never poisoned");
+        let taken = RecipeKinds::BUILT_IN
+            .iter()
+            .chain(registered.iter())
+            .any(|existing| existing.name.eq_ignore_ascii_case(kind.name));
+        if taken {
+            return Err(RecipeKindsError::AlreadyRegistered(kind.name.to_string()).into());
         }
+        registered.push(kind);
+        Ok(())
+    }
+
+    ///
+    /// Every available recipe, built-in recipes first
+    ///
+    pub fn all() -> Vec<RecipeKinds> {
+        let registered = REGISTERED
+            .read()
+            .expect("recipe registry lock is never poisoned");
+        RecipeKinds::BUILT_IN
+            .iter()
+            .chain(registered.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn find(name: &str) -> Option<RecipeKinds> {
+        RecipeKinds::all()
+            .into_iter()
+            .find(|kind| kind.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn select(kind: RecipeKinds) -> Box<dyn Recipe<'a, 'b>> {
+        kind.recipe.create()
     }
     pub fn names() -> Vec<&'static str> {
-        vec![
-            RecipeKinds::M2_NAME,
-            RecipeKinds::WP_NAME,
-            // RecipeKinds::PWA_NAME,
-        ]
+        RecipeKinds::all().iter().map(|kind| kind.name).collect()
     }
     pub fn from_ctx(ctx: &Context) -> Box<dyn Recipe<'a, 'b>> {
         RecipeKinds::select(ctx.recipe.expect("recipe"))
     }
 }
 
+impl PartialEq for RecipeKinds {
+    fn eq(&self, other: &RecipeKinds) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Debug for RecipeKinds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.name)
+    }
+}
+
 impl fmt::Display for RecipeKinds {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        match self {
-            RecipeKinds::M2 => write!(f, "m2"),
-            RecipeKinds::Wp => write!(f, "wp"),
-            // RecipeKinds::Pwa => write!(f, "pwa"),
-        }
+        write!(f, "{}", self.name.to_lowercase())
     }
 }
 
@@ -66,12 +194,47 @@ impl FromStr for RecipeKinds {
     type Err = failure::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let m = match s {
-            RecipeKinds::M2_NAME => Ok(RecipeKinds::M2),
-            RecipeKinds::WP_NAME => Ok(RecipeKinds::Wp),
-            // RecipeKinds::PWA_NAME => Ok(RecipeKinds::Pwa),
-            _a => Err(RecipeKindsError::Unknown(_a.to_string())),
-        }?;
+        let m = RecipeKinds::find(s).ok_or_else(|| RecipeKindsError::Unknown(s.to_string()))?;
         Ok(m)
     }
 }
+
+impl Serialize for RecipeKinds {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for RecipeKinds {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        RecipeKinds::from_str(&s).map_err(DeError::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_names() {
+        assert_eq!(RecipeKinds::names()[..3], ["M2", "Wp", "Drupal"]);
+        assert_eq!(RecipeKinds::Drupal.to_string(), "drupal");
+    }
+
+    #[test]
+    fn test_deserialize_unknown() {
+        let actual = serde_yaml::from_str::<RecipeKinds>("Unknown");
+        assert!(actual.is_err());
+        let actual = serde_yaml::from_str::<RecipeKinds>("Wp").expect("test");
+        assert_eq!(actual, RecipeKinds::Wp);
+        let actual = serde_yaml::from_str::<RecipeKinds>("drupal").expect("test");
+        assert_eq!(actual, RecipeKinds::Drupal);
+    }
+}
@@ -7,49 +7,34 @@ use crate::dc_volume::DcVolume;
 use crate::recipes::wp::pass_thru::WpPassThru;
 use crate::recipes::wp::subcommands::{wp_recipe_global_subcommands, wp_recipe_subcommands};
 use crate::recipes::Recipe;
-use crate::scripts::script::ResolveScript;
+use crate::scripts::script::{ResolveScript, Script};
 use crate::task::Task;
 
+use crate::dc_service::DcService;
 use crate::output_files::OutputFiles;
+use crate::recipes::app_services::{AppServices, DbCredentials};
+use crate::recipes::m2::services::db::DbService;
 use crate::recipes::validate::ValidateRecipe;
+use crate::recipes::wp::services::wp_db::WpDbService;
+use crate::recipes::wp::services::wp_nginx::WpNginxService;
+use crate::recipes::wp::services::wp_php::WpPhpService;
 use crate::recipes::wp::services::WpServices;
-use crate::services::Services;
+use crate::services::{Service, Services};
 use crate::subcommands::PassThru;
 use volumes::get_volumes;
 
+#[derive(Clone)]
 pub struct WpRecipe;
 
 impl ValidateRecipe for WpRecipe {}
 
-impl WpRecipe {
-    const DEFAULT_DOMAIN: &'static str = "localhost:8080";
-    pub fn ctx_domain(ctx: &Context) -> String {
-        ctx.domains
-            .get(0)
-            .and_then(|d| {
-                if d == "local.m2" {
-                    None
-                } else {
-                    Some(d.clone())
-                }
-            })
-            .unwrap_or_else(|| String::from(WpRecipe::DEFAULT_DOMAIN))
-    }
-    pub fn ctx_port(ctx: &Context) -> String {
-        let d = WpRecipe::ctx_domain(&ctx);
-        let _s = d.contains(':');
-        if d.contains(':') {
-            let s: String = d.split(':').skip(1).take(1).collect();
-            return s;
-        }
-        String::from("80")
-    }
-}
-
 impl DcTasksTrait for WpRecipe {
     fn volumes(&self, ctx: &Context) -> Vec<DcVolume> {
         get_volumes(ctx)
     }
+    fn proxy_service(&self) -> Option<(&'static str, u32)> {
+        Some((WpNginxService::NAME, 80))
+    }
     fn services(&self, ctx: &Context) -> Result<Box<dyn Services>, failure::Error> {
         let services = WpServices::from_ctx(ctx);
         Ok(Box::new(services))
@@ -89,8 +74,35 @@ impl PassThru for WpRecipe {
     }
 }
 
+impl AppServices for WpRecipe {
+    fn app_service(&self, ctx: &Context) -> Result<DcService, failure::Error> {
+        Ok(WpPhpService::select(&ctx))
+    }
+    fn app_root(&self) -> &'static str {
+        WpServices::ROOT
+    }
+    fn db_service(&self, ctx: &Context) -> Result<DbCredentials, failure::Error> {
+        Ok(DbCredentials::new(
+            (WpDbService).dc_service(&ctx, &()),
+            DbService::DB_USER,
+            DbService::DB_PASS,
+            DbService::DB_NAME,
+        ))
+    }
+}
+
 impl OutputFiles for WpRecipe {}
-impl ResolveScript for WpRecipe {}
+impl ResolveScript for WpRecipe {
+    fn resolve_script(&self, ctx: &Context, script: &Script) -> Option<Vec<Task>> {
+        if Script::has_dc_tasks(&script.steps) {
+            let (dc, dc_tasks) = (WpRecipe).dc_and_tasks(&ctx).ok()?;
+            Some(script.dc_tasks(&dc, &dc_tasks, vec![]))
+        } else {
+            let ts: Vec<Task> = script.clone().into();
+            Some(ts)
+        }
+    }
+}
 
 pub mod pass_thru;
 pub mod services;
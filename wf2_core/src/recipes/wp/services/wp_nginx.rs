@@ -1,9 +1,9 @@
 use crate::context::Context;
 use crate::dc_service::DcService;
+use crate::recipes::local_domain::LocalDomain;
 use crate::recipes::wp::services::wp_php::WpPhpService;
 use crate::recipes::wp::services::WpServices;
 use crate::recipes::wp::volumes::WpVolumeMounts;
-use crate::recipes::wp::WpRecipe;
 use crate::services::Service;
 
 pub struct WpNginxService;
@@ -13,7 +13,7 @@ impl Service for WpNginxService {
     const IMAGE: &'static str = "wearejh/nginx:stable-m2";
 
     fn dc_service(&self, ctx: &Context, _vars: &()) -> DcService {
-        let host_port = WpRecipe::ctx_port(&ctx);
+        let host_port = LocalDomain::port(&ctx);
         DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
             .set_depends_on(vec![WpPhpService::NAME])
             .set_volumes(vec![
@@ -1,19 +1,34 @@
 use crate::context::Context;
 use crate::dc_service::DcService;
+use crate::recipes::local_domain::LocalDomain;
 use crate::recipes::m2::services::php::PhpService;
 use crate::recipes::wp::services::wp_db::WpDbService;
+use crate::recipes::wp::services::wp_php_debug::WpPhpDebugService;
 use crate::recipes::wp::services::WpServices;
-use crate::recipes::wp::WpRecipe;
 use crate::services::Service;
 
 pub struct WpPhpService;
 
+impl WpPhpService {
+    ///
+    /// Select either the regular PHP container, or the one with XDEBUG
+    /// enabled when `--debug` was given
+    ///
+    pub fn select(ctx: &Context) -> DcService {
+        if ctx.debug {
+            (WpPhpDebugService).dc_service(ctx, &())
+        } else {
+            (WpPhpService).dc_service(ctx, &())
+        }
+    }
+}
+
 impl Service for WpPhpService {
     const NAME: &'static str = "php";
-    const IMAGE: &'static str = PhpService::IMAGE_7_3;
+    const IMAGE: &'static str = PhpService::IMAGE;
 
     fn dc_service(&self, ctx: &Context, _vars: &()) -> DcService {
-        let domain = WpRecipe::ctx_domain(&ctx);
+        let domain = LocalDomain::from_ctx(&ctx);
         let php_image = (PhpService).select_image(&ctx);
         DcService::new(ctx.name(), Self::NAME, php_image)
             .set_volumes(vec![format!("{}:{}", ctx.cwd.display(), WpServices::ROOT)])
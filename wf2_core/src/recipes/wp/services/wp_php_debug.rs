@@ -8,7 +8,7 @@ pub struct WpPhpDebugService;
 
 impl Service for WpPhpDebugService {
     const NAME: &'static str = "php-debug";
-    const IMAGE: &'static str = PhpService::IMAGE_7_3;
+    const IMAGE: &'static str = PhpService::IMAGE;
 
     fn dc_service(&self, ctx: &Context, _vars: &()) -> DcService {
         let mut php_cnt = (WpPhpService).dc_service(ctx, &());
@@ -1,16 +1,44 @@
 use crate::commands::CliCommand;
 
+use crate::subcommands::db_dump::DbDump;
+use crate::subcommands::db_import::DbImport;
 use crate::subcommands::down::DcDown;
+use crate::subcommands::exec::Exec;
+use crate::subcommands::hosts::HostsCmd;
+use crate::subcommands::list_images::ListImages;
+use crate::subcommands::pull::Pull;
+use crate::subcommands::push::Push;
+use crate::subcommands::sql::SqlCmd;
 use crate::subcommands::stop::DcStop;
+use crate::subcommands::update_images::UpdateImages;
+use wp_doctor::WpDoctor;
 use wp_playground::WpPlaygroundCmd;
 use wp_up::WpUp;
+use wp_xdebug::WpXdebugCmd;
 
+pub mod wp_doctor;
 pub mod wp_playground;
 pub mod wp_playground_help;
 pub mod wp_up;
+pub mod wp_xdebug;
 
 pub fn wp_recipe_subcommands<'a, 'b>() -> Vec<Box<dyn CliCommand<'a, 'b>>> {
-    vec![Box::new(WpUp), Box::new(DcStop), Box::new(DcDown)]
+    vec![
+        Box::new(WpUp),
+        Box::new(DcStop),
+        Box::new(DcDown),
+        Box::new(DbImport),
+        Box::new(DbDump),
+        Box::new(SqlCmd),
+        Box::new(WpDoctor),
+        Box::new(Exec),
+        Box::new(Pull),
+        Box::new(Push),
+        Box::new(ListImages),
+        Box::new(UpdateImages),
+        Box::new(HostsCmd),
+        Box::new(WpXdebugCmd),
+    ]
 }
 
 pub fn wp_recipe_global_subcommands<'a, 'b>() -> Vec<Box<dyn CliCommand<'a, 'b>>> {
@@ -0,0 +1,65 @@
+//!
+//! Diagnose & fix potential problems in the current WP project.
+//!
+//! For now this resets ownership of the uploads directory, which is the most
+//! common cause of media uploads failing, and checks that docker-compose is
+//! recent enough for the generated file.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 doctor
+//! # "#;
+//! # let commands = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::WP_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .commands();
+//! # assert_eq!(commands, vec!["docker exec -it wf2__shane__php chown -R www-data:www-data /var/www/web/app/uploads"])
+//! ```
+//!
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::recipes::wp::services::wp_php::WpPhpService;
+use crate::recipes::wp::services::WpServices;
+use crate::task::Task;
+use crate::tasks::compose_version::compose_version_task;
+use clap::{App, ArgMatches};
+
+#[doc_link::doc_link("/recipes/wp/subcommands/wp_doctor")]
+pub struct WpDoctor;
+
+impl WpDoctor {
+    const NAME: &'static str = "doctor";
+    const ABOUT: &'static str = "[wp] Try to fix common issues with a recipe";
+    const UPLOADS: &'static str = "web/app/uploads";
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for WpDoctor {
+    fn name(&self) -> String {
+        String::from(WpDoctor::NAME)
+    }
+    fn exec(&self, _matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        Some(doctor(ctx))
+    }
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(WpDoctor::NAME)
+            .about(WpDoctor::ABOUT)
+            .after_help(WpDoctor::DOC_LINK)]
+    }
+}
+
+fn doctor(ctx: &Context) -> Vec<Task> {
+    let service = WpPhpService::select(&ctx);
+    vec![
+        compose_version_task(),
+        Task::simple_command(format!(
+            "docker exec -it {container_name} chown -R www-data:www-data {root}/{uploads}",
+            container_name = service.container_name,
+            root = WpServices::ROOT,
+            uploads = WpDoctor::UPLOADS,
+        )),
+        Task::notify("Fixed permissions on the uploads directory"),
+    ]
+}
@@ -1,9 +1,9 @@
 use crate::commands::CliCommand;
 use crate::context::Context;
 use crate::file_op::inner_write_err;
+use crate::recipes::local_domain::LocalDomain;
 use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::recipes::wp::subcommands::wp_playground_help::help;
-use crate::recipes::wp::WpRecipe;
 use crate::task::Task;
 use crate::zip_utils;
 use ansi_term::Colour::{Cyan, Green};
@@ -65,7 +65,7 @@ impl<'a, 'b> CliCommand<'a, 'b> for WpPlaygroundCmd {
         let wp = WpPlayground {
             dir: ctx.cwd.join(output),
             version,
-            domain: WpRecipe::ctx_domain(&ctx),
+            domain: LocalDomain::from_ctx(&ctx),
         };
 
         let version_display = if wp.version.is_empty() {
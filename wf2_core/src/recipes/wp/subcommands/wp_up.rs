@@ -2,10 +2,14 @@ use crate::commands::CliCommand;
 use crate::context::Context;
 
 use crate::dc_tasks::DcTasksTrait;
+use crate::hosts::HostEntries;
+use crate::proxy::SharedProxy;
+use crate::recipes::wp::services::wp_php::WpPhpService;
 use crate::recipes::wp::volumes::WpVolumeMounts;
 use crate::recipes::wp::WpRecipe;
+use crate::services::Service;
 use crate::task::Task;
-use crate::tasks::docker_clean::docker_clean;
+use crate::tasks::port_check::port_checked_up;
 use clap::{App, ArgMatches};
 use structopt::StructOpt;
 
@@ -22,6 +26,8 @@ struct Opts {
     attached: bool,
     #[structopt(short, long)]
     clean: bool,
+    #[structopt(long)]
+    remap_ports: bool,
 }
 
 impl<'a, 'b> CliCommand<'a, 'b> for WpUp {
@@ -30,20 +36,29 @@ impl<'a, 'b> CliCommand<'a, 'b> for WpUp {
     }
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         let opts: Opts = matches.map(Opts::from_clap).expect("guarded by Clap");
-        Some(up(&ctx, opts.clean, opts.attached))
+        Some(up(&ctx, opts.clean, opts.attached, opts.remap_ports))
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
         vec![App::new(WpUp::NAME)
             .about(WpUp::ABOUT)
             .arg_from_usage("-a --attached 'Run in attached mode (streaming logs)'")
-            .arg_from_usage("-c --clean 'stop & remove other containers before starting new ones'")]
+            .arg_from_usage("-c --clean 'stop & remove other containers before starting new ones'")
+            .arg_from_usage("--remap-ports 'move services to free ports if theirs are taken'")]
     }
 }
 
-fn up(ctx: &Context, clean: bool, attached: bool) -> Vec<Task> {
+fn up(ctx: &Context, clean: bool, attached: bool, remap_ports: bool) -> Vec<Task> {
     (WpRecipe)
-        .dc_tasks(&ctx)
-        .map(|dc_tasks| {
+        .dc_and_tasks(&ctx)
+        .and_then(|(dc, dc_tasks)| {
+            let proxy_files = if SharedProxy::enabled(ctx) {
+                SharedProxy::from_home()?.project_tasks(ctx)
+            } else {
+                vec![]
+            };
+            Ok((dc, dc_tasks, proxy_files))
+        })
+        .map(|(dc, dc_tasks, proxy_files)| {
             let base_tasks = vec![
                 Task::file_write(
                     ctx.output_file_path(WpVolumeMounts::NGINX_CONF),
@@ -53,24 +68,73 @@ fn up(ctx: &Context, clean: bool, attached: bool) -> Vec<Task> {
                 Task::file_write(
                     ctx.output_file_path(WpVolumeMounts::NGINX_DEFAULT_HOST),
                     "Writes the nginx conf file",
-                    include_bytes!("../templates/host.conf").to_vec(),
+                    WpVolumeMounts::nginx_host_bytes(WpPhpService::NAME),
                 ),
             ];
 
-            let clean = if clean { docker_clean() } else { vec![] };
+            let clean = SharedProxy::clean_tasks(ctx, clean);
+            let proxy_network = SharedProxy::project_up_tasks(ctx);
 
-            let up_task = if attached {
-                dc_tasks.cmd_task(vec!["up".to_string()])
-            } else {
-                dc_tasks.cmd_task(vec!["up -d".to_string()])
+            let up_args = if attached { "up" } else { "up -d" };
+            let up_tasks = port_checked_up(ctx, &dc, &dc_tasks, up_args, remap_ports);
+
+            let hosts_task = match HostEntries::from_ctx(ctx) {
+                Ok(entries) if !attached => entries.warn_task(HostEntries::DEFAULT_PATH),
+                Ok(_) => Task::Noop,
+                Err(e) => Task::notify_error(e.to_string()),
             };
 
             vec![]
                 .into_iter()
                 .chain(base_tasks.into_iter())
+                .chain(proxy_files.into_iter())
                 .chain(clean.into_iter())
-                .chain(vec![up_task].into_iter())
+                .chain(proxy_network.into_iter())
+                .chain(up_tasks.into_iter())
+                .chain(vec![hosts_task].into_iter())
                 .collect()
         })
         .unwrap_or_else(Task::task_err_vec)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::cli_input::CLIInput;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::task::Task;
+    use crate::test::Test;
+
+    #[test]
+    fn test_up_with_shared_proxy() {
+        let commands = Test::from_cmd("wf2 up --clean")
+            .with_file("../fixtures/wp_proxy.yml")
+            .with_cli_input(CLIInput::from_cwd("/users/shane"))
+            .commands();
+        assert_eq!(
+            commands,
+            vec![
+                "docker network inspect wf2_proxy >/dev/null 2>&1 || docker network create wf2_proxy",
+                "docker-compose -f /users/shane/.wf2_wp_shane/docker-compose.yml up -d",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_up_checks_ports() {
+        let tasks = Test::from_cmd("wf2 up --remap-ports")
+            .with_recipe(RecipeKinds::WP_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/shane"))
+            .tasks();
+        assert!(tasks.iter().any(|task| match task {
+            Task::Exec {
+                description: Some(d),
+                ..
+            } => d == "check published ports are available",
+            _ => false,
+        }));
+        assert_eq!(
+            Test::_commands(&tasks),
+            vec!["docker-compose -f /users/shane/.wf2_wp_shane/docker-compose.yml up -d"]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//!
+//! Enable or disable XDebug for the Wp recipe.
+//!
+//! This re-writes the nginx host file to send PHP requests to the
+//! `php-debug` container and then reloads nginx.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 xdebug enable
+//! # "#;
+//! # let (commands, (_read, write, _delete)) = Test::from_cmd(cmd)
+//! #     .with_recipe(RecipeKinds::WP_NAME)
+//! #     .with_cli_input(CLIInput::from_cwd("/users/shane"))
+//! #     .file_ops_paths_commands();
+//! # assert_eq!(write, vec![
+//! #     "/users/shane/.wf2_wp_shane/nginx/host.conf",
+//! # ]);
+//! # assert_eq!(commands, vec!["docker-compose -f /users/shane/.wf2_wp_shane/docker-compose.yml exec nginx nginx -s reload"]);
+//! ```
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::dc_tasks::DcTasksTrait;
+use crate::recipes::wp::services::wp_php::WpPhpService;
+use crate::recipes::wp::services::wp_php_debug::WpPhpDebugService;
+use crate::recipes::wp::volumes::WpVolumeMounts;
+use crate::recipes::wp::WpRecipe;
+use crate::services::Service;
+use crate::task::Task;
+use clap::{App, ArgMatches, SubCommand};
+
+#[doc_link::doc_link("/recipes/wp/subcommands/wp_xdebug")]
+pub struct WpXdebugCmd;
+
+impl WpXdebugCmd {
+    const NAME: &'static str = "xdebug";
+    const ABOUT: &'static str = "[wp] Enable or disable XDebug";
+
+    const ENABLE: &'static str = "enable";
+    const DISABLE: &'static str = "disable";
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for WpXdebugCmd {
+    fn name(&self) -> String {
+        String::from(WpXdebugCmd::NAME)
+    }
+
+    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        let enabled = matches.map(|m| m.subcommand_name()).and_then(|n| match n {
+            Some(WpXdebugCmd::ENABLE) => Some(true),
+            Some(WpXdebugCmd::DISABLE) => Some(false),
+            _ => None,
+        });
+
+        match enabled {
+            Some(enabled) => Some(xdebug(ctx, enabled)),
+            None => Some(vec![Task::notify_error("missing `enable` or `disable`")]),
+        }
+    }
+
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(WpXdebugCmd::NAME)
+            .about(WpXdebugCmd::ABOUT)
+            .after_help(WpXdebugCmd::DOC_LINK)
+            .subcommands(vec![
+                SubCommand::with_name(WpXdebugCmd::ENABLE)
+                    .display_order(0)
+                    .about("Enable XDebug"),
+                SubCommand::with_name(WpXdebugCmd::DISABLE)
+                    .display_order(0)
+                    .about("Disable XDebug"),
+            ])]
+    }
+}
+
+fn xdebug(ctx: &Context, enabled: bool) -> Vec<Task> {
+    let (backend, msg) = if enabled {
+        (WpPhpDebugService::NAME, "XDebug Enabled")
+    } else {
+        (WpPhpService::NAME, "XDebug Disabled")
+    };
+
+    (WpRecipe)
+        .dc_tasks(&ctx)
+        .map(|dc| {
+            vec![
+                Task::notify_info("updating host.conf"),
+                Task::file_write(
+                    ctx.output_file_path(WpVolumeMounts::NGINX_DEFAULT_HOST),
+                    "Writes the nginx conf file",
+                    WpVolumeMounts::nginx_host_bytes(backend),
+                ),
+                Task::notify_info("reloading nginx conf"),
+                dc.cmd_task(vec!["exec", "nginx", "nginx", "-s", "reload"]),
+                Task::notify_info(msg),
+            ]
+        })
+        .unwrap_or_else(Task::task_err_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nginx_host_backend() {
+        let enabled = String::from_utf8(WpVolumeMounts::nginx_host_bytes(WpPhpDebugService::NAME))
+            .expect("test");
+        assert!(enabled.contains("fastcgi_pass php-debug:9000;"));
+        let disabled =
+            String::from_utf8(WpVolumeMounts::nginx_host_bytes(WpPhpService::NAME)).expect("test");
+        assert!(disabled.contains("fastcgi_pass php:9000;"));
+    }
+}
@@ -20,3 +20,17 @@ impl WpVolumeMounts {
     pub const NGINX_DEFAULT_HOST: &'static str = "nginx/host.conf";
     pub const NGINX_DEFAULT_REMOTE: &'static str = "/etc/nginx/conf.d/default.conf";
 }
+
+impl WpVolumeMounts {
+    ///
+    /// The nginx host file, with PHP requests passed to `backend`
+    ///
+    pub fn nginx_host_bytes(backend: &str) -> Vec<u8> {
+        include_str!("templates/host.conf")
+            .replace(
+                "fastcgi_pass php:9000",
+                &format!("fastcgi_pass {}:9000", backend),
+            )
+            .into_bytes()
+    }
+}
@@ -1,8 +1,12 @@
 use crate::context::Context;
+use crate::dc::Dc;
+use crate::dc_tasks::DcTasks;
+use crate::file::File;
 use crate::scripts::script_item::ScriptItem;
 use crate::scripts::scripts::Scripts;
 use crate::scripts::service_cmd::ServiceCmd;
 use crate::task::Task;
+use crate::util::path_buf_to_string;
 
 pub trait ResolveScript {
     fn resolve_script(&self, _ctx: &Context, _script: &Script) -> Option<Vec<Task>> {
@@ -134,4 +138,81 @@ impl Script {
             ..self.clone()
         }
     }
+
+    ///
+    /// Rewrite the commands of `run` & `exec` steps, eg: to swap a binary
+    ///
+    pub fn map_commands(&self, f: impl Fn(&str) -> String) -> Script {
+        let map = |cmd: ServiceCmd| ServiceCmd {
+            command: cmd.command.as_deref().map(&f),
+            commands: cmd
+                .commands
+                .as_ref()
+                .map(|commands| commands.iter().map(|c| f(c)).collect()),
+            ..cmd
+        };
+        Script {
+            steps: self
+                .steps
+                .clone()
+                .into_iter()
+                .map(|step| match step {
+                    ScriptItem::DcRunCommand { run } => ScriptItem::DcRunCommand { run: map(run) },
+                    ScriptItem::DcExecCommand { exec } => {
+                        ScriptItem::DcExecCommand { exec: map(exec) }
+                    }
+                    _ => step,
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    ///
+    /// Convert this script into tasks for a recipe that uses docker-compose.
+    ///
+    /// Any `run`/`exec`/`dc` steps are checked against the services in `dc` and
+    /// then pointed at the docker-compose file, which is written first along with
+    /// any other files given in `before`.
+    ///
+    pub fn dc_tasks(&self, dc: &Dc, dc_tasks: &DcTasks, before: Vec<Task>) -> Vec<Task> {
+        if !Script::has_dc_tasks(&self.steps) {
+            return self.clone().into();
+        }
+
+        let recipes_services = dc.service_names();
+        let script_refs = Script::service_names(&self.steps);
+
+        if let (Some(allowed), Some(script_refs)) = (recipes_services, script_refs) {
+            let missing: Vec<String> = script_refs
+                .iter()
+                .filter(|item| !allowed.contains(item))
+                .map(String::from)
+                .collect();
+
+            if !missing.is_empty() {
+                use ansi_term::Colour::{Cyan, Red};
+                let error = format!(
+                    "You tried to use the following service(s) in \
+                    your wf2 file - \nbut they don't exist in this recipe\n\n    {}
+                    ",
+                    Red.paint(missing.join(", "))
+                );
+                let advise = format!(
+                    "The following names are all valid though\n\n    {}",
+                    Cyan.paint(allowed.join("\n    "))
+                );
+                return vec![Task::notify_error(vec![error, advise].join("\n"))];
+            }
+        }
+
+        let script = self.set_dc_file(path_buf_to_string(&dc_tasks.file));
+        let script_tasks: Vec<Task> = script.into();
+
+        vec![dc_tasks.write_task()]
+            .into_iter()
+            .chain(before.into_iter())
+            .chain(script_tasks.into_iter())
+            .collect()
+    }
 }
@@ -1,13 +1,26 @@
 use crate::context::Context;
+use crate::dc_healthcheck::DcHealthcheck;
 use crate::dc_service::DcService;
 
 use crate::recipes::m2::dc_tasks::M2Volumes;
 use crate::services::Service;
+use crate::versions::search_engine::SearchEngine;
 
 pub struct ElasticSearchService;
 
 impl ElasticSearchService {
     const VOLUME_DATA: &'static str = "/usr/share/elasticsearch/data";
+
+    pub fn healthcheck() -> DcHealthcheck {
+        DcHealthcheck::cmd_shell(
+            "curl -fs 'http://127.0.0.1:9200/_cluster/health?wait_for_status=yellow&timeout=5s' || exit 1",
+        )
+        .set_interval("10s")
+        .set_timeout("10s")
+        .set_retries(30)
+        .set_start_period("30s")
+        .finish()
+    }
 }
 
 impl Service for ElasticSearchService {
@@ -15,8 +28,7 @@ impl Service for ElasticSearchService {
     const IMAGE: &'static str = "wearejh/elasticsearch:7.6-m2";
 
     fn dc_service(&self, ctx: &Context, _: &()) -> DcService {
-        let image = format!("wearejh/elasticsearch:{}-m2", ctx.es_version.get_image());
-        DcService::new(ctx.name(), Self::NAME, image)
+        DcService::new(ctx.name(), Self::NAME, self.select_image(ctx))
             .set_ports(vec!["9200:9200"])
             .set_volumes(vec![format!(
                 "{}:{}",
@@ -25,6 +37,16 @@ impl Service for ElasticSearchService {
             )])
             .set_environment(vec!["discovery.type=single-node"])
             .set_labels(vec![Self::TRAEFIK_DISABLE_LABEL.to_string()])
+            .set_healthcheck(ElasticSearchService::healthcheck())
             .finish()
     }
+
+    fn select_image(&self, ctx: &Context) -> String {
+        let search_engine = SearchEngine::from_ctx(ctx);
+        if search_engine.is_opensearch() {
+            Self::IMAGE.to_string()
+        } else {
+            search_engine.image(ctx)
+        }
+    }
 }
@@ -0,0 +1,280 @@
+//!
+//! Extra services, declared by the user under `options.services.extra`.
+//!
+//! These are available in every recipe and are added to the generated
+//! `docker-compose.yml` alongside the recipe's own services.
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     extra:
+//!       solr:
+//!         image: solr:8
+//!         volumes: [ "solr-data:/var/solr" ]
+//!         domains: [ solr.jh ]
+//!         port: 8983
+//!       mock-payments:
+//!         build:
+//!           context: ./docker/mock-payments
+//!         env:
+//!           - PORT=3000
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx).expect("valid dc");
+//! # let yaml = String::from_utf8(dc.to_bytes()).expect("utf8");
+//! # let services = dc.services.expect("services");
+//! # let solr = services.get("solr").expect("solr");
+//! # assert_eq!(solr.container_name, "wf2__wf2_default__solr");
+//! # assert_eq!(
+//! #     solr.labels,
+//! #     Some(vec![
+//! #         String::from("traefik.frontend.rule=Host:solr.jh"),
+//! #         String::from("traefik.port=8983"),
+//! #     ])
+//! # );
+//! # let mock = services.get("mock-payments").expect("mock-payments");
+//! # assert_eq!(mock.build.as_ref().expect("build").dockerfile.to_str(), Some("Dockerfile"));
+//! # assert!(!yaml.contains("image: \"\""));
+//! # assert!(dc.volumes.expect("volumes").get("solr-data").is_some());
+//! ```
+//!
+//! - Either `image` or `build` is required. With only `build`, docker-compose names the image.
+//! - `domains` will route through Traefik to `port` (defaults to 80).
+//! - Named volumes, like `solr-data` above, are created automatically.
+//! - Names must not clash with any service the recipe already provides.
+//!
+use crate::context::Context;
+use crate::dc_image_build::DcImageBuild;
+use crate::dc_service::DcService;
+use crate::dc_volume::DcVolume;
+use crate::services::traefik::TraefikService;
+use crate::services::Services;
+use std::collections::BTreeMap;
+
+///
+/// The `DcService`-shaped definition that can be given for
+/// each entry under `options.services.extra`
+///
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ExtraServiceOptions {
+    pub image: Option<String>,
+    pub build: Option<DcImageBuild>,
+    pub ports: Option<Vec<String>>,
+    pub volumes: Option<Vec<String>>,
+    #[serde(alias = "env")]
+    pub environment: Option<Vec<String>>,
+    pub labels: Option<Vec<String>>,
+    pub depends_on: Option<Vec<String>>,
+    pub command: Option<String>,
+    pub working_dir: Option<String>,
+    pub domains: Option<Vec<String>>,
+    pub port: Option<u32>,
+}
+
+impl ExtraServiceOptions {
+    pub const DEFAULT_PORT: u32 = 80;
+
+    pub fn dc_service(&self, ctx: &Context, name: &str) -> Result<DcService, failure::Error> {
+        if self.image.is_none() && self.build.is_none() {
+            return Err(ExtraServiceError::MissingImage(name.to_string()).into());
+        }
+
+        let mut service = DcService::new(ctx.name(), name, self.image.clone().unwrap_or_default());
+
+        if let Some(build) = self.build.clone() {
+            service.set_build(build);
+        }
+        if let Some(ports) = self.ports.clone() {
+            service.set_ports(ports);
+        }
+        if let Some(volumes) = self.volumes.clone() {
+            service.set_volumes(volumes);
+        }
+        if let Some(environment) = self.environment.clone() {
+            service.set_environment(environment);
+        }
+        if let Some(depends_on) = self.depends_on.clone() {
+            service.set_depends_on(depends_on);
+        }
+        if let Some(command) = self.command.clone() {
+            service.set_command(command);
+        }
+        if let Some(working_dir) = self.working_dir.clone() {
+            service.set_working_dir(working_dir);
+        }
+
+        let mut labels = self.labels.clone().unwrap_or_default();
+        if let Some(domains) = self.domains.as_ref().filter(|d| !d.is_empty()) {
+            labels.extend(TraefikService::labels(
+                ctx,
+                name,
+                domains.clone(),
+                Some(self.port.unwrap_or(ExtraServiceOptions::DEFAULT_PORT)),
+            ));
+        }
+        if !labels.is_empty() {
+            service.set_labels(labels);
+        }
+
+        Ok(service.finish())
+    }
+
+    ///
+    /// Volumes like `solr-data:/var/solr` need a top-level entry,
+    /// whereas host paths like `./solr:/var/solr` do not
+    ///
+    fn named_volumes(&self) -> Vec<String> {
+        self.volumes.as_ref().map_or(vec![], |volumes| {
+            volumes
+                .iter()
+                .filter_map(|volume| volume.split(':').next())
+                .filter(|source| {
+                    !source.is_empty()
+                        && !source.starts_with('.')
+                        && !source.starts_with('/')
+                        && !source.starts_with('~')
+                })
+                .map(String::from)
+                .collect()
+        })
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum ExtraServiceError {
+    #[fail(
+        display = "Extra service `{}` needs either an `image` or a `build` section",
+        _0
+    )]
+    MissingImage(String),
+    #[fail(
+        display = "Extra service `{}` clashes with a service of the same name in this recipe",
+        _0
+    )]
+    Conflict(String),
+    #[fail(
+        display = "Couldn't parse options.services.extra from wf2.yaml: {}",
+        _0
+    )]
+    Invalid(String),
+}
+
+///
+/// Wraps the services of any recipe, adding those found
+/// under `options.services.extra`
+///
+pub struct ExtraServices {
+    services: Vec<DcService>,
+    volumes: Vec<DcVolume>,
+}
+
+impl ExtraServices {
+    pub fn options(ctx: &Context) -> Result<BTreeMap<String, ExtraServiceOptions>, failure::Error> {
+        let extra = ctx
+            .options
+            .as_ref()
+            .map(|opts| opts["services"]["extra"].clone())
+            .filter(|extra| !extra.is_null());
+
+        match extra {
+            Some(extra) => serde_yaml::from_value(extra)
+                .map_err(|e| ExtraServiceError::Invalid(e.to_string()).into()),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    pub fn from_ctx(ctx: &Context, recipe_services: &dyn Services) -> Result<Self, failure::Error> {
+        let mut services = recipe_services.dc_services();
+        let mut volumes = vec![];
+
+        for (name, opts) in ExtraServices::options(ctx)? {
+            if recipe_services.service_by_name(&name).is_some() {
+                return Err(ExtraServiceError::Conflict(name).into());
+            }
+            services.push(opts.dc_service(ctx, &name)?);
+            volumes.extend(
+                opts.named_volumes()
+                    .into_iter()
+                    .map(|volume| DcVolume::new(ctx.name(), volume)),
+            );
+        }
+
+        Ok(ExtraServices { services, volumes })
+    }
+
+    pub fn volumes(&self) -> Vec<DcVolume> {
+        self.volumes.clone()
+    }
+}
+
+impl Services for ExtraServices {
+    fn dc_services(&self) -> Vec<DcService> {
+        self.services.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_tasks::DcTasksTrait;
+    use crate::recipes::wp::WpRecipe;
+
+    #[test]
+    fn test_extra_services_wp() {
+        let ctx_yaml = r#"
+        recipe: Wp
+        options:
+          services:
+            extra:
+              redis-sessions:
+                image: redis:5
+                ports: [ "6380:6379" ]
+        "#;
+        let ctx = Context::new_from_str(ctx_yaml).expect("test");
+        let dc = (WpRecipe).dc(&ctx).expect("test");
+        let services = dc.services.expect("test");
+        let redis = services.get("redis-sessions").expect("test");
+        assert_eq!(redis.image, "redis:5");
+        assert_eq!(redis.ports, Some(vec![String::from("6380:6379")]));
+        assert!(redis.labels.is_none());
+        assert!(services.contains_key("php"));
+    }
+
+    #[test]
+    fn test_extra_services_errors() {
+        let missing_image = r#"
+        recipe: Wp
+        options:
+          services:
+            extra:
+              solr:
+                ports: [ "8983" ]
+        "#;
+        let ctx = Context::new_from_str(missing_image).expect("test");
+        let err = (WpRecipe).dc(&ctx).expect_err("test");
+        assert_eq!(
+            err.to_string(),
+            "Extra service `solr` needs either an `image` or a `build` section"
+        );
+
+        let conflict = r#"
+        recipe: Wp
+        options:
+          services:
+            extra:
+              php:
+                image: php:7.4
+        "#;
+        let ctx = Context::new_from_str(conflict).expect("test");
+        let err = (WpRecipe).dc(&ctx).expect_err("test");
+        assert_eq!(
+            err.to_string(),
+            "Extra service `php` clashes with a service of the same name in this recipe"
+        );
+    }
+}
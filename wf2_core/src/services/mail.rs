@@ -9,6 +9,8 @@ pub struct MailService;
 
 impl MailService {
     pub const DOMAIN: &'static str = "mail.jh";
+    pub const SMTP_PORT: u32 = 1025;
+    pub const UI_PORT: u32 = 8025;
 }
 
 impl fmt::Display for MailService {
@@ -23,10 +25,12 @@ impl Service for MailService {
 
     fn dc_service(&self, ctx: &Context, _: &()) -> DcService {
         DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
-            .set_ports(vec!["1025"])
-            .set_labels(TraefikService::host_entry_label(
-                MailService::DOMAIN,
-                8025_u32,
+            .set_ports(vec![MailService::SMTP_PORT.to_string()])
+            .set_labels(TraefikService::labels(
+                ctx,
+                Self::NAME,
+                vec![MailService::DOMAIN.to_string()],
+                Some(MailService::UI_PORT),
             ))
             .finish()
     }
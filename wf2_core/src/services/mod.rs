@@ -3,9 +3,13 @@ use crate::dc_service::DcService;
 
 pub mod blackfire;
 pub mod elastic_search;
+pub mod extra;
 pub mod mail;
 pub mod nginx;
 pub mod node;
+pub mod open_search;
+pub mod overrides;
+pub mod ports;
 pub mod pwa;
 pub mod rabbit_mq;
 pub mod redis;
@@ -0,0 +1,66 @@
+use crate::context::Context;
+use crate::dc_service::DcService;
+
+use crate::recipes::m2::dc_tasks::M2Volumes;
+use crate::services::elastic_search::ElasticSearchService;
+use crate::services::Service;
+use crate::versions::search_engine::SearchEngine;
+
+pub struct OpenSearchService;
+
+impl OpenSearchService {
+    const VOLUME_DATA: &'static str = "/usr/share/opensearch/data";
+
+    ///
+    /// Magento needs these plugins, which the official image doesn't include
+    ///
+    const PLUGINS: [&'static str; 2] = ["analysis-icu", "analysis-phonetic"];
+
+    pub fn command() -> String {
+        let installs = OpenSearchService::PLUGINS
+            .iter()
+            .map(|plugin| {
+                format!(
+                    "(bin/opensearch-plugin list | grep -q {plugin} || bin/opensearch-plugin install --batch {plugin})",
+                    plugin = plugin
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" && ");
+        format!("sh -c '{} && ./opensearch-docker-entrypoint.sh'", installs)
+    }
+}
+
+impl Service for OpenSearchService {
+    const NAME: &'static str = "opensearch";
+    const IMAGE: &'static str = "opensearchproject/opensearch:2.12.0";
+
+    fn dc_service(&self, ctx: &Context, _: &()) -> DcService {
+        DcService::new(ctx.name(), Self::NAME, self.select_image(ctx))
+            .set_ports(vec!["9200:9200"])
+            .set_volumes(vec![format!(
+                "{}:{}",
+                M2Volumes::OPENSEARCH,
+                OpenSearchService::VOLUME_DATA
+            )])
+            .set_environment(vec![
+                "discovery.type=single-node",
+                "DISABLE_SECURITY_PLUGIN=true",
+                "DISABLE_INSTALL_DEMO_CONFIG=true",
+                "OPENSEARCH_JAVA_OPTS=-Xms512m -Xmx512m",
+            ])
+            .set_command(OpenSearchService::command())
+            .set_labels(vec![Self::TRAEFIK_DISABLE_LABEL.to_string()])
+            .set_healthcheck(ElasticSearchService::healthcheck())
+            .finish()
+    }
+
+    fn select_image(&self, ctx: &Context) -> String {
+        let search_engine = SearchEngine::from_ctx(ctx);
+        if search_engine.is_opensearch() {
+            search_engine.image(ctx)
+        } else {
+            Self::IMAGE.to_string()
+        }
+    }
+}
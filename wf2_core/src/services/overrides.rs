@@ -0,0 +1,300 @@
+//!
+//! Per-service overrides of the generated docker-compose definitions.
+//!
+//! Any service, in any recipe, can have compose keys merged into it
+//! under `options.services.<name>.override`
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     php:
+//!       override:
+//!         ports: [ "9001:9001" ]
+//!         volumes: [ "./php/custom.ini:/usr/local/etc/php/conf.d/zz-custom.ini" ]
+//!     elasticsearch:
+//!       override:
+//!         environment:
+//!           - ES_JAVA_OPTS=-Xms2g -Xmx2g
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx).expect("valid dc");
+//! # let services = dc.services.expect("services");
+//! # let php = services.get("php").expect("php");
+//! # assert!(php.ports.as_ref().expect("ports").contains(&String::from("9001:9001")));
+//! # assert!(php.volumes.as_ref().expect("volumes").contains(&String::from(
+//! #     "./php/custom.ini:/usr/local/etc/php/conf.d/zz-custom.ini"
+//! # )));
+//! # let es = services.get("elasticsearch").expect("es");
+//! # assert_eq!(
+//! #     es.environment,
+//! #     Some(vec![
+//! #         String::from("discovery.type=single-node"),
+//! #         String::from("ES_JAVA_OPTS=-Xms2g -Xmx2g")
+//! #     ])
+//! # );
+//! ```
+//!
+//! - Mappings are merged recursively.
+//! - Lists are appended to, except `KEY=VALUE` entries which replace
+//!   any existing entry with the same `KEY`.
+//! - `entrypoint` is always replaced, in either its string or list form.
+//! - Anything else replaces the generated value.
+//! - `name` and `container_name` cannot be changed.
+//!
+use crate::context::Context;
+use crate::dc_service::DcService;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Fail)]
+pub enum ServiceOverrideError {
+    #[fail(display = "Cannot override `{}` on service `{}`", key, service)]
+    Forbidden { service: String, key: String },
+    #[fail(display = "`{}` is not a supported key (service `{}`)", key, service)]
+    UnknownKey { service: String, key: String },
+    #[fail(
+        display = "Cannot override `{}`, since it's not a service in this recipe",
+        _0
+    )]
+    UnknownService(String),
+    #[fail(display = "Invalid override for service `{}`: {}", service, error)]
+    Invalid { service: String, error: String },
+}
+
+pub struct ServiceOverrides;
+
+impl ServiceOverrides {
+    ///
+    /// Keys that are managed by wf2 and can never be changed
+    ///
+    pub const FORBIDDEN: [&'static str; 2] = ["name", "container_name"];
+
+    ///
+    /// Keys that are replaced as a whole, rather than merged
+    ///
+    pub const REPLACED: [&'static str; 1] = ["entrypoint"];
+
+    ///
+    /// Every `override` block, keyed by service name
+    ///
+    pub fn from_ctx(ctx: &Context) -> BTreeMap<String, Value> {
+        ctx.options
+            .as_ref()
+            .and_then(|opts| opts["services"].as_mapping())
+            .map_or(BTreeMap::new(), |services| {
+                services
+                    .iter()
+                    .filter(|(_, opts)| !opts["override"].is_null())
+                    .filter_map(|(name, opts)| {
+                        name.as_str()
+                            .map(|name| (name.to_string(), opts["override"].clone()))
+                    })
+                    .collect()
+            })
+    }
+
+    ///
+    /// Apply any overrides from the context to the given services
+    ///
+    pub fn apply(
+        ctx: &Context,
+        services: Vec<DcService>,
+    ) -> Result<Vec<DcService>, failure::Error> {
+        let overrides = ServiceOverrides::from_ctx(ctx);
+
+        if let Some(unknown) = overrides
+            .keys()
+            .find(|name| !services.iter().any(|service| &service.name == *name))
+        {
+            return Err(ServiceOverrideError::UnknownService(unknown.to_string()).into());
+        }
+
+        services
+            .into_iter()
+            .map(|service| match overrides.get(&service.name) {
+                Some(value) => ServiceOverrides::merge_service(&service, value),
+                None => Ok(service),
+            })
+            .collect()
+    }
+
+    ///
+    /// Deep-merge a single override block into a service
+    ///
+    pub fn merge_service(service: &DcService, value: &Value) -> Result<DcService, failure::Error> {
+        let invalid = |error: String| ServiceOverrideError::Invalid {
+            service: service.name.clone(),
+            error,
+        };
+
+        let mapping = value
+            .as_mapping()
+            .ok_or_else(|| invalid(String::from("expected a map of compose keys")))?;
+
+        if let Some(key) = mapping
+            .iter()
+            .filter_map(|(key, _)| key.as_str())
+            .find(|key| ServiceOverrides::FORBIDDEN.contains(key))
+        {
+            return Err(ServiceOverrideError::Forbidden {
+                service: service.name.clone(),
+                key: key.to_string(),
+            }
+            .into());
+        }
+
+        let mut merged = serde_yaml::to_value(service).map_err(|e| invalid(e.to_string()))?;
+        if let Value::Mapping(m) = &mut merged {
+            for key in ServiceOverrides::REPLACED.iter() {
+                if !value[*key].is_null() {
+                    m.remove(&Value::from(*key));
+                }
+            }
+        }
+        merge(&mut merged, value);
+
+        // `name` is internal only, so it's not serialized - it needs adding back
+        if let Value::Mapping(m) = &mut merged {
+            m.insert(Value::from("name"), Value::from(service.name.clone()));
+        }
+
+        let next: DcService = serde_yaml::from_value(merged).map_err(|e| invalid(e.to_string()))?;
+
+        // any key that didn't survive the round-trip is one that DcService doesn't know about
+        let as_value = serde_yaml::to_value(&next).map_err(|e| invalid(e.to_string()))?;
+        if let Some(key) = mapping
+            .iter()
+            .filter(|(_, v)| !v.is_null())
+            .filter_map(|(key, _)| key.as_str())
+            .find(|key| as_value[*key].is_null())
+        {
+            return Err(ServiceOverrideError::UnknownKey {
+                service: service.name.clone(),
+                key: key.to_string(),
+            }
+            .into());
+        }
+
+        Ok(next)
+    }
+}
+
+fn merge(a: &mut Value, b: &Value) {
+    match (a, b) {
+        (Value::Mapping(a), Value::Mapping(b)) => {
+            for (k, v) in b {
+                match a.get_mut(k) {
+                    Some(existing) => merge(existing, v),
+                    None => {
+                        a.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        (Value::Sequence(a), Value::Sequence(b)) => {
+            for item in b {
+                match env_key(item).and_then(|key| a.iter().position(|x| env_key(x) == Some(key))) {
+                    Some(index) => a[index] = item.clone(),
+                    None if !a.contains(item) => a.push(item.clone()),
+                    None => {}
+                }
+            }
+        }
+        (a, b) => *a = b.clone(),
+    }
+}
+
+///
+/// For entries like `discovery.type=single-node` return `discovery.type`
+///
+fn env_key(value: &Value) -> Option<&str> {
+    value
+        .as_str()
+        .and_then(|s| s.find('=').map(|index| &s[..index]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_entrypoint::DcEntrypoint;
+    use crate::dc_tasks::DcTasksTrait;
+    use crate::recipes::m2::M2Recipe;
+
+    #[test]
+    fn test_merge_service() {
+        let service = DcService::new("acme", "php", "wearejh/php:7.3-m2")
+            .set_environment(vec!["A=1", "B=2"])
+            .set_ports(vec!["80"])
+            .finish();
+        let value: Value = serde_yaml::from_str(
+            r#"
+            image: wearejh/php:7.4-m2
+            environment: [ "B=3", "C=4" ]
+            ports: [ "80", "81" ]
+            "#,
+        )
+        .expect("test");
+        let actual = ServiceOverrides::merge_service(&service, &value).expect("test");
+        assert_eq!(actual.name, "php");
+        assert_eq!(actual.image, "wearejh/php:7.4-m2");
+        assert_eq!(
+            actual.environment,
+            Some(vec![
+                String::from("A=1"),
+                String::from("B=3"),
+                String::from("C=4")
+            ])
+        );
+        assert_eq!(
+            actual.ports,
+            Some(vec![String::from("80"), String::from("81")])
+        );
+    }
+
+    #[test]
+    fn test_merge_entrypoint() {
+        let service = DcService::new("acme", "php", "wearejh/php:7.3-m2")
+            .set_entrypoint(vec!["docker-php-entrypoint"])
+            .finish();
+        let value: Value =
+            serde_yaml::from_str(r#"entrypoint: ["sh", "-c", "exec php-fpm"]"#).expect("test");
+        let actual = ServiceOverrides::merge_service(&service, &value).expect("test");
+        assert_eq!(
+            actual.entrypoint,
+            Some(DcEntrypoint::from(vec!["sh", "-c", "exec php-fpm"]))
+        );
+
+        let value: Value = serde_yaml::from_str("entrypoint: /custom.sh").expect("test");
+        let actual = ServiceOverrides::merge_service(&service, &value).expect("test");
+        assert_eq!(actual.entrypoint, Some(DcEntrypoint::from("/custom.sh")));
+    }
+
+    #[test]
+    fn test_override_errors() {
+        let cases = vec![
+            (
+                "php: { override: { container_name: other } }",
+                "Cannot override `container_name` on service `php`",
+            ),
+            (
+                "php: { override: { nope: 1 } }",
+                "`nope` is not a supported key (service `php`)",
+            ),
+            (
+                "mongo: { override: { image: mongo } }",
+                "Cannot override `mongo`, since it's not a service in this recipe",
+            ),
+        ];
+        for (services, expected) in cases {
+            let ctx_yaml = format!("recipe: M2\noptions:\n  services: {{ {} }}", services);
+            let ctx = Context::new_from_str(&ctx_yaml).expect("test");
+            let err = (M2Recipe).dc(&ctx).expect_err("test");
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+}
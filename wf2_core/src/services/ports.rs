@@ -0,0 +1,201 @@
+//!
+//! Change the published ports of any service.
+//!
+//! Unlike `override`, which appends to lists, `ports` replaces the
+//! service's published ports entirely - useful when something else on your
+//! machine is already using `3306`, or when running two projects at once.
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     db:
+//!       ports: [ "3307:3306" ]
+//!     elasticsearch:
+//!       ports: [ "9201:9200" ]
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx).expect("valid dc");
+//! # let services = dc.services.expect("services");
+//! # assert_eq!(services.get("db").expect("db").ports, Some(vec![String::from("3307:3306")]));
+//! # assert_eq!(
+//! #     services.get("elasticsearch").expect("es").ports,
+//! #     Some(vec![String::from("9201:9200")])
+//! # );
+//! ```
+//!
+//! Ports picked by `wf2 up --remap-ports` are saved in `.wf2_ports.yml`, next to `wf2.yml`,
+//! and used in the same way until that file is deleted. Ports given here always win.
+//!
+use crate::context::Context;
+use crate::dc_service::DcService;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Fail)]
+pub enum ServicePortsError {
+    #[fail(
+        display = "Cannot set ports for `{}`, since it's not a service in this recipe",
+        _0
+    )]
+    UnknownService(String),
+    #[fail(
+        display = "Invalid ports for service `{}`, expected a list like [ \"3307:3306\" ]",
+        _0
+    )]
+    Invalid(String),
+    #[fail(
+        display = "Invalid remapped ports in {}, delete it to use the defaults",
+        _0
+    )]
+    InvalidRemapped(String),
+}
+
+pub type PortsMap = BTreeMap<String, Vec<String>>;
+
+pub struct ServicePorts;
+
+impl ServicePorts {
+    pub const REMAPPED_FILE: &'static str = ".wf2_ports.yml";
+
+    ///
+    /// Every `ports` list, keyed by service name, followed by
+    /// any saved by `--remap-ports` for the other services
+    ///
+    pub fn from_ctx(ctx: &Context) -> Result<PortsMap, failure::Error> {
+        let mut output = ServicePorts::explicit(ctx)?;
+        for (name, ports) in ServicePorts::read_remapped(&ServicePorts::remapped_path(ctx))? {
+            output.entry(name).or_insert(ports);
+        }
+        Ok(output)
+    }
+
+    pub fn remapped_path(ctx: &Context) -> PathBuf {
+        ctx.cwd.join(ServicePorts::REMAPPED_FILE)
+    }
+
+    ///
+    /// A missing file means nothing was remapped
+    ///
+    pub fn read_remapped(path: &Path) -> Result<PortsMap, failure::Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_yaml::from_str(&content)
+                .map_err(|_| ServicePortsError::InvalidRemapped(path.display().to_string()).into()),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    ///
+    /// Only the `ports` lists from `options.services`
+    ///
+    fn explicit(ctx: &Context) -> Result<PortsMap, failure::Error> {
+        let services = match ctx
+            .options
+            .as_ref()
+            .and_then(|opts| opts["services"].as_mapping())
+        {
+            Some(services) => services,
+            None => return Ok(BTreeMap::new()),
+        };
+
+        let mut output = BTreeMap::new();
+        for (name, opts) in services {
+            if let (Some(name), false) = (name.as_str(), opts["ports"].is_null()) {
+                let ports: Vec<String> = serde_yaml::from_value(opts["ports"].clone())
+                    .map_err(|_| ServicePortsError::Invalid(name.to_string()))?;
+                output.insert(name.to_string(), ports);
+            }
+        }
+        Ok(output)
+    }
+
+    ///
+    /// Replace the published ports of any services given in the context
+    ///
+    pub fn apply(
+        ctx: &Context,
+        services: Vec<DcService>,
+    ) -> Result<Vec<DcService>, failure::Error> {
+        let ports = ServicePorts::from_ctx(ctx)?;
+
+        // saved ports for services that are no longer in the recipe are just skipped
+        if let Some(unknown) = ServicePorts::explicit(ctx)?
+            .keys()
+            .find(|name| !services.iter().any(|service| &service.name == *name))
+        {
+            return Err(ServicePortsError::UnknownService(unknown.to_string()).into());
+        }
+
+        Ok(services
+            .into_iter()
+            .map(|mut service| {
+                if let Some(ports) = ports.get(&service.name) {
+                    service.ports = Some(ports.clone());
+                }
+                service
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_tasks::DcTasksTrait;
+    use crate::recipes::m2::M2Recipe;
+
+    #[test]
+    fn test_ports_errors() {
+        let cases = vec![
+            (
+                "mongo: { ports: [ \"27017:27017\" ] }",
+                "Cannot set ports for `mongo`, since it's not a service in this recipe",
+            ),
+            (
+                "db: { ports: 3307 }",
+                "Invalid ports for service `db`, expected a list like [ \"3307:3306\" ]",
+            ),
+        ];
+        for (services, expected) in cases {
+            let ctx_yaml = format!("recipe: M2\noptions:\n  services: {{ {} }}", services);
+            let ctx = Context::new_from_str(&ctx_yaml).expect("test");
+            let err = (M2Recipe).dc(&ctx).expect_err("test");
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_remapped_ports() -> Result<(), failure::Error> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(ServicePorts::REMAPPED_FILE),
+            "db: [ \"3307:3306\" ]\nelasticsearch: [ \"9201:9200\" ]\nmongo: [ \"27018:27017\" ]\n",
+        )?;
+        let ctx = Context::new_from_str(
+            "recipe: M2\noptions:\n  services: { elasticsearch: { ports: [ \"9300:9200\" ] } }",
+        )
+        .expect("test");
+        let ctx = Context {
+            cwd: dir.path().to_path_buf(),
+            ..ctx
+        };
+        let dc = (M2Recipe).dc(&ctx)?;
+        let services = dc.services.expect("test");
+        assert_eq!(
+            services.get("db").expect("test").ports,
+            Some(vec![String::from("3307:3306")])
+        );
+        assert_eq!(
+            services.get("elasticsearch").expect("test").ports,
+            Some(vec![String::from("9300:9200")])
+        );
+        Ok(())
+    }
+}
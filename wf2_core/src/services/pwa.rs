@@ -5,6 +5,7 @@ use crate::dc_image_build::DcImageBuild;
 use crate::dc_service_network::DcServiceNetwork;
 use crate::recipes::m2::dc_tasks::M2Volumes;
 use crate::services::Service;
+use crate::versions::node::NodeVersion;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
@@ -28,6 +29,16 @@ impl PwaService {
 /// These are the options that can be provided in the wf2 file
 /// under 'options'
 ///
+/// The image is built from `dockerfile` (`<src_dir>/Dockerfile` by default), with
+/// the `BUILD_COMMAND` & `NODE_VERSION` build args. The Node version comes from
+/// `node_version`, or an `.nvmrc` in `src_dir`, so the Dockerfile must declare it to use it:
+///
+/// ```dockerfile
+/// ARG NODE_VERSION
+/// FROM wearejh/node:${NODE_VERSION}-m2
+/// ARG BUILD_COMMAND
+/// ```
+///
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct PwaServiceOptions {
     pub src_dir: PathBuf,
@@ -47,16 +58,21 @@ impl Service<PwaServiceOptions> for PwaService {
             .build_command
             .clone()
             .unwrap_or_else(|| PwaService::DEFAULT_BUILD_COMMAND.to_string());
-        let build_arg = "BUILD_COMMAND".to_string();
+        let node_version = NodeVersion::from_dir(ctx, &opts.src_dir)
+            .map(|node_version| node_version.node)
+            .unwrap_or_default();
         let image_build = DcImageBuild {
             context: opts.src_dir.clone(),
             dockerfile: opts
                 .dockerfile
                 .clone()
                 .unwrap_or_else(|| opts.src_dir.clone().join("Dockerfile")),
-            args: vec![(build_arg, build_cmd)]
-                .into_iter()
-                .collect::<BTreeMap<String, String>>(),
+            args: vec![
+                (String::from("BUILD_COMMAND"), build_cmd),
+                (String::from("NODE_VERSION"), node_version.to_string()),
+            ]
+            .into_iter()
+            .collect::<BTreeMap<String, String>>(),
         };
         DcService::new(ctx.name(), Self::NAME, image_name)
             .set_init(true)
@@ -109,6 +125,7 @@ mod tests {
                 dockerfile: "/users/shane/pwa/Dockerfile"
                 args:
                     BUILD_COMMAND: "npm run build:debug"
+                    NODE_VERSION: "8"
             volumes:
               - "pwa-src:/home/node/app/packages/server/pwa"
             labels:
@@ -125,4 +142,19 @@ mod tests {
         let expected_dc: DcService = serde_yaml::from_str(expected).expect("test yaml");
         assert_eq!(actual_dc, expected_dc);
     }
+
+    #[test]
+    fn test_pwa_nvmrc_from_src_dir() {
+        let ctx = Context::new("..");
+        let opts = PwaServiceOptions {
+            src_dir: PathBuf::from("fixtures/nvmrc"),
+            ..PwaServiceOptions::default()
+        };
+        let args = (PwaService)
+            .dc_service(&ctx, &opts)
+            .build
+            .expect("test")
+            .args;
+        assert_eq!(args.get("NODE_VERSION"), Some(&String::from("18")));
+    }
 }
@@ -1,4 +1,5 @@
 use crate::context::Context;
+use crate::dc_healthcheck::DcHealthcheck;
 use crate::dc_service::DcService;
 use crate::services::traefik::TraefikService;
 use crate::services::Service;
@@ -10,6 +11,15 @@ impl RabbitMqService {
     pub const DOMAIN: &'static str = "queue.jh";
     pub const PORT_PUBLIC: u32 = 15672;
     pub const PORT_INTERNAL: u32 = 5672;
+
+    pub fn healthcheck() -> DcHealthcheck {
+        DcHealthcheck::cmd_shell("rabbitmqctl status")
+            .set_interval("10s")
+            .set_timeout("10s")
+            .set_retries(20)
+            .set_start_period("20s")
+            .finish()
+    }
 }
 
 impl fmt::Display for RabbitMqService {
@@ -32,10 +42,13 @@ impl Service for RabbitMqService {
                 format!("{port}:{port}", port = RabbitMqService::PORT_PUBLIC),
                 format!("{port}:{port}", port = RabbitMqService::PORT_INTERNAL),
             ])
-            .set_labels(TraefikService::host_entry_label(
-                RabbitMqService::DOMAIN,
-                RabbitMqService::PORT_PUBLIC,
+            .set_labels(TraefikService::labels(
+                ctx,
+                Self::NAME,
+                vec![RabbitMqService::DOMAIN.to_string()],
+                Some(RabbitMqService::PORT_PUBLIC),
             ))
+            .set_healthcheck(RabbitMqService::healthcheck())
             .finish()
     }
 }
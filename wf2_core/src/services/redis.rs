@@ -1,10 +1,21 @@
 use crate::context::Context;
+use crate::dc_healthcheck::DcHealthcheck;
 use crate::dc_service::DcService;
 
 use crate::services::Service;
 
 pub struct RedisService;
 
+impl RedisService {
+    pub fn healthcheck() -> DcHealthcheck {
+        DcHealthcheck::cmd_shell("redis-cli ping | grep PONG")
+            .set_interval("5s")
+            .set_timeout("3s")
+            .set_retries(10)
+            .finish()
+    }
+}
+
 impl Service for RedisService {
     const NAME: &'static str = "redis";
     const IMAGE: &'static str = "redis:3-alpine";
@@ -12,6 +23,7 @@ impl Service for RedisService {
     fn dc_service(&self, ctx: &Context, _: &()) -> DcService {
         DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
             .set_labels(vec![Self::TRAEFIK_DISABLE_LABEL.to_string()])
+            .set_healthcheck(RedisService::healthcheck())
             .finish()
     }
 }
@@ -1,17 +1,102 @@
+//!
+//! Traefik routes every domain to the correct container.
+//!
+//! Version 1.7 is used by default, but version 2 can be selected with:
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::services::traefik::{TraefikService, TraefikVersion};
+//! # use wf2_core::services::Service;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     traefik:
+//!       version: 2
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # assert_eq!(TraefikVersion::from_ctx(&ctx), TraefikVersion::V2);
+//! # assert_eq!((TraefikService).dc_service(&ctx, &()).image, "traefik:2.4");
+//! ```
+//!
+//! With version 2, HTTPS is served with a certificate signed by the
+//! local wf2 CA - see `wf2 certs install-path`. Version 1 uses its own default certificate.
+//!
+use crate::certs::ProjectCert;
 use crate::context::Context;
 use crate::dc_service::DcService;
 use crate::dc_service_network::DcServiceNetwork;
 
 use crate::file::File;
-use crate::recipes::m2::output_files::traefik::TraefikFile;
+use crate::proxy::SharedProxy;
+use crate::recipes::m2::output_files::traefik::{TraefikDynamicFile, TraefikFile};
 use crate::services::Service;
+use crate::util::path_buf_to_string;
 
 pub struct TraefikService;
 
 #[derive(Default)]
 pub struct TraefikServiceVars;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraefikVersion {
+    V1,
+    V2,
+}
+
+impl TraefikVersion {
+    ///
+    /// Read `options.services.traefik.version`, accepting `2`, `"2"` or `"v2"`.
+    /// Always v2 when using the shared proxy
+    ///
+    pub fn from_ctx(ctx: &Context) -> TraefikVersion {
+        if SharedProxy::enabled(ctx) {
+            return TraefikVersion::V2;
+        }
+        let version = ctx
+            .options
+            .as_ref()
+            .map(|opts| opts["services"]["traefik"]["version"].clone());
+
+        match version {
+            Some(serde_yaml::Value::Number(n)) if n.as_u64() == Some(2) => TraefikVersion::V2,
+            Some(serde_yaml::Value::String(ref s)) if s == "2" || s == "v2" => TraefikVersion::V2,
+            _ => TraefikVersion::V1,
+        }
+    }
+    pub fn image(self) -> &'static str {
+        match self {
+            TraefikVersion::V1 => TraefikService::IMAGE,
+            TraefikVersion::V2 => TraefikService::IMAGE_V2,
+        }
+    }
+}
+
 impl TraefikService {
+    pub const IMAGE_V2: &'static str = "traefik:2.4";
+
+    ///
+    /// The labels needed to route `domains` to a service, in whichever
+    /// format the configured version of Traefik expects
+    ///
+    pub fn labels(
+        ctx: &Context,
+        service: &str,
+        domains: Vec<String>,
+        port: Option<u32>,
+    ) -> Vec<String> {
+        match TraefikVersion::from_ctx(ctx) {
+            TraefikVersion::V1 => match port {
+                Some(port) => TraefikService::host_entry_label(domains.join(","), port),
+                None => TraefikService::host_only_entry_label(domains.join(",")),
+            },
+            TraefikVersion::V2 => TraefikService::router_labels(
+                &format!("{}-{}", ctx.name(), service),
+                &domains,
+                port,
+            ),
+        }
+    }
     pub fn host_entry_label(domain: impl Into<String>, port: impl Into<u32>) -> Vec<String> {
         vec![
             TraefikService::host(domain.into()),
@@ -22,12 +107,82 @@ impl TraefikService {
         vec![TraefikService::host(domain.into())]
     }
 
+    ///
+    /// The reverse of [`TraefikService::labels`] - every domain routed
+    /// by the given labels, in either v1 or v2 format
+    ///
+    pub fn hosts_from_labels(labels: &[String]) -> Vec<String> {
+        const V1_RULE: &str = "traefik.frontend.rule=Host:";
+        labels
+            .iter()
+            .filter_map(|label| {
+                if label.starts_with(V1_RULE) {
+                    Some(
+                        label
+                            .trim_start_matches(V1_RULE)
+                            .split(',')
+                            .map(|host| host.trim().to_string())
+                            .collect::<Vec<String>>(),
+                    )
+                } else if label.starts_with("traefik.http.routers.") && label.contains(".rule=") {
+                    Some(
+                        label
+                            .split("Host(`")
+                            .skip(1)
+                            .filter_map(|part| part.split('`').next())
+                            .map(String::from)
+                            .collect(),
+                    )
+                } else {
+                    None
+                }
+            })
+            .flatten()
+            .filter(|host| !host.is_empty())
+            .collect()
+    }
+
     fn host(domain: String) -> String {
         format!("traefik.frontend.rule=Host:{}", domain)
     }
     fn port(port: u32) -> String {
         format!("traefik.port={}", port)
     }
+
+    ///
+    /// Traefik v2 labels, one router + service per container
+    ///
+    pub fn router_labels(router: &str, domains: &[String], port: Option<u32>) -> Vec<String> {
+        let name = router
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+        let rule = domains
+            .iter()
+            .map(|domain| format!("Host(`{}`)", domain))
+            .collect::<Vec<String>>()
+            .join(" || ");
+
+        let mut labels = vec![
+            String::from("traefik.enable=true"),
+            format!("traefik.http.routers.{}.rule={}", name, rule),
+            format!("traefik.http.routers.{}.entrypoints=websecure", name),
+            format!("traefik.http.routers.{}.tls=true", name),
+        ];
+        if let Some(port) = port {
+            labels.push(format!(
+                "traefik.http.services.{}.loadbalancer.server.port={}",
+                name, port
+            ));
+        }
+        labels
+    }
 }
 
 impl Service for TraefikService {
@@ -35,24 +190,46 @@ impl Service for TraefikService {
     const IMAGE: &'static str = "traefik:1.7";
 
     fn dc_service(&self, ctx: &Context, _vars: &()) -> DcService {
-        DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
-            .set_volumes(vec![
-                "/var/run/docker.sock:/var/run/docker.sock".to_string(),
-                format!(
-                    "{}:/etc/traefik/traefik.toml",
-                    TraefikFile::from_ctx(&ctx)
-                        .expect("cannot fail to get traefik file")
-                        .file_path_string(),
-                ),
-            ])
+        let version = TraefikVersion::from_ctx(ctx);
+        let traefik_file = TraefikFile::from_ctx(ctx).expect("cannot fail to get traefik file");
+
+        let mut volumes = vec![
+            "/var/run/docker.sock:/var/run/docker.sock".to_string(),
+            format!(
+                "{}:{}",
+                traefik_file.file_path_string(),
+                TraefikFile::CONTAINER_PATH
+            ),
+        ];
+
+        if version == TraefikVersion::V2 {
+            volumes.push(format!(
+                "{}:{}",
+                path_buf_to_string(&ProjectCert::dir_from_ctx(ctx)),
+                ProjectCert::CONTAINER_DIR
+            ));
+            let dynamic_file =
+                TraefikDynamicFile::from_ctx(ctx).expect("cannot fail to get traefik file");
+            volumes.push(format!(
+                "{}:{}",
+                dynamic_file.file_path_string(),
+                TraefikDynamicFile::CONTAINER_PATH
+            ));
+        }
+
+        let mut service = DcService::new(ctx.name(), Self::NAME, version.image());
+        service
+            .set_volumes(volumes)
             .set_ports(vec!["80:80", "443:443", "8080:8080"])
-            .set_command("--api --docker")
             .set_labels(vec![Self::TRAEFIK_DISABLE_LABEL])
-            .set_network(
-                "default",
-                DcServiceNetwork::with_aliases(ctx.domains.clone()),
-            )
-            .finish()
+            .set_network("default", DcServiceNetwork::with_aliases(ctx.domains()));
+
+        // v2 reads everything from the static config file instead
+        if version == TraefikVersion::V1 {
+            service.set_command("--api --docker");
+        }
+
+        service.finish()
     }
 }
 
@@ -101,4 +278,58 @@ mod test {
         let labels = TraefikService::host_only_entry_label("mail.jh");
         assert_eq!(labels, vec!["traefik.frontend.rule=Host:mail.jh"])
     }
+    #[test]
+    fn test_hosts_from_labels() {
+        let v1 = TraefikService::host_entry_label("mail.jh,other.jh", 8080_u32);
+        assert_eq!(
+            TraefikService::hosts_from_labels(&v1),
+            vec!["mail.jh", "other.jh"]
+        );
+        let ctx = Context::new_from_str("options: { services: { traefik: { version: 2 } } }")
+            .expect("test");
+        let v2 = TraefikService::labels(
+            &ctx,
+            "mail",
+            vec![String::from("mail.jh"), String::from("other.jh")],
+            Some(8025),
+        );
+        assert_eq!(
+            TraefikService::hosts_from_labels(&v2),
+            vec!["mail.jh", "other.jh"]
+        );
+    }
+    #[test]
+    fn test_v2() {
+        let ctx = Context::new_from_str(
+            "recipe: M2\ndomains: [acme.m2, acme.pwa]\noptions: { services: { traefik: { version: 2 } } }",
+        )
+        .expect("test");
+        let labels = TraefikService::labels(
+            &ctx,
+            "varnish",
+            vec![String::from("acme.m2"), String::from("acme.pwa")],
+            Some(80),
+        );
+        assert_eq!(
+            labels,
+            vec![
+                "traefik.enable=true",
+                "traefik.http.routers.wf2-default-varnish.rule=Host(`acme.m2`) || Host(`acme.pwa`)",
+                "traefik.http.routers.wf2-default-varnish.entrypoints=websecure",
+                "traefik.http.routers.wf2-default-varnish.tls=true",
+                "traefik.http.services.wf2-default-varnish.loadbalancer.server.port=80",
+            ]
+        );
+
+        let actual = (TraefikService).dc_service(&ctx, &());
+        assert_eq!(actual.image, "traefik:2.4");
+        assert!(actual.command.is_none());
+        let volumes = actual.volumes.expect("test");
+        assert!(volumes
+            .iter()
+            .any(|volume| volume.ends_with("traefik/dynamic.toml:/etc/traefik/dynamic.toml")));
+        assert!(volumes
+            .iter()
+            .any(|volume| volume.ends_with(":/etc/traefik/certs")));
+    }
 }
@@ -1,6 +1,35 @@
+//!
+//! Varnish, in front of nginx.
+//!
+//! It runs in pass-through mode until `wf2 varnish enable` loads the project's
+//! VCL. By default that's Magento's own VCL, but you can provide your own:
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # use wf2_core::test::Test;
+//! # let input = r#"
+//! recipe: M2
+//! options:
+//!   services:
+//!     varnish:
+//!       vcl: ./varnish/project.vcl
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx).expect("valid dc");
+//! # let varnish = dc.services.expect("services").get("varnish").cloned().expect("varnish");
+//! # assert!(varnish.volumes.expect("volumes")[0].ends_with("/varnish:/etc/varnish/wf2"));
+//! ```
+//!
+//! The file is copied into the project's `.wf2_m2_*` folder on every `wf2 up` and
+//! `wf2 varnish enable`, so after changing it just run `wf2 varnish enable` again.
+//!
 use crate::context::Context;
 use crate::dc_service::DcService;
+use crate::recipes::m2::multi_store::Stores;
 
+use crate::recipes::m2::output_files::varnish_vcl::VarnishVcl;
 use crate::recipes::m2::services::php::PhpService;
 use crate::recipes::m2::services::M2RecipeOptions;
 use crate::services::nginx::NginxService;
@@ -15,7 +44,7 @@ impl Service for VarnishService {
     const IMAGE: &'static str = "wearejh/varnish:latest";
 
     fn dc_service(&self, ctx: &Context, _: &()) -> DcService {
-        let mut base_domains = ctx.domains();
+        let mut base_domains = Stores::all_domains(ctx);
         let mut depends_on = vec![NginxService::NAME, PhpService::NAME];
 
         if let Some(opts) = M2RecipeOptions::get_pwa_options(ctx) {
@@ -24,10 +53,9 @@ impl Service for VarnishService {
         }
 
         DcService::new(ctx.name(), Self::NAME, Self::IMAGE)
+            .set_volumes(vec![VarnishVcl::volume(ctx)])
             .set_depends_on(depends_on)
-            .set_labels(TraefikService::host_only_entry_label(
-                base_domains.join(","),
-            ))
+            .set_labels(TraefikService::labels(ctx, Self::NAME, base_domains, None))
             .finish()
     }
 }
@@ -46,6 +74,8 @@ mod tests {
             name: varnish
             container_name: wf2__wf2_default__varnish
             image: "wearejh/varnish:latest"
+            volumes:
+              - "./.wf2_default/varnish:/etc/varnish/wf2"
             labels:
               - "traefik.frontend.rule=Host:local.m2"
             depends_on:
@@ -68,6 +98,8 @@ mod tests {
             name: varnish
             container_name: wf2__wf2_default__varnish
             image: "wearejh/varnish:latest"
+            volumes:
+              - "./.wf2_m2_wf2_default/varnish:/etc/varnish/wf2"
             labels:
               - "traefik.frontend.rule=Host:example.m2,example.pwa,test.ngrok.io"
             depends_on:
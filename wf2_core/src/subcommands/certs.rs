@@ -0,0 +1,90 @@
+//!
+//! Manage the local certificate authority used for HTTPS
+//!
+//! Every project using Traefik v2 gets a certificate signed by a CA that wf2 creates once,
+//! in `~/.wf2/ca`. Trust that CA and your browser will accept every project's
+//! domains without warnings.
+//!
+//! # Example: print where the CA lives
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 certs install-path
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd).with_recipe(RecipeKinds::M2_NAME).tasks();
+//! # assert_eq!(tasks.len(), 2);
+//! ```
+//!
+//! ## Trusting the CA
+//!
+//! - macOS: `sudo security add-trusted-cert -d -r trustRoot -k /Library/Keychains/System.keychain <path>`
+//! - Linux (Debian/Ubuntu): copy it to `/usr/local/share/ca-certificates/wf2.crt`,
+//!   then run `sudo update-ca-certificates`
+//! - Firefox uses its own store: Preferences -> Certificates -> Import
+//!
+use crate::certs::LocalCa;
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::task::Task;
+use clap::{App, ArgMatches, SubCommand};
+use futures::future::lazy;
+
+#[doc_link::doc_link("/subcommands/certs")]
+pub struct CertsCmd;
+
+impl CertsCmd {
+    const NAME: &'static str = "certs";
+    const ABOUT: &'static str = "Manage the local CA used for HTTPS certificates";
+
+    const INSTALL_PATH: &'static str = "install-path";
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for CertsCmd {
+    fn name(&self) -> String {
+        String::from(CertsCmd::NAME)
+    }
+    fn exec(&self, matches: Option<&ArgMatches>, _ctx: &Context) -> Option<Vec<Task>> {
+        match matches.and_then(|m| m.subcommand_name()) {
+            Some(CertsCmd::INSTALL_PATH) => Some(install_path()),
+            _ => Some(vec![Task::notify_error("missing `install-path`")]),
+        }
+    }
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(CertsCmd::NAME)
+            .about(CertsCmd::ABOUT)
+            .after_help(CertsCmd::DOC_LINK)
+            .subcommand(
+                SubCommand::with_name(CertsCmd::INSTALL_PATH)
+                    .about("Print the path of the CA certificate that needs to be trusted"),
+            )]
+    }
+}
+
+///
+/// Ensure the CA exists, then print where it is + how to trust it
+///
+pub fn install_path() -> Vec<Task> {
+    let ca = match LocalCa::from_home() {
+        Ok(ca) => ca,
+        Err(e) => return Task::task_err_vec(e),
+    };
+    let path = ca.cert_path().display().to_string();
+    vec![
+        Task::Exec {
+            description: Some(String::from("create the local CA, if it's missing")),
+            exec: Box::new(lazy(move || ca.ensure().map(|_| ()))),
+        },
+        Task::notify(format!(
+            r#"{path}
+
+Trust it once, and every project's certificate will be accepted:
+
+  macOS:   sudo security add-trusted-cert -d -r trustRoot -k /Library/Keychains/System.keychain {path}
+  Linux:   sudo cp {path} /usr/local/share/ca-certificates/wf2.crt && sudo update-ca-certificates
+  Firefox: Preferences -> Certificates -> Import"#,
+            path = path
+        )),
+    ]
+}
@@ -20,32 +20,31 @@
 //!
 use crate::commands::CliCommand;
 use crate::context::Context;
-use crate::recipes::m2::services::db::DbService;
+use crate::recipes::app_services::DbCredentials;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 
-use crate::dc_service::DcService;
-use crate::services::Service;
 use clap::{App, ArgMatches};
 
-#[doc_link::doc_link("/recipes/m2/subcommands/db_dump")]
-pub struct M2DbDump;
+#[doc_link::doc_link("/subcommands/db_dump")]
+pub struct DbDump;
 
-impl M2DbDump {
+impl DbDump {
     const NAME: &'static str = "db-dump";
     const ABOUT: &'static str = "Dump the current database to dump.sql";
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2DbDump {
+impl<'a, 'b> CliCommand<'a, 'b> for DbDump {
     fn name(&self) -> String {
-        String::from(M2DbDump::NAME)
+        String::from(DbDump::NAME)
     }
     fn exec(&self, _matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         Some(from_ctx(&ctx))
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        let cmd = App::new(M2DbDump::NAME)
-            .about(M2DbDump::ABOUT)
-            .after_help(M2DbDump::DOC_LINK);
+        let cmd = App::new(DbDump::NAME)
+            .about(DbDump::ABOUT)
+            .after_help(DbDump::DOC_LINK);
         vec![cmd]
     }
 }
@@ -54,7 +53,8 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2DbDump {
 /// Create the tasks from a ctx
 ///
 fn from_ctx(ctx: &Context) -> Vec<Task> {
-    DbService::from_ctx(&ctx)
+    RecipeKinds::from_ctx(&ctx)
+        .db_service(&ctx)
         .map(db_dump)
         .unwrap_or_else(Task::task_err_vec)
 }
@@ -63,13 +63,14 @@ fn from_ctx(ctx: &Context) -> Vec<Task> {
 /// Dumps the Database to `dump.sql` in the project root. The filename
 /// is not configurable.
 ///
-pub fn db_dump(service: DcService) -> Vec<Task> {
+pub fn db_dump(db: DbCredentials) -> Vec<Task> {
     let db_dump_command = format!(
-        r#"docker exec -i {container_name} mysqldump -u{user} -p{pass} {db} > dump.sql"#,
-        container_name = service.container_name,
-        user = DbService::DB_USER,
-        pass = DbService::DB_PASS,
-        db = DbService::DB_NAME,
+        r#"docker exec -i {container_name} {dump_client} -u{user} -p{pass} {db} > dump.sql"#,
+        container_name = db.service.container_name,
+        dump_client = db.dump_client,
+        user = db.user,
+        pass = db.pass,
+        db = db.db,
     );
     vec![
         Task::simple_command(db_dump_command),
@@ -83,7 +84,10 @@ mod test {
 
     #[test]
     fn test_db_dump() {
-        let ctx = Context::new("/users/shane/acme");
+        let ctx = Context {
+            recipe: Some(RecipeKinds::M2),
+            ..Context::new("/users/shane/acme")
+        };
         let ts = from_ctx(&ctx);
         let t1 = ts.get(0).expect("command");
         if let Task::SimpleCommand { command, .. } = t1 {
@@ -97,3 +101,34 @@ mod test {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::cli_input::CLIInput;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::test::Test;
+
+    #[test]
+    fn test_db_dump_wp() {
+        let cmds = Test::from_cmd("wf2 db-dump")
+            .with_recipe(RecipeKinds::WP_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .commands();
+        assert_eq!(
+            cmds,
+            vec!["docker exec -i wf2__acme__db mysqldump -udocker -pdocker docker > dump.sql"]
+        );
+    }
+
+    #[test]
+    fn test_db_dump_drupal() {
+        let cmds = Test::from_cmd("wf2 db-dump")
+            .with_recipe(RecipeKinds::DRUPAL_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .commands();
+        assert_eq!(
+            cmds,
+            vec!["docker exec -i wf2__acme__db mysqldump -udocker -pdocker docker > dump.sql"]
+        );
+    }
+}
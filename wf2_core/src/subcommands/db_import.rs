@@ -56,19 +56,18 @@
 //! ```
 use crate::commands::CliCommand;
 use crate::context::Context;
-use crate::dc_service::DcService;
-use crate::recipes::m2::services::db::DbService;
-use crate::services::Service;
+use crate::recipes::app_services::DbCredentials;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 use crate::util::path_buf_to_string;
 use clap::{App, ArgMatches};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/db_import")]
-pub struct M2DbImport;
+#[doc_link::doc_link("/subcommands/db_import")]
+pub struct DbImport;
 
-impl M2DbImport {
+impl DbImport {
     const NAME: &'static str = "db-import";
     const ABOUT: &'static str = "Import a DB file";
 }
@@ -79,19 +78,19 @@ struct Opts {
     file: PathBuf,
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2DbImport {
+impl<'a, 'b> CliCommand<'a, 'b> for DbImport {
     fn name(&self) -> String {
-        String::from(M2DbImport::NAME)
+        String::from(DbImport::NAME)
     }
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         let opts: Opts = matches.map(Opts::from_clap).expect("guarded by clap");
         Some(from_ctx(&ctx, opts.file))
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        let cmd = App::new(M2DbImport::NAME)
-            .about(M2DbImport::ABOUT)
+        let cmd = App::new(DbImport::NAME)
+            .about(DbImport::ABOUT)
             .arg_from_usage("<file> 'db file to import'")
-            .after_help(M2DbImport::DOC_LINK);
+            .after_help(DbImport::DOC_LINK);
         vec![cmd]
     }
 }
@@ -100,8 +99,9 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2DbImport {
 /// Create the tasks from a ctx
 ///
 fn from_ctx(ctx: &Context, file: PathBuf) -> Vec<Task> {
-    DbService::from_ctx(&ctx)
-        .map(|service| db_import(ctx.pv.is_some(), service, file))
+    RecipeKinds::from_ctx(&ctx)
+        .db_service(&ctx)
+        .map(|db| db_import(ctx.pv.is_some(), db, file))
         .unwrap_or_else(Task::task_err_vec)
 }
 
@@ -110,25 +110,27 @@ fn from_ctx(ctx: &Context, file: PathBuf) -> Vec<Task> {
 ///
 /// If you have the `pv` package installed, it will be used to provide progress information.
 ///
-pub fn db_import(has_pv: bool, service: DcService, path: impl Into<PathBuf>) -> Vec<Task> {
+pub fn db_import(has_pv: bool, db: DbCredentials, path: impl Into<PathBuf>) -> Vec<Task> {
     let path = path.into();
     let db_import_command = if has_pv {
         format!(
-            r#"pv -f {file} | docker exec -i {container} mysql -f -u{user} -p{pass} -D {db}"#,
+            r#"pv -f {file} | docker exec -i {container} {client} -f -u{user} -p{pass} -D {db}"#,
             file = path_buf_to_string(&path),
-            container = service.container_name,
-            user = DbService::DB_USER,
-            pass = DbService::DB_PASS,
-            db = DbService::DB_NAME,
+            container = db.service.container_name,
+            client = db.client,
+            user = db.user,
+            pass = db.pass,
+            db = db.db,
         )
     } else {
         format!(
-            r#"docker exec -i {container} mysql -f -u{user} -p{pass} {db} < {file}"#,
+            r#"docker exec -i {container} {client} -f -u{user} -p{pass} {db} < {file}"#,
             file = path_buf_to_string(&path),
-            container = service.container_name,
-            user = DbService::DB_USER,
-            pass = DbService::DB_PASS,
-            db = DbService::DB_NAME,
+            container = db.service.container_name,
+            client = db.client,
+            user = db.user,
+            pass = db.pass,
+            db = db.db,
         )
     };
     vec![
@@ -136,3 +138,34 @@ pub fn db_import(has_pv: bool, service: DcService, path: impl Into<PathBuf>) ->
         Task::simple_command(db_import_command),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::cli_input::CLIInput;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::test::Test;
+
+    #[test]
+    fn test_db_import_wp() {
+        let cmds = Test::from_cmd("wf2 db-import dump.sql")
+            .with_recipe(RecipeKinds::WP_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .commands();
+        assert_eq!(
+            cmds,
+            vec!["docker exec -i wf2__acme__db mysql -f -udocker -pdocker docker < dump.sql"]
+        );
+    }
+
+    #[test]
+    fn test_db_import_drupal() {
+        let cmds = Test::from_cmd("wf2 db-import dump.sql")
+            .with_recipe(RecipeKinds::DRUPAL_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .commands();
+        assert_eq!(
+            cmds,
+            vec!["docker exec -i wf2__acme__db mysql -f -udocker -pdocker docker < dump.sql"]
+        );
+    }
+}
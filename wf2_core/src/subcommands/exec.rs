@@ -57,15 +57,16 @@
 //! ```
 use crate::commands::CliCommand;
 use crate::context::Context;
-use crate::recipes::m2::services::php::PhpService;
+use crate::dc_service::DcService;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 use clap::{App, ArgMatches};
 use structopt::StructOpt;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/exec")]
-pub struct M2Exec;
+#[doc_link::doc_link("/subcommands/exec")]
+pub struct Exec;
 
-impl M2Exec {
+impl Exec {
     const NAME: &'static str = "exec";
     const ABOUT: &'static str = "Execute commands in the main container";
 }
@@ -76,9 +77,9 @@ struct Opts {
     root: bool,
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2Exec {
+impl<'a, 'b> CliCommand<'a, 'b> for Exec {
     fn name(&self) -> String {
-        String::from(M2Exec::NAME)
+        String::from(Exec::NAME)
     }
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         let matches = matches.expect("guarded by Clap");
@@ -88,9 +89,9 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2Exec {
         Some(exec(ctx, trailing, user))
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        vec![App::new(M2Exec::NAME)
-            .about(M2Exec::ABOUT)
-            .after_help(M2Exec::DOC_LINK)
+        vec![App::new(Exec::NAME)
+            .about(Exec::ABOUT)
+            .after_help(Exec::DOC_LINK)
             .args_from_usage(
                 "-r --root 'Execute commands as root'
                                   [cmd]... 'Trailing args'",
@@ -114,21 +115,65 @@ fn get_trailing(sub_matches: &ArgMatches) -> Vec<String> {
 }
 
 ///
-/// Alias for `docker exec` inside the PHP Container.
+/// Alias for `docker exec` inside the main container of the current recipe.
 ///
 /// Note: if the command you're running requires flags like `-h`, then you
 /// need to place `--` directly after `exec` (see below)
 ///
 pub fn exec(ctx: &Context, trailing: Vec<String>, user: &str) -> Vec<Task> {
-    PhpService::select(&ctx).map(|service| {
-        let exec_command = format!(
-            r#"docker exec -it -u {user} -e COLUMNS="{width}" -e LINES="{height}" {container_name} {trailing_args}"#,
-            user = user,
-            width = ctx.term.width,
-            height = ctx.term.height,
-            container_name = service.container_name,
-            trailing_args = trailing.join(" ")
+    RecipeKinds::from_ctx(&ctx)
+        .app_service(&ctx)
+        .map(|service| exec_service(ctx, service, trailing, user))
+        .unwrap_or_else(Task::task_err_vec)
+}
+
+///
+/// `docker exec` inside any service, sized to the current terminal
+///
+pub fn exec_service(
+    ctx: &Context,
+    service: DcService,
+    trailing: Vec<String>,
+    user: &str,
+) -> Vec<Task> {
+    let exec_command = format!(
+        r#"docker exec -it -u {user} -e COLUMNS="{width}" -e LINES="{height}" {container_name} {trailing_args}"#,
+        user = user,
+        width = ctx.term.width,
+        height = ctx.term.height,
+        container_name = service.container_name,
+        trailing_args = trailing.join(" ")
+    );
+    vec![Task::simple_command(exec_command)]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::cli_input::CLIInput;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::test::Test;
+
+    #[test]
+    fn test_exec_wp() {
+        let cmds = Test::from_cmd("wf2 exec ls")
+            .with_recipe(RecipeKinds::WP_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .commands();
+        assert_eq!(
+            cmds,
+            vec![r#"docker exec -it -u www-data -e COLUMNS="80" -e LINES="30" wf2__acme__php ls"#]
         );
-        vec![Task::simple_command(exec_command)]
-    }).unwrap_or_else(Task::task_err_vec)
+    }
+
+    #[test]
+    fn test_exec_drupal() {
+        let cmds = Test::from_cmd("wf2 exec -r ls")
+            .with_recipe(RecipeKinds::DRUPAL_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .commands();
+        assert_eq!(
+            cmds,
+            vec![r#"docker exec -it -u root -e COLUMNS="80" -e LINES="30" wf2__acme__php ls"#]
+        );
+    }
 }
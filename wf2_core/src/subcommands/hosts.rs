@@ -0,0 +1,130 @@
+//!
+//! Manage the `/etc/hosts` entries for the current project
+//!
+//! The domains are taken from `domains` in your `wf2.yml`, plus any
+//! that the recipe's services are routed on (such as `mail.jh`)
+//!
+//! # Example: check for missing entries
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 hosts check
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd).with_recipe(RecipeKinds::M2_NAME).tasks();
+//! # assert_eq!(tasks.len(), 1);
+//! ```
+//!
+//! # Example: add or remove this project's entries
+//!
+//! Since `/etc/hosts` is owned by root, these need running with `sudo`
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 hosts apply
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd).with_recipe(RecipeKinds::M2_NAME).tasks();
+//! # assert_eq!(tasks.len(), 1);
+//! ```
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 hosts remove
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd).with_recipe(RecipeKinds::M2_NAME).tasks();
+//! # assert_eq!(tasks.len(), 1);
+//! ```
+//!
+//! A different hosts file can be given with `--path`, which is useful
+//! for testing or for tools that manage their own hosts file.
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::recipes::recipe_kinds::RecipeKinds;
+//! # let cmd = r#"
+//! wf2 hosts apply --path ./hosts
+//! # "#;
+//! # let tasks = Test::from_cmd(cmd).with_recipe(RecipeKinds::M2_NAME).tasks();
+//! # assert_eq!(tasks.len(), 1);
+//! ```
+//!
+use crate::commands::CliCommand;
+use crate::context::Context;
+use crate::hosts::HostEntries;
+use crate::task::Task;
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+#[doc_link::doc_link("/subcommands/hosts")]
+pub struct HostsCmd;
+
+impl HostsCmd {
+    const NAME: &'static str = "hosts";
+    const ABOUT: &'static str = "Check, add or remove the /etc/hosts entries for this project";
+
+    const CHECK: &'static str = "check";
+    const APPLY: &'static str = "apply";
+    const REMOVE: &'static str = "remove";
+    const PATH: &'static str = "path";
+
+    fn path_arg<'a, 'b>() -> Arg<'a, 'b> {
+        Arg::with_name(HostsCmd::PATH)
+            .long(HostsCmd::PATH)
+            .takes_value(true)
+            .default_value(HostEntries::DEFAULT_PATH)
+            .help("The hosts file to use")
+    }
+}
+
+impl<'a, 'b> CliCommand<'a, 'b> for HostsCmd {
+    fn name(&self) -> String {
+        String::from(HostsCmd::NAME)
+    }
+    fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
+        let (name, sub_matches) = match matches.map(|m| m.subcommand()) {
+            Some((name, Some(sub_matches))) => (name, sub_matches),
+            _ => {
+                return Some(vec![Task::notify_error(
+                    "missing `check`, `apply` or `remove`",
+                )])
+            }
+        };
+
+        let path = sub_matches
+            .value_of(HostsCmd::PATH)
+            .unwrap_or(HostEntries::DEFAULT_PATH);
+
+        let entries = match HostEntries::from_ctx(ctx) {
+            Ok(entries) => entries,
+            Err(e) => return Some(Task::task_err_vec(e)),
+        };
+
+        let task = match name {
+            HostsCmd::CHECK => entries.check_task(path),
+            HostsCmd::APPLY => entries.apply_task(path),
+            _ => entries.remove_task(path),
+        };
+
+        Some(vec![task])
+    }
+    fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
+        vec![App::new(HostsCmd::NAME)
+            .about(HostsCmd::ABOUT)
+            .after_help(HostsCmd::DOC_LINK)
+            .subcommands(vec![
+                SubCommand::with_name(HostsCmd::CHECK)
+                    .about("List any entries missing from the hosts file")
+                    .arg(HostsCmd::path_arg()),
+                SubCommand::with_name(HostsCmd::APPLY)
+                    .about("Add (or update) this project's entries")
+                    .arg(HostsCmd::path_arg()),
+                SubCommand::with_name(HostsCmd::REMOVE)
+                    .about("Remove this project's entries")
+                    .arg(HostsCmd::path_arg()),
+            ])]
+    }
+}
@@ -27,43 +27,56 @@
 //!    php              wearejh/php:7.3-m2
 //!    mail             mailhog/mailhog
 //! ```
+//!
+//! For M2, where the Node version came from is shown too, since it can be
+//! set with `node_version` or picked up from an `.nvmrc` file
+//!
+//! ```txt
+//! node version: 16 (from /users/shane/acme/.nvmrc)
+//! ```
 use crate::commands::CliCommand;
 use crate::context::Context;
 use crate::task::Task;
 use clap::{App, ArgMatches};
 
-use crate::dc_tasks::DcTasksTrait;
-use crate::recipes::m2::M2Recipe;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::util::two_col;
+use crate::versions::node::NodeVersion;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/list_images")]
-pub struct M2ListImages;
+#[doc_link::doc_link("/subcommands/list_images")]
+pub struct ListImages;
 
-impl M2ListImages {
+impl ListImages {
     const NAME: &'static str = "list-images";
     const ABOUT: &'static str = "List the images used in the current recipe";
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2ListImages {
+impl<'a, 'b> CliCommand<'a, 'b> for ListImages {
     fn name(&self) -> String {
-        String::from(M2ListImages::NAME)
+        String::from(ListImages::NAME)
     }
     fn exec(&self, _matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         Some(list_images(&ctx))
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        vec![App::new(M2ListImages::NAME)
-            .about(M2ListImages::ABOUT)
-            .after_help(M2ListImages::DOC_LINK)]
+        vec![App::new(ListImages::NAME)
+            .about(ListImages::ABOUT)
+            .after_help(ListImages::DOC_LINK)]
     }
 }
 
 pub fn list_images(ctx: &Context) -> Vec<Task> {
-    let dc = (M2Recipe).dc(&ctx);
+    let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx);
     match dc {
         Ok(dc) => {
             let pairs = dc.service_img();
-            vec![Task::notify(two_col(pairs))]
+            let mut tasks = vec![Task::notify(two_col(pairs))];
+            if ctx.recipe == Some(RecipeKinds::M2) {
+                if let Ok(node_version) = NodeVersion::from_ctx(ctx) {
+                    tasks.push(Task::notify(format!("node version: {}", node_version)));
+                }
+            }
+            tasks
         }
         Err(e) => Task::task_err_vec(e),
     }
@@ -2,10 +2,20 @@ use crate::cmd::PassThruCmd;
 use crate::context::Context;
 use crate::task::Task;
 
+pub mod certs;
+pub mod db_dump;
+pub mod db_import;
 pub mod dc;
 pub mod down;
+pub mod exec;
+pub mod hosts;
+pub mod list_images;
 pub mod pm2;
+pub mod pull;
+pub mod push;
+pub mod sql;
 pub mod stop;
+pub mod update_images;
 
 pub trait PassThru {
     fn resolve(&self, _ctx: &Context, _cmd: &PassThruCmd) -> Option<Vec<Task>> {
@@ -47,8 +47,7 @@
 use crate::commands::CliCommand;
 use crate::context::Context;
 
-use crate::recipes::m2::services::php::PhpService;
-use crate::recipes::m2::services::M2_ROOT;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 use crate::util::path_buf_to_string;
 use clap::{App, ArgMatches};
@@ -57,10 +56,10 @@ use structopt::StructOpt;
 
 use crate::dc_service::DcService;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/push")]
-pub struct M2Pull;
+#[doc_link::doc_link("/subcommands/pull")]
+pub struct Pull;
 
-impl M2Pull {
+impl Pull {
     const NAME: &'static str = "pull";
     const ABOUT: &'static str = "Pull files or folders from the main container to the host";
 }
@@ -71,31 +70,37 @@ struct Opts {
     paths: Vec<String>,
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2Pull {
+impl<'a, 'b> CliCommand<'a, 'b> for Pull {
     fn name(&self) -> String {
-        String::from(M2Pull::NAME)
+        String::from(Pull::NAME)
     }
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         let opts: Opts = matches.map(Opts::from_clap).expect("guarded by Clap");
-        match PhpService::select(&ctx) {
-            Ok(service) => Some(pull(ctx, service, opts.paths)),
+        let recipe = RecipeKinds::from_ctx(&ctx);
+        match recipe.app_service(&ctx) {
+            Ok(service) => Some(pull(ctx, service, recipe.app_root(), opts.paths)),
             Err(e) => Some(Task::task_err_vec(e)),
         }
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        vec![App::new(M2Pull::NAME)
-            .about(M2Pull::ABOUT)
+        vec![App::new(Pull::NAME)
+            .about(Pull::ABOUT)
             .arg_from_usage("<paths>... 'files or paths to pull'")
-            .after_help(M2Pull::DOC_LINK)]
+            .after_help(Pull::DOC_LINK)]
     }
 }
 
 ///
 /// Pull files out of the docker container
 ///
-pub fn pull(ctx: &Context, service: DcService, trailing: Vec<String>) -> Vec<Task> {
+pub fn pull(
+    ctx: &Context,
+    service: DcService,
+    root: impl Into<PathBuf>,
+    trailing: Vec<String>,
+) -> Vec<Task> {
     let container_name = service.container_name;
-    let prefix = PathBuf::from(M2_ROOT);
+    let prefix = root.into();
 
     let cp_command = |file: &String| {
         format!(
@@ -274,6 +279,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pull_wp() {
+        let (commands, _) = Test::from_cmd("wf2 pull wp-content/uploads")
+            .with_recipe(RecipeKinds::WP_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .file_ops_commands();
+        assert_eq!(
+            commands,
+            vec![
+                "docker exec wf2__acme__php test -e /var/www/wp-content/uploads",
+                "docker cp wf2__acme__php:/var/www/wp-content/uploads /users/acme/wp-content",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pull_drupal() {
+        let (commands, _) = Test::from_cmd("wf2 pull web/sites/default/files")
+            .with_recipe(RecipeKinds::DRUPAL_NAME)
+            .with_cli_input(CLIInput::from_cwd("/users/acme"))
+            .file_ops_commands();
+        assert_eq!(
+            commands,
+            vec![
+                "docker exec wf2__acme__php test -e /var/www/web/sites/default/files",
+                "docker cp wf2__acme__php:/var/www/web/sites/default/files /users/acme/web/sites/default",
+            ]
+        );
+    }
+
     fn test_pull(
         cmd: impl Into<String>,
         cwd: impl Into<PathBuf>,
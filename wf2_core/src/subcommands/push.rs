@@ -1,5 +1,5 @@
 //!
-//! Push files into the main PHP container
+//! Push files into the main container
 //!
 //! Use this command when you've edited files in vendor, or
 //! any other folder that's not currently being 'synced'
@@ -37,18 +37,17 @@
 //!
 use crate::commands::CliCommand;
 use crate::context::Context;
-use crate::recipes::m2::services::php::PhpService;
-use crate::recipes::m2::services::M2_ROOT;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 use crate::util::path_buf_to_string;
 use clap::{App, ArgMatches};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/push")]
-pub struct M2Push;
+#[doc_link::doc_link("/subcommands/push")]
+pub struct Push;
 
-impl M2Push {
+impl Push {
     const NAME: &'static str = "push";
     const ABOUT: &'static str = "Push files or folders (use -f to force)";
 }
@@ -62,27 +61,39 @@ struct Opts {
     force: bool,
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2Push {
+impl<'a, 'b> CliCommand<'a, 'b> for Push {
     fn name(&self) -> String {
-        String::from(M2Push::NAME)
+        String::from(Push::NAME)
     }
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         let opts: Opts = matches.map(Opts::from_clap).expect("guarded by Clap");
-        PhpService::select(&ctx)
-            .map(|service| Some(push(ctx, service.container_name, opts.paths, opts.force)))
+        let recipe = RecipeKinds::from_ctx(&ctx);
+        let synced_paths = recipe.synced_paths();
+        recipe
+            .app_service(&ctx)
+            .map(|service| {
+                Some(push(
+                    ctx,
+                    service.container_name,
+                    recipe.app_root(),
+                    &synced_paths,
+                    opts.paths,
+                    opts.force,
+                ))
+            })
             .unwrap_or_else(|e| Some(Task::task_err_vec(e)))
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        vec![App::new(M2Push::NAME)
-            .about(M2Push::ABOUT)
+        vec![App::new(Push::NAME)
+            .about(Push::ABOUT)
             .arg_from_usage("<paths>... 'files or paths to push'")
             .arg_from_usage("-f --force 'ignore warnings about synced files'")
-            .after_help(M2Push::DOC_LINK)]
+            .after_help(Push::DOC_LINK)]
     }
 }
 
 ///
-/// Push files into the main running PHP container
+/// Push files into the main running container
 ///
 /// If -f is provided, it will not attempt to delete in the
 /// container first, but instead it will
@@ -90,22 +101,24 @@ impl<'a, 'b> CliCommand<'a, 'b> for M2Push {
 pub fn push(
     ctx: &Context,
     container_name: String,
+    root: &str,
+    synced_paths: &[&str],
     trailing: Vec<String>,
     force: bool,
 ) -> Vec<Task> {
-    let remote_prefix = PathBuf::from(M2_ROOT);
+    let remote_prefix = PathBuf::from(root);
 
-    // if any paths begin with contain "app/", create a notify error for each
-    // this will prevent subsequent actions from happening if even 1 of the
-    // given paths are invalid
+    // if any paths begin with a synced path (eg: "app/" in M2), create a notify
+    // error for each - this will prevent subsequent actions from happening
+    // if even 1 of the given paths are invalid
     let invalid_push_paths = trailing
         .iter()
-        .filter(|path| path.starts_with("app/"))
-        .map(|_| {
+        .filter_map(|path| synced_paths.iter().find(|synced| path.starts_with(*synced)))
+        .map(|synced| {
             if force {
                 Task::notify_warn("Ignoring all warning/checks. I hope you know what you're doing :)")
             } else {
-                Task::notify_error("Invalid paths provided. Don't try to push anything into `app/` - files there are already synced (override with -f)")
+                Task::notify_error(format!("Invalid paths provided. Don't try to push anything into `{}` - files there are already synced (override with -f)", synced))
             }
         });
 
@@ -244,6 +257,29 @@ mod tests {
         test_push(args, cwd, expected_commands);
     }
 
+    #[test]
+    fn test_push_wp() {
+        let args = vec!["prog", "--recipe=Wp", "push", "wp-config.php"];
+        let cwd = "/users/acme";
+        let expected_commands = vec![
+            "docker exec wf2__acme__php rm -rf /var/www/wp-config.php",
+            "docker cp /users/acme/wp-config.php wf2__acme__php:/var/www",
+        ];
+        test_push(args, cwd, expected_commands);
+    }
+
+    #[test]
+    fn test_push_drupal() {
+        let args = vec!["prog", "--recipe=Drupal", "push", "web/modules/custom"];
+        let cwd = "/users/acme";
+        let expected_commands = vec![
+            "docker exec wf2__acme__php rm -rf /var/www/web/modules/custom",
+            "docker exec -u www-data wf2__acme__php mkdir -p /var/www/web/modules",
+            "docker cp /users/acme/web/modules/custom wf2__acme__php:/var/www/web/modules",
+        ];
+        test_push(args, cwd, expected_commands);
+    }
+
     fn test_push(args: Vec<&str>, cwd: impl Into<PathBuf>, expected_commands: Vec<&str>) {
         let input = CLIInput::_from_args(args)._with_cwd(cwd);
         let cli_output = CLIOutput::from_input(input);
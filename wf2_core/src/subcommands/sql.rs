@@ -17,15 +17,14 @@
 //! ```
 use crate::commands::CliCommand;
 use crate::context::Context;
-use crate::recipes::m2::m2_vars::M2Vars;
-use crate::recipes::m2::services::db::DbService;
-use crate::services::Service;
+use crate::recipes::app_services::DbCredentials;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 use clap::{App, Arg, ArgMatches};
 use snailquote::escape;
 use structopt::StructOpt;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/sql")]
+#[doc_link::doc_link("/subcommands/sql")]
 pub struct SqlCmd;
 
 impl SqlCmd {
@@ -65,22 +64,28 @@ impl<'a, 'b> CliCommand<'a, 'b> for SqlCmd {
 /// generate a safe (escaped) query to the running Db service
 ///
 pub fn sql(ctx: &Context, query: String) -> Vec<Task> {
-    M2Vars::from_ctx(&ctx)
-        .map(|vars| (DbService).dc_service(&ctx, &vars))
-        .map(|service| {
-            let exec_command = format!(
-                r#"docker exec -it {container_name} mysql -u{user} -p{pass} {db} -e {trailing_args}"#,
-                container_name = service.container_name,
-                trailing_args = escape(&query),
-                user = DbService::DB_USER,
-                pass = DbService::DB_PASS,
-                db = DbService::DB_NAME,
-            );
-            vec![Task::simple_command(exec_command)]
-        })
+    RecipeKinds::from_ctx(&ctx)
+        .db_service(&ctx)
+        .map(|db| sql_exec(db, query))
         .unwrap_or_else(Task::task_err_vec)
 }
 
+///
+/// Run an escaped query against a running Db service
+///
+pub fn sql_exec(db: DbCredentials, query: String) -> Vec<Task> {
+    let exec_command = format!(
+        r#"docker exec -it {container_name} {client} -u{user} -p{pass} {db} -e {trailing_args}"#,
+        container_name = db.service.container_name,
+        client = db.client,
+        trailing_args = escape(&query),
+        user = db.user,
+        pass = db.pass,
+        db = db.db,
+    );
+    vec![Task::simple_command(exec_command)]
+}
+
 #[test]
 fn test_unescape() {
     let input = r#"wf2 sql "select * from core_config_data where config_id = '27'""#;
@@ -90,3 +95,44 @@ fn test_unescape() {
     before.push(after.as_str());
     dbg!(before);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::cli_input::CLIInput;
+    use crate::recipes::recipe_kinds::RecipeKinds;
+    use crate::test::Test;
+
+    #[test]
+    fn test_sql_wp() {
+        let cmds = Test::from_skipped(
+            r#"wf2 sql "select option_value from wp_options where option_name = 'siteurl'""#,
+            2,
+        )
+        .with_recipe(RecipeKinds::WP_NAME)
+        .with_cli_input(CLIInput::from_cwd("/users/acme"))
+        .commands();
+        assert_eq!(
+            cmds,
+            vec![
+                r#"docker exec -it wf2__acme__db mysql -udocker -pdocker docker -e "select option_value from wp_options where option_name = 'siteurl'""#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sql_drupal() {
+        let cmds = Test::from_skipped(
+            r#"wf2 sql "select name from users_field_data where uid = '1'""#,
+            2,
+        )
+        .with_recipe(RecipeKinds::DRUPAL_NAME)
+        .with_cli_input(CLIInput::from_cwd("/users/acme"))
+        .commands();
+        assert_eq!(
+            cmds,
+            vec![
+                r#"docker exec -it wf2__acme__db mysql -udocker -pdocker docker -e "select name from users_field_data where uid = '1'""#
+            ]
+        );
+    }
+}
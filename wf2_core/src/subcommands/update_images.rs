@@ -74,16 +74,15 @@
 use crate::commands::CliCommand;
 use crate::context::Context;
 use crate::dc::Dc;
-use crate::dc_tasks::DcTasksTrait;
-use crate::recipes::m2::M2Recipe;
+use crate::recipes::recipe_kinds::RecipeKinds;
 use crate::task::Task;
 use clap::{App, Arg, ArgMatches};
 use structopt::StructOpt;
 
-#[doc_link::doc_link("/recipes/m2/subcommands/update_images")]
-pub struct M2UpdateImages;
+#[doc_link::doc_link("/subcommands/update_images")]
+pub struct UpdateImages;
 
-impl M2UpdateImages {
+impl UpdateImages {
     const NAME: &'static str = "update-images";
     const ABOUT: &'static str = "Update images used in the current recipe by service name";
 }
@@ -93,22 +92,22 @@ struct Opts {
     services: Vec<String>,
 }
 
-impl<'a, 'b> CliCommand<'a, 'b> for M2UpdateImages {
+impl<'a, 'b> CliCommand<'a, 'b> for UpdateImages {
     fn name(&self) -> String {
-        String::from(M2UpdateImages::NAME)
+        String::from(UpdateImages::NAME)
     }
     fn exec(&self, matches: Option<&ArgMatches>, ctx: &Context) -> Option<Vec<Task>> {
         let opts: Opts = matches.map(Opts::from_clap).expect("guarded by Clap");
-        let dc = (M2Recipe).dc(&ctx);
+        let dc = RecipeKinds::from_ctx(&ctx).dc(&ctx);
         match dc {
             Ok(dc) => Some(update_images(&dc, opts.services)),
             Err(e) => Some(Task::task_err_vec(e)),
         }
     }
     fn subcommands(&self, _ctx: &Context) -> Vec<App<'a, 'b>> {
-        vec![App::new(M2UpdateImages::NAME)
-            .about(M2UpdateImages::ABOUT)
-            .after_help(M2UpdateImages::DOC_LINK)
+        vec![App::new(UpdateImages::NAME)
+            .about(UpdateImages::ABOUT)
+            .after_help(UpdateImages::DOC_LINK)
             .arg(
                 Arg::with_name("services")
                     .help("limit the update to a subset of services")
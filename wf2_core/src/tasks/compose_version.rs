@@ -0,0 +1,93 @@
+//!
+//! Check that the installed `docker-compose` understands the files wf2 writes.
+//!
+//! Any `depends_on` entry that waits for a `healthcheck` is written as
+//! `condition: service_healthy`, which docker-compose only accepts in a
+//! `version: "3.x"` file from 1.27.0 onwards. Older versions refuse to start.
+//!
+use crate::task::Task;
+use ansi_term::Colour::{Green, Yellow};
+use futures::future::lazy;
+use std::process::Command;
+
+///
+/// The first docker-compose release that accepts `condition` in a 3.x file
+///
+pub const MIN_COMPOSE_VERSION: (u32, u32) = (1, 27);
+
+///
+/// Parse the output of `docker-compose version --short`,
+/// eg: `1.29.2`, `v2.20.2` or `2.20.2+ds1`
+///
+pub fn parse_compose_version(output: &str) -> Option<(u32, u32)> {
+    let mut parts = output.trim().trim_start_matches('v').split('.');
+    let major = parts.next().and_then(|major| major.parse::<u32>().ok())?;
+    let minor = parts
+        .next()
+        .map(|minor| {
+            minor
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+        })
+        .and_then(|minor| minor.parse::<u32>().ok())
+        .unwrap_or(0);
+    Some((major, minor))
+}
+
+///
+/// Warn when docker-compose is missing, or too old for the generated file
+///
+pub fn compose_version_task() -> Task {
+    Task::Exec {
+        description: Some(format!(
+            "check docker-compose is at least {}.{}",
+            MIN_COMPOSE_VERSION.0, MIN_COMPOSE_VERSION.1
+        )),
+        exec: Box::new(lazy(move || {
+            let output = Command::new("docker-compose")
+                .args(&["version", "--short"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string());
+            match output.as_ref().and_then(|o| parse_compose_version(o)) {
+                Some(version) if version >= MIN_COMPOSE_VERSION => println!(
+                    "{}: docker-compose {} is supported",
+                    Green.paint("[wf2 info]"),
+                    output.unwrap_or_default().trim()
+                ),
+                Some(_) => println!(
+                    "{}: docker-compose {} is too old, services that wait for a healthcheck need {}.{} or newer",
+                    Yellow.paint("[wf2 warning]"),
+                    output.unwrap_or_default().trim(),
+                    MIN_COMPOSE_VERSION.0,
+                    MIN_COMPOSE_VERSION.1
+                ),
+                None => println!(
+                    "{}: could not read the docker-compose version, {}.{} or newer is required",
+                    Yellow.paint("[wf2 warning]"),
+                    MIN_COMPOSE_VERSION.0,
+                    MIN_COMPOSE_VERSION.1
+                ),
+            }
+            Ok(())
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compose_version() {
+        assert_eq!(parse_compose_version("1.29.2\n"), Some((1, 29)));
+        assert_eq!(parse_compose_version("1.25.0"), Some((1, 25)));
+        assert_eq!(parse_compose_version("v2.20.2"), Some((2, 20)));
+        assert_eq!(parse_compose_version("2.20.2+ds1"), Some((2, 20)));
+        assert_eq!(parse_compose_version(""), None);
+        assert!(parse_compose_version("1.26.2").expect("test") < MIN_COMPOSE_VERSION);
+        assert!(parse_compose_version("1.27.0").expect("test") >= MIN_COMPOSE_VERSION);
+    }
+}
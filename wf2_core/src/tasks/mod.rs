@@ -1 +1,4 @@
+pub mod compose_version;
 pub mod docker_clean;
+pub mod port_check;
+pub mod wait_for_healthy;
@@ -0,0 +1,554 @@
+//!
+//! Check that every published host port is free before `docker-compose up`
+//!
+//! Ports held by this project's own containers are fine, anything else
+//! is a conflict. Non-essential services can be moved to another port, either
+//! with `options.services.<name>.ports` or automatically with `--remap-ports`.
+//!
+//! Remapped ports are saved in `.wf2_ports.yml`, so every later command (like `wf2 dc`
+//! or `wf2 xdebug enable`) keeps using them. Delete that file to go back to the defaults.
+//!
+use crate::context::Context;
+use crate::dc::Dc;
+use crate::dc_tasks::DcTasks;
+use crate::services::ports::ServicePorts;
+use crate::services::traefik::TraefikService;
+use crate::services::Service;
+use crate::task::Task;
+use crate::util::two_col;
+use ansi_term::Colour::Green;
+use ansi_term::Colour::Yellow;
+use futures::future::lazy;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::Command;
+
+///
+/// A single `host:container` entry from a service's `ports`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishedPort {
+    pub service: String,
+    pub ip: Option<String>,
+    pub host: u16,
+    pub container: String,
+}
+
+impl PublishedPort {
+    ///
+    /// Parse `3306:3306`, `127.0.0.1:3306:3306` or `5672:5672/tcp`.
+    ///
+    /// Entries without a host port (like `9000`) are given a random one by
+    /// docker, so they can never conflict.
+    ///
+    pub fn parse(service: impl Into<String>, raw: &str) -> Option<PublishedPort> {
+        let parts: Vec<&str> = raw.split(':').collect();
+        let (ip, host, container) = match parts.as_slice() {
+            [host, container] => (None, host, container),
+            [ip, host, container] => (Some(ip.to_string()), host, container),
+            _ => return None,
+        };
+        host.parse::<u16>().ok().map(|host| PublishedPort {
+            service: service.into(),
+            ip,
+            host,
+            container: container.to_string(),
+        })
+    }
+
+    pub fn from_dc(dc: &Dc) -> Vec<PublishedPort> {
+        dc.services.as_ref().map_or(vec![], |services| {
+            services
+                .iter()
+                .flat_map(|(name, service)| {
+                    service
+                        .ports
+                        .clone()
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|raw| PublishedPort::parse(name.as_str(), raw))
+                        .collect::<Vec<PublishedPort>>()
+                })
+                .collect()
+        })
+    }
+
+    ///
+    /// Traefik's 80 + 443 are needed for routing every domain,
+    /// so they can never be moved
+    ///
+    pub fn is_essential(&self) -> bool {
+        self.service == TraefikService::NAME && (self.container == "80" || self.container == "443")
+    }
+
+    pub fn with_host(&self, host: u16) -> PublishedPort {
+        PublishedPort {
+            host,
+            ..self.clone()
+        }
+    }
+}
+
+impl std::fmt::Display for PublishedPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.ip {
+            Some(ip) => write!(f, "{}:{}:{}", ip, self.host, self.container),
+            None => write!(f, "{}:{}", self.host, self.container),
+        }
+    }
+}
+
+///
+/// Decides whether a host port is available
+///
+pub trait PortProbe {
+    ///
+    /// `Some(owner)` when the port is taken by something other than this project
+    ///
+    fn taken_by(&self, port: &PublishedPort) -> Option<String>;
+}
+
+///
+/// Tries to bind each port, then asks docker who owns it.
+///
+/// Something listening only on `127.0.0.1` doesn't always stop a bind
+/// on `0.0.0.0` (eg: on macOS), so both are tried, plus the port's own ip if it has one.
+///
+pub struct HostPortProbe {
+    pub prefix: String,
+}
+
+impl HostPortProbe {
+    pub fn from_ctx(ctx: &Context) -> HostPortProbe {
+        HostPortProbe {
+            prefix: ctx.prefixed_name(""),
+        }
+    }
+    fn docker_owner(port: u16) -> Option<String> {
+        Command::new("docker")
+            .arg("ps")
+            .arg("--filter")
+            .arg(format!("publish={}", port))
+            .arg("--format")
+            .arg("{{.Names}}")
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|names| !names.is_empty())
+    }
+}
+
+impl PortProbe for HostPortProbe {
+    fn taken_by(&self, port: &PublishedPort) -> Option<String> {
+        let mut ips = vec!["0.0.0.0", "127.0.0.1"];
+        if let Some(ip) = port.ip.as_ref() {
+            if !ips.contains(&ip.as_str()) {
+                ips.push(ip.as_str());
+            }
+        }
+        let in_use = ips
+            .into_iter()
+            .any(|ip| match TcpListener::bind((ip, port.host)) {
+                Err(ref e) => e.kind() == ErrorKind::AddrInUse,
+                Ok(_) => false,
+            });
+        // any other error, eg: permission denied for ports < 1024, is left for docker to report
+        if !in_use {
+            return None;
+        }
+        match HostPortProbe::docker_owner(port.host) {
+            Some(ref owner) if owner.starts_with(&self.prefix) => None,
+            Some(owner) => Some(owner),
+            None => Some(String::from("another process")),
+        }
+    }
+}
+
+#[derive(Debug, Fail)]
+enum PortCheckError {
+    #[fail(display = "{}", _0)]
+    Conflicts(String),
+}
+
+pub struct PortCheck;
+
+impl PortCheck {
+    ///
+    /// How far from the original port to look for a free one
+    ///
+    pub const SEARCH_RANGE: u16 = 100;
+
+    pub fn conflicts(
+        ports: &[PublishedPort],
+        probe: &dyn PortProbe,
+    ) -> Vec<(PublishedPort, String)> {
+        ports
+            .iter()
+            .filter_map(|port| probe.taken_by(port).map(|owner| (port.clone(), owner)))
+            .collect()
+    }
+
+    ///
+    /// The next free port, that's also not already published by another service
+    ///
+    pub fn suggest(
+        port: &PublishedPort,
+        ports: &[PublishedPort],
+        probe: &dyn PortProbe,
+    ) -> Option<u16> {
+        (1..=PortCheck::SEARCH_RANGE)
+            .filter_map(|offset| port.host.checked_add(offset))
+            .find(|candidate| {
+                !ports.iter().any(|p| p.host == *candidate)
+                    && probe.taken_by(&port.with_host(*candidate)).is_none()
+            })
+    }
+
+    ///
+    /// Move every non-essential conflicting port to a free one.
+    ///
+    /// Returns the ports that were changed, as `(before, after)`
+    ///
+    pub fn remap(dc: &mut Dc, probe: &dyn PortProbe) -> Vec<(PublishedPort, PublishedPort)> {
+        let mut ports = PublishedPort::from_dc(dc);
+        let mut changed = vec![];
+
+        for (port, _) in PortCheck::conflicts(&ports, probe) {
+            if port.is_essential() {
+                continue;
+            }
+            if let Some(next) = PortCheck::suggest(&port, &ports, probe) {
+                let next = port.with_host(next);
+                ports.push(next.clone());
+                changed.push((port, next));
+            }
+        }
+
+        if let Some(services) = dc.services.as_mut() {
+            for (before, after) in &changed {
+                if let Some(service_ports) = services
+                    .get_mut(&before.service)
+                    .and_then(|service| service.ports.as_mut())
+                {
+                    for raw in service_ports.iter_mut() {
+                        if PublishedPort::parse(before.service.as_str(), raw).as_ref()
+                            == Some(before)
+                        {
+                            *raw = after.to_string();
+                        }
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    ///
+    /// eg:
+    ///
+    /// ```txt
+    /// db               3307 -> 3306
+    /// elasticsearch    9200 -> 9200
+    /// ```
+    ///
+    pub fn mapping(ports: &[PublishedPort]) -> String {
+        two_col(
+            ports
+                .iter()
+                .map(|port| {
+                    (
+                        port.service.clone(),
+                        format!("{} -> {}", port.host, port.container),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    ///
+    /// A readable explanation of each conflict, with a suggested fix
+    ///
+    pub fn explain(
+        conflicts: &[(PublishedPort, String)],
+        ports: &[PublishedPort],
+        probe: &dyn PortProbe,
+    ) -> String {
+        let lines = conflicts
+            .iter()
+            .map(|(port, owner)| {
+                let prefix = format!(
+                    "Port {} for `{}` is already in use by {}.",
+                    port.host, port.service, owner
+                );
+                if port.is_essential() {
+                    return format!(
+                        "{} It's needed to route every domain, so please stop it first (eg: `wf2 down` in the other project)",
+                        prefix
+                    );
+                }
+                match PortCheck::suggest(port, ports, probe) {
+                    Some(next) => format!(
+                        "{} Either stop it, or use another port in your wf2.yml:\n\noptions:\n  services:\n    {}:\n      ports: [ \"{}\" ]\n",
+                        prefix,
+                        port.service,
+                        port.with_host(next)
+                    ),
+                    None => prefix,
+                }
+            })
+            .collect::<Vec<String>>();
+        format!(
+            "{}\nOr run `wf2 up --remap-ports` to move non-essential services to free ports automatically",
+            lines.join("\n")
+        )
+    }
+}
+
+///
+/// Where `--remap-ports` writes the updated docker-compose file, and saves the ports it picked
+///
+#[derive(Debug, Clone)]
+pub struct RemapPorts {
+    pub dc_file: PathBuf,
+    pub state_file: PathBuf,
+}
+
+impl RemapPorts {
+    pub fn from_ctx(ctx: &Context, dc_file: impl Into<PathBuf>) -> RemapPorts {
+        RemapPorts {
+            dc_file: dc_file.into(),
+            state_file: ServicePorts::remapped_path(ctx),
+        }
+    }
+
+    ///
+    /// Add the full `ports` list of every changed service to the saved ones
+    ///
+    fn save(
+        &self,
+        dc: &Dc,
+        changed: &[(PublishedPort, PublishedPort)],
+    ) -> Result<(), failure::Error> {
+        let mut saved = ServicePorts::read_remapped(&self.state_file)?;
+        for (before, _) in changed {
+            if let Some(ports) = dc
+                .services
+                .as_ref()
+                .and_then(|services| services.get(&before.service))
+                .and_then(|service| service.ports.clone())
+            {
+                saved.insert(before.service.clone(), ports);
+            }
+        }
+        fs::write(&self.state_file, serde_yaml::to_string(&saved)?)?;
+        Ok(())
+    }
+}
+
+///
+/// Fail early, with suggestions, if any published port is already taken
+///
+pub fn port_check_task(ctx: &Context, dc: &Dc) -> Task {
+    port_check_task_with(dc.clone(), HostPortProbe::from_ctx(ctx), None)
+}
+
+///
+/// The port check, followed by `docker-compose <args>` - for any recipe's `up`.
+///
+/// With `remap`, the port check writes the docker-compose file itself, since the
+/// remapped ports are only known once it has run
+///
+pub fn port_checked_up(
+    ctx: &Context,
+    dc: &Dc,
+    dc_tasks: &DcTasks,
+    args: &str,
+    remap: bool,
+) -> Vec<Task> {
+    if remap {
+        vec![
+            port_check_task_with(
+                dc.clone(),
+                HostPortProbe::from_ctx(ctx),
+                Some(RemapPorts::from_ctx(ctx, dc_tasks.file.clone())),
+            ),
+            Task::simple_command(dc_tasks.cmd_string(args)),
+        ]
+    } else {
+        vec![port_check_task(ctx, dc), dc_tasks.cmd_task(vec![args])]
+    }
+}
+
+///
+/// Same as [`port_check_task`], but with the given probe.
+///
+/// With `remap`, conflicting ports are moved first, then the updated docker-compose
+/// file is written & the new ports are saved. Nothing is probed until the task runs.
+///
+pub fn port_check_task_with(
+    dc: Dc,
+    probe: impl PortProbe + Send + 'static,
+    remap: Option<RemapPorts>,
+) -> Task {
+    Task::Exec {
+        description: Some(String::from("check published ports are available")),
+        exec: Box::new(lazy(move || {
+            let mut dc = dc;
+            if let Some(remap) = remap {
+                let changed = PortCheck::remap(&mut dc, &probe);
+                for (before, after) in &changed {
+                    println!(
+                        "{}: port {} is taken, so `{}` will use {} instead (saved in {})",
+                        Yellow.paint("[wf2 warning]"),
+                        before.host,
+                        before.service,
+                        after.host,
+                        ServicePorts::REMAPPED_FILE
+                    );
+                }
+                if !changed.is_empty() {
+                    remap.save(&dc, &changed)?;
+                }
+                fs::write(&remap.dc_file, dc.to_bytes())?;
+            }
+            let ports = PublishedPort::from_dc(&dc);
+            let conflicts = PortCheck::conflicts(&ports, &probe);
+            if !conflicts.is_empty() {
+                return Err(PortCheckError::Conflicts(PortCheck::explain(
+                    &conflicts, &ports, &probe,
+                ))
+                .into());
+            }
+            println!(
+                "{}: published ports\n{}",
+                Green.paint("[wf2 info]"),
+                PortCheck::mapping(&ports)
+            );
+            Ok(())
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dc_service::DcService;
+    use futures::Future;
+
+    struct TakenPorts(Vec<u16>);
+
+    impl PortProbe for TakenPorts {
+        fn taken_by(&self, port: &PublishedPort) -> Option<String> {
+            if self.0.contains(&port.host) {
+                Some(String::from("mysqld"))
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            PublishedPort::parse("db", "127.0.0.1:3306:3306"),
+            Some(PublishedPort {
+                service: String::from("db"),
+                ip: Some(String::from("127.0.0.1")),
+                host: 3306,
+                container: String::from("3306"),
+            })
+        );
+        assert_eq!(
+            PublishedPort::parse("rabbitmq", "5672:5672/tcp").map(|p| p.to_string()),
+            Some(String::from("5672:5672/tcp"))
+        );
+        assert_eq!(PublishedPort::parse("php", "9000"), None);
+    }
+
+    #[test]
+    fn test_remap() {
+        let mut dc = Dc::new()
+            .set_services(&[
+                DcService::new("acme", "db", "mysql:5.7")
+                    .set_ports(vec!["3306:3306"])
+                    .finish(),
+                DcService::new("acme", "traefik", "traefik:1.7")
+                    .set_ports(vec!["80:80", "443:443", "8080:8080"])
+                    .finish(),
+                DcService::new("acme", "elasticsearch", "es")
+                    .set_ports(vec!["3307:9200"])
+                    .finish(),
+            ])
+            .build();
+        let probe = TakenPorts(vec![80, 3306, 3308]);
+        let changed = PortCheck::remap(&mut dc, &probe);
+
+        // 3307 is used by elasticsearch, 3308 by something else
+        assert_eq!(
+            changed
+                .iter()
+                .map(|(before, after)| (before.to_string(), after.to_string()))
+                .collect::<Vec<(String, String)>>(),
+            vec![(String::from("3306:3306"), String::from("3309:3306"))]
+        );
+        let services = dc.services.expect("test");
+        assert_eq!(
+            services.get("db").expect("test").ports,
+            Some(vec![String::from("3309:3306")])
+        );
+        // essential ports are never moved
+        assert_eq!(
+            services.get("traefik").expect("test").ports,
+            Some(vec![
+                String::from("80:80"),
+                String::from("443:443"),
+                String::from("8080:8080")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_port_check_task_remaps_when_run() -> Result<(), failure::Error> {
+        let dir = tempfile::tempdir()?;
+        let remap = RemapPorts {
+            dc_file: dir.path().join("docker-compose.yml"),
+            state_file: dir.path().join(ServicePorts::REMAPPED_FILE),
+        };
+        let dc = Dc::new()
+            .set_services(&[DcService::new("acme", "db", "mysql:5.7")
+                .set_ports(vec!["3306:3306"])
+                .finish()])
+            .build();
+        let task = port_check_task_with(dc, TakenPorts(vec![3306]), Some(remap.clone()));
+        // nothing is probed or written until the task is executed
+        assert!(!remap.dc_file.exists());
+        match task {
+            Task::Exec { exec, .. } => exec.wait()?,
+            _ => unreachable!(),
+        }
+        assert!(fs::read_to_string(&remap.dc_file)?.contains("3307:3306"));
+        // and the new port is kept for the next command
+        let saved = ServicePorts::read_remapped(&remap.state_file)?;
+        assert_eq!(saved.get("db"), Some(&vec![String::from("3307:3306")]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() {
+        let ports = vec![
+            PublishedPort::parse("db", "3306:3306").expect("test"),
+            PublishedPort::parse("traefik", "80:80").expect("test"),
+        ];
+        let probe = TakenPorts(vec![80, 3306]);
+        let conflicts = PortCheck::conflicts(&ports, &probe);
+        let explained = PortCheck::explain(&conflicts, &ports, &probe);
+        assert!(explained.contains("Port 3306 for `db` is already in use by mysqld."));
+        assert!(explained.contains("    db:\n      ports: [ \"3307:3306\" ]"));
+        assert!(
+            explained.contains("Port 80 for `traefik` is already in use by mysqld. It's needed")
+        );
+    }
+}
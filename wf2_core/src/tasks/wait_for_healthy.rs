@@ -0,0 +1,205 @@
+//!
+//! Wait for containers to be ready after `docker-compose up -d`
+//!
+//! Services with a `healthcheck` must report `healthy`, everything else
+//! just needs to be `running`.
+//!
+use crate::dc::Dc;
+use crate::task::Task;
+use ansi_term::Colour::{Green, Yellow};
+use futures::future::lazy;
+use std::io::Write;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaitFor {
+    pub name: String,
+    pub container_name: String,
+    pub healthcheck: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContainerStatus {
+    Healthy,
+    Unhealthy,
+    Starting,
+    Running,
+    Other(String),
+}
+
+impl ContainerStatus {
+    ///
+    /// Parse the output of the `docker inspect` format used below
+    ///
+    pub fn from_inspect(output: &str) -> ContainerStatus {
+        match output.trim() {
+            "healthy" => ContainerStatus::Healthy,
+            "unhealthy" => ContainerStatus::Unhealthy,
+            "starting" => ContainerStatus::Starting,
+            "running" => ContainerStatus::Running,
+            "" => ContainerStatus::Other(String::from("missing")),
+            other => ContainerStatus::Other(other.to_string()),
+        }
+    }
+    pub fn is_ready(&self, healthcheck: bool) -> bool {
+        match self {
+            ContainerStatus::Healthy => true,
+            ContainerStatus::Running => !healthcheck,
+            _ => false,
+        }
+    }
+    pub fn as_str(&self) -> &str {
+        match self {
+            ContainerStatus::Healthy => "healthy",
+            ContainerStatus::Unhealthy => "unhealthy",
+            ContainerStatus::Starting => "starting",
+            ContainerStatus::Running => "running",
+            ContainerStatus::Other(s) => s,
+        }
+    }
+}
+
+#[derive(Debug, Fail)]
+enum WaitError {
+    #[fail(
+        display = "Timed out after {}s, these services never became ready: {}",
+        _0, _1
+    )]
+    Timeout(u64, String),
+    #[fail(display = "{} reported as unhealthy, check `wf2 dc logs {}`", _0, _0)]
+    Unhealthy(String),
+}
+
+impl WaitFor {
+    pub fn from_dc(dc: &Dc) -> Vec<WaitFor> {
+        dc.services.as_ref().map_or(vec![], |services| {
+            services
+                .iter()
+                .map(|(name, service)| WaitFor {
+                    name: name.to_string(),
+                    container_name: service.container_name.clone(),
+                    healthcheck: service.healthcheck.is_some(),
+                })
+                .collect()
+        })
+    }
+    fn status(&self) -> ContainerStatus {
+        Command::new("docker")
+            .arg("inspect")
+            .arg("--format")
+            .arg("{{if .State.Health}}{{.State.Health.Status}}{{else}}{{.State.Status}}{{end}}")
+            .arg(&self.container_name)
+            .output()
+            .map(|output| ContainerStatus::from_inspect(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_else(|e| ContainerStatus::Other(e.to_string()))
+    }
+}
+
+///
+/// Poll every service until they're all ready, or until `timeout` is reached
+///
+pub fn wait_for_healthy(services: Vec<WaitFor>, timeout: Duration) -> Task {
+    Task::Exec {
+        description: Some(format!(
+            "wait up to {}s for services to be ready",
+            timeout.as_secs()
+        )),
+        exec: Box::new(lazy(move || poll(&services, timeout))),
+    }
+}
+
+fn poll(services: &[WaitFor], timeout: Duration) -> Result<(), failure::Error> {
+    let start = Instant::now();
+    loop {
+        let statuses = services
+            .iter()
+            .map(|service| (service, service.status()))
+            .collect::<Vec<(&WaitFor, ContainerStatus)>>();
+
+        if let Some((service, _)) = statuses
+            .iter()
+            .find(|(_, status)| *status == ContainerStatus::Unhealthy)
+        {
+            println!();
+            return Err(WaitError::Unhealthy(service.name.clone()).into());
+        }
+
+        let pending = pending_summary(&statuses);
+        let elapsed = start.elapsed();
+
+        if pending.is_empty() {
+            println!(
+                "\r{}: all {} services are ready ({}s)\x1b[K",
+                Green.paint("[wf2 info]"),
+                services.len(),
+                elapsed.as_secs()
+            );
+            return Ok(());
+        }
+
+        if elapsed >= timeout {
+            println!();
+            return Err(WaitError::Timeout(timeout.as_secs(), pending).into());
+        }
+
+        print!(
+            "\r{}: waiting for {} ({}s/{}s)\x1b[K",
+            Yellow.paint("[wf2 info]"),
+            pending,
+            elapsed.as_secs(),
+            timeout.as_secs()
+        );
+        std::io::stdout().flush()?;
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+///
+/// eg: `db (starting), elasticsearch (starting)`
+///
+fn pending_summary(statuses: &[(&WaitFor, ContainerStatus)]) -> String {
+    statuses
+        .iter()
+        .filter(|(service, status)| !status.is_ready(service.healthcheck))
+        .map(|(service, status)| format!("{} ({})", service.name, status.as_str()))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_summary() {
+        let db = WaitFor {
+            name: String::from("db"),
+            container_name: String::from("wf2__acme__db"),
+            healthcheck: true,
+        };
+        let mail = WaitFor {
+            name: String::from("mail"),
+            container_name: String::from("wf2__acme__mail"),
+            healthcheck: false,
+        };
+        let statuses = vec![
+            (&db, ContainerStatus::from_inspect("running\n")),
+            (&mail, ContainerStatus::from_inspect("running\n")),
+        ];
+        assert_eq!(pending_summary(&statuses), "db (running)");
+
+        let statuses = vec![
+            (&db, ContainerStatus::from_inspect("healthy")),
+            (&mail, ContainerStatus::from_inspect("")),
+        ];
+        assert_eq!(pending_summary(&statuses), "mail (missing)");
+
+        let statuses = vec![
+            (&db, ContainerStatus::from_inspect("healthy")),
+            (&mail, ContainerStatus::from_inspect("running")),
+        ];
+        assert_eq!(pending_summary(&statuses), "");
+    }
+}
@@ -1,5 +1,20 @@
+use snailquote::escape;
+use std::borrow::Cow;
 use std::path::PathBuf;
 
+///
+/// Like [`snailquote::escape`], but always quoted, since on its own
+/// it leaves characters like `&` or `;` as they are.
+///
+/// Unchanged strings have no quotes, backslashes or whitespace, so single quotes are enough
+///
+pub fn shell_quote(arg: &str) -> String {
+    match escape(arg) {
+        Cow::Borrowed(arg) => format!("'{}'", arg),
+        Cow::Owned(quoted) => quoted,
+    }
+}
+
 pub fn path_buf_to_string(pb: &PathBuf) -> String {
     pb.to_string_lossy().to_string()
 }
@@ -0,0 +1,105 @@
+//!
+//! Pick Composer 1 or 2 per project
+//!
+//! ```
+//! # use wf2_core::test::Test;
+//! # use wf2_core::cli::cli_input::CLIInput;
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::m2::M2Recipe;
+//! # let input = r#"
+//! recipe: M2
+//! composer_version: 1
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = (M2Recipe).dc(&ctx).expect("valid dc");
+//! # let volumes = dc.volumes.expect("volumes");
+//! # assert!(volumes.contains_key("composer-cache-v1"));
+//! # assert!(!volumes.contains_key("composer-cache"));
+//! ```
+//!
+//! `wf2 composer` and any `composer` commands in scripts then use `composer1`
+//! or `composer2` from the PHP image. Each major version gets its own cache volume,
+//! since the cache formats differ.
+//!
+//! Without `composer_version`, the image's default `composer` is used.
+//!
+use crate::context::Context;
+use version_enum::VersionEnum;
+
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+pub enum COMPOSER {
+    #[version("1")]
+    One,
+    #[version("2", default)]
+    Two,
+}
+
+impl COMPOSER {
+    pub const BINARY: &'static str = "composer";
+    pub const CACHE_VOLUME: &'static str = "composer-cache";
+
+    pub fn binary(&self) -> String {
+        format!("{}{}", COMPOSER::BINARY, self.version())
+    }
+
+    pub fn cache_volume(&self) -> String {
+        format!("{}-v{}", COMPOSER::CACHE_VOLUME, self.version())
+    }
+
+    pub fn binary_from_ctx(ctx: &Context) -> String {
+        ctx.composer_version
+            .as_ref()
+            .map_or_else(|| COMPOSER::BINARY.to_string(), COMPOSER::binary)
+    }
+
+    pub fn cache_volume_from_ctx(ctx: &Context) -> String {
+        ctx.composer_version.as_ref().map_or_else(
+            || COMPOSER::CACHE_VOLUME.to_string(),
+            COMPOSER::cache_volume,
+        )
+    }
+
+    ///
+    /// Swap `composer` at the start of a command for the selected binary
+    ///
+    pub fn replace_binary(ctx: &Context, command: &str) -> String {
+        match command.split_whitespace().next() {
+            Some(COMPOSER::BINARY) => {
+                command.replacen(COMPOSER::BINARY, &COMPOSER::binary_from_ctx(ctx), 1)
+            }
+            _ => command.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composer_version() {
+        let ctx = Context::new_from_str("composer_version: 1").expect("test");
+        assert_eq!(ctx.composer_version, Some(COMPOSER::One));
+        assert_eq!(COMPOSER::cache_volume_from_ctx(&ctx), "composer-cache-v1");
+        assert_eq!(
+            COMPOSER::replace_binary(&ctx, "composer install"),
+            "composer1 install"
+        );
+        assert_eq!(
+            COMPOSER::replace_binary(&ctx, "  composer install"),
+            "  composer1 install"
+        );
+        assert_eq!(
+            COMPOSER::replace_binary(&ctx, "bin/magento composer"),
+            "bin/magento composer"
+        );
+
+        let ctx = Context::default();
+        assert_eq!(COMPOSER::cache_volume_from_ctx(&ctx), "composer-cache");
+        assert_eq!(
+            COMPOSER::replace_binary(&ctx, "composer install"),
+            "composer install"
+        );
+    }
+}
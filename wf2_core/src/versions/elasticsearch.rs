@@ -1,69 +1,37 @@
-use serde::de;
-use std::fmt;
-use std::fmt::Formatter;
-
-//TODO: Refactor to make more generic, so we can use for php versions too.
-macro_rules! build_es_version {
-($default:ident,[$( $key:ident => $value:expr ),*]) => {
-    #[derive(Debug, Clone, PartialEq)]
-    pub enum ELASTICSEARCH {
-    $($key,)*
-    }
-    impl ELASTICSEARCH {
-    pub fn get_image(&self) -> f64 {
-        match self {
-            $(
-                ELASTICSEARCH::$key => $value,
-            )*
-        }
-    }
-    }
-
-    impl Default for ELASTICSEARCH {
-        fn default() -> Self {
-            ELASTICSEARCH::$default
-        }
-    }
-
-
-    impl std::fmt::Display for ELASTICSEARCH {
-        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-            write!(f, "{}", self)
-        }
-    }
-
-    pub fn deserialize_elasticsearch<'de, D>(deserializer: D) -> Result<ELASTICSEARCH, D::Error>
-    where
-        D: de::Deserializer<'de>,
-    {
-        struct ELASTICSEARCHVisitor;
+use version_enum::VersionEnum;
+
+///
+/// The Elasticsearch version, set with `es_version` in the wf2 file
+///
+/// ```yaml
+/// es_version: 6.8
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "wearejh/elasticsearch:{}-m2")]
+pub enum ELASTICSEARCH {
+    #[version("6.8")]
+    SixEight,
+    #[version("7.6", default)]
+    SevenSix,
+}
 
-        impl<'de> de::Visitor<'de> for ELASTICSEARCHVisitor {
-            type Value = ELASTICSEARCH;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                let versions = vec![$($value.to_string(),)*].iter().map(|val| format!("`{}`",val)).collect::<Vec<String>>().join(", ");
-                formatter.write_fmt(format_args!("either {}",versions))
-            }
+    #[test]
+    fn test_es_version() {
+        let ctx = Context::new_from_str("es_version: 6.8").expect("test");
+        assert_eq!(ctx.es_version, ELASTICSEARCH::SixEight);
+        assert_eq!(ctx.es_version.to_string(), "6.8");
+        assert_eq!(ctx.es_version.image(), "wearejh/elasticsearch:6.8-m2");
 
-            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                let versions = vec![$($value.to_string(),)*].join(", ");
-                let r = match v {
-                    $(num if num == $value => Ok(ELASTICSEARCH::$key),)*
-                        _ => Err(format!("expected versions: {}",versions)),
-                };
-                r.map_err(E::custom)
-            }
-        }
+        let ctx = Context::new_from_str("es_version: \"7.6\"").expect("test");
+        assert_eq!(ctx.es_version, ELASTICSEARCH::SevenSix);
 
-        deserializer.deserialize_any(ELASTICSEARCHVisitor)
+        let err = Context::new_from_str("es_version: 5").expect_err("test");
+        assert!(err.to_string().contains("expected either `6.8` or `7.6`"));
     }
-
-
-}
-
 }
-build_es_version!(SevenSix,[SixEight => 6.8,SevenSix => 7.6]);
@@ -1 +1,7 @@
+pub mod composer;
 pub mod elasticsearch;
+pub mod mysql;
+pub mod node;
+pub mod opensearch;
+pub mod redis;
+pub mod search_engine;
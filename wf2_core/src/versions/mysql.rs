@@ -0,0 +1,32 @@
+use version_enum::VersionEnum;
+
+///
+/// MySQL versions, set with `options.services.db.version`
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "mysql:{}")]
+pub enum MYSQL {
+    #[version("5.6")]
+    FiveSix,
+    #[version("5.7", default)]
+    FiveSeven,
+    #[version("8.0")]
+    EightZero,
+}
+
+///
+/// MariaDB versions, set with `options.services.db.version`
+/// along with `engine: mariadb`
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "mariadb:{}")]
+pub enum MARIADB {
+    #[version("10.2")]
+    TenTwo,
+    #[version("10.3")]
+    TenThree,
+    #[version("10.4", default)]
+    TenFour,
+    #[version("10.6")]
+    TenSix,
+}
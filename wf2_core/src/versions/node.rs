@@ -0,0 +1,244 @@
+//!
+//! The Node version used by the `node` service & PWA builds.
+//!
+//! It can be set in the wf2 file
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::m2::M2Recipe;
+//! # let input = r#"
+//! recipe: M2
+//! node_version: 16
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = (M2Recipe).dc(&ctx).expect("valid dc");
+//! # let node = dc.services.expect("services").get("node").cloned().expect("node");
+//! # assert_eq!(node.image, "wearejh/node:16-m2");
+//! ```
+//!
+//! Otherwise, an `.nvmrc` file is used when there is one, either in `npm_path`
+//! or in the project root (or the PWA's `src_dir`, for PWA builds). If neither is found, Node 8 is used.
+//!
+use crate::context::Context;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use version_enum::VersionEnum;
+
+///
+/// Node versions, using the `wearejh/node` images
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "wearejh/node:{}-m2")]
+pub enum NODE {
+    #[version("8", default)]
+    Eight,
+    #[version("10")]
+    Ten,
+    #[version("12")]
+    Twelve,
+    #[version("14")]
+    Fourteen,
+    #[version("16")]
+    Sixteen,
+    #[version("18")]
+    Eighteen,
+    #[version("20")]
+    Twenty,
+}
+
+#[derive(Debug, Fail)]
+pub enum NodeVersionError {
+    #[fail(
+        display = "Node {} from {} is not supported, expected either {}",
+        version, path, expected
+    )]
+    UnsupportedNvmrc {
+        version: String,
+        path: String,
+        expected: String,
+    },
+}
+
+///
+/// Where the Node version came from
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeVersionSource {
+    Config,
+    Nvmrc(PathBuf),
+    Default,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeVersion {
+    pub node: NODE,
+    pub source: NodeVersionSource,
+}
+
+impl NodeVersion {
+    pub const NVMRC: &'static str = ".nvmrc";
+
+    ///
+    /// `lts/<name>` aliases that nvm understands
+    ///
+    const LTS_NAMES: [(&'static str, NODE); 7] = [
+        ("carbon", NODE::Eight),
+        ("dubnium", NODE::Ten),
+        ("erbium", NODE::Twelve),
+        ("fermium", NODE::Fourteen),
+        ("gallium", NODE::Sixteen),
+        ("hydrogen", NODE::Eighteen),
+        ("iron", NODE::Twenty),
+    ];
+
+    pub fn from_ctx(ctx: &Context) -> Result<NodeVersion, failure::Error> {
+        NodeVersion::from_paths(ctx, NodeVersion::nvmrc_paths(ctx))
+    }
+
+    ///
+    /// For projects like the PWA that live in their own directory,
+    /// where only that directory's `.nvmrc` applies
+    ///
+    pub fn from_dir(ctx: &Context, dir: &Path) -> Result<NodeVersion, failure::Error> {
+        NodeVersion::from_paths(ctx, vec![ctx.cwd.join(dir).join(NodeVersion::NVMRC)])
+    }
+
+    fn from_paths(ctx: &Context, paths: Vec<PathBuf>) -> Result<NodeVersion, failure::Error> {
+        if let Some(node) = ctx.node_version.clone() {
+            return Ok(NodeVersion {
+                node,
+                source: NodeVersionSource::Config,
+            });
+        }
+        for path in paths {
+            if let Ok(content) = fs::read_to_string(&path) {
+                return NodeVersion::from_nvmrc(&content, path);
+            }
+        }
+        Ok(NodeVersion {
+            node: NODE::default(),
+            source: NodeVersionSource::Default,
+        })
+    }
+
+    ///
+    /// `npm_path` first, since themes can pin a different version to the project
+    ///
+    pub fn nvmrc_paths(ctx: &Context) -> Vec<PathBuf> {
+        let mut paths = vec![
+            // collecting the components drops the `.` of the default `npm_path`
+            ctx.cwd
+                .join(&ctx.npm_path)
+                .join(NodeVersion::NVMRC)
+                .components()
+                .collect::<PathBuf>(),
+            ctx.cwd.join(NodeVersion::NVMRC),
+        ];
+        paths.dedup();
+        paths
+    }
+
+    ///
+    /// `lts/*` is the newest LTS listed above. Aliases like `node` don't pin
+    /// a version, so the default is used for those
+    ///
+    pub fn from_nvmrc(content: &str, path: PathBuf) -> Result<NodeVersion, failure::Error> {
+        let content = content.trim();
+        let major = match content.strip_prefix("lts/") {
+            Some("*") => NodeVersion::LTS_NAMES
+                .last()
+                .map(|(_, node)| node.version().to_string()),
+            Some(name) => NodeVersion::LTS_NAMES
+                .iter()
+                .find(|(lts, _)| *lts == name)
+                .map(|(_, node)| node.version().to_string()),
+            None => content
+                .trim_start_matches('v')
+                .split('.')
+                .next()
+                .filter(|major| major.parse::<u32>().is_ok())
+                .map(String::from),
+        };
+        match major {
+            Some(major) => match NODE::from_version(&major) {
+                Some(node) => Ok(NodeVersion {
+                    node,
+                    source: NodeVersionSource::Nvmrc(path),
+                }),
+                None => Err(NodeVersionError::UnsupportedNvmrc {
+                    version: content.to_string(),
+                    path: path.display().to_string(),
+                    expected: NODE::expected(),
+                }
+                .into()),
+            },
+            None => Ok(NodeVersion {
+                node: NODE::default(),
+                source: NodeVersionSource::Default,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for NodeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            NodeVersionSource::Config => write!(f, "{} (from node_version)", self.node),
+            NodeVersionSource::Nvmrc(path) => write!(f, "{} (from {})", self.node, path.display()),
+            NodeVersionSource::Default => write!(f, "{} (default)", self.node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::node::NodeService;
+    use crate::services::Service;
+
+    #[test]
+    fn test_default_image() {
+        assert_eq!(NODE::default().image(), NodeService::IMAGE);
+    }
+
+    #[test]
+    fn test_from_nvmrc() {
+        let path = PathBuf::from(".nvmrc");
+        let version = |content: &str| {
+            NodeVersion::from_nvmrc(content, path.clone())
+                .expect("test")
+                .node
+        };
+        assert_eq!(version("v16.20.2\n"), NODE::Sixteen);
+        assert_eq!(version("18"), NODE::Eighteen);
+        assert_eq!(version("lts/iron"), NODE::Twenty);
+        assert_eq!(version("lts/*"), NODE::Twenty);
+        assert_eq!(version("node"), NODE::Eight);
+        assert_eq!(
+            NodeVersion::from_nvmrc("v21.1.0", path)
+                .expect_err("test")
+                .to_string(),
+            "Node v21.1.0 from .nvmrc is not supported, expected either `8`, `10`, `12`, `14`, `16`, `18` or `20`"
+        );
+    }
+
+    #[test]
+    fn test_from_ctx() {
+        let ctx = Context::new("../fixtures/nvmrc");
+        let node_version = NodeVersion::from_ctx(&ctx).expect("test");
+        assert_eq!(node_version.node, NODE::Eighteen);
+        assert_eq!(
+            node_version.to_string(),
+            "18 (from ../fixtures/nvmrc/.nvmrc)"
+        );
+
+        let ctx = Context {
+            node_version: Some(NODE::Twelve),
+            ..Context::new("../fixtures/nvmrc")
+        };
+        let node_version = NodeVersion::from_ctx(&ctx).expect("test");
+        assert_eq!(node_version.to_string(), "12 (from node_version)");
+    }
+}
@@ -0,0 +1,16 @@
+use version_enum::VersionEnum;
+
+///
+/// OpenSearch versions supported by Magento, each pinned to a specific image
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+pub enum OPENSEARCH {
+    #[version("1.2", image = "opensearchproject/opensearch:1.2.4")]
+    OneTwo,
+    #[version("2.5", image = "opensearchproject/opensearch:2.5.0")]
+    TwoFive,
+    #[version("2.11", image = "opensearchproject/opensearch:2.11.1")]
+    TwoEleven,
+    #[version("2.12", default, image = "opensearchproject/opensearch:2.12.0")]
+    TwoTwelve,
+}
@@ -0,0 +1,33 @@
+use version_enum::VersionEnum;
+
+///
+/// Redis versions, all using the alpine images
+///
+#[derive(Debug, Clone, PartialEq, VersionEnum)]
+#[version(image = "redis:{}-alpine")]
+pub enum REDIS {
+    #[version("3", default)]
+    Three,
+    #[version("5")]
+    Five,
+    #[version("6.0")]
+    SixZero,
+    #[version("6.2")]
+    SixTwo,
+    #[version("7.0")]
+    SevenZero,
+    #[version("7.2")]
+    SevenTwo,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::redis::RedisService;
+    use crate::services::Service;
+
+    #[test]
+    fn test_default_image() {
+        assert_eq!(REDIS::default().image(), RedisService::IMAGE);
+    }
+}
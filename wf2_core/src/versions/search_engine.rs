@@ -0,0 +1,252 @@
+//!
+//! Choose between Elasticsearch and OpenSearch, along with a version
+//!
+//! ```
+//! # use wf2_core::context::Context;
+//! # use wf2_core::dc_tasks::DcTasksTrait;
+//! # use wf2_core::recipes::m2::M2Recipe;
+//! # let input = r#"
+//! recipe: M2
+//! search_engine:
+//!   engine: opensearch
+//!   version: 2.12
+//! # "#;
+//! # let ctx = Context::new_from_str(input).expect("can parse");
+//! # let dc = (M2Recipe).dc(&ctx).expect("valid dc");
+//! # let services = dc.services.expect("services");
+//! # assert!(services.get("elasticsearch").is_none());
+//! # assert_eq!(services.get("opensearch").expect("opensearch").image, "opensearchproject/opensearch:2.12.0");
+//! ```
+//!
+//! Without `search_engine`, Elasticsearch is used with the version from `es_version`.
+//!
+use crate::context::Context;
+use crate::versions::elasticsearch::ELASTICSEARCH;
+use crate::versions::opensearch::OPENSEARCH;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Debug, Fail)]
+pub enum SearchEngineError {
+    #[fail(
+        display = "{} {} is not supported, expected one of: {}",
+        engine, version, supported
+    )]
+    UnsupportedVersion {
+        engine: SearchEngineKind,
+        version: String,
+        supported: String,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchEngineKind {
+    Elasticsearch,
+    Opensearch,
+}
+
+impl std::fmt::Display for SearchEngineKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchEngineKind::Elasticsearch => write!(f, "elasticsearch"),
+            SearchEngineKind::Opensearch => write!(f, "opensearch"),
+        }
+    }
+}
+
+///
+/// `search_engine.version`, from either engine's supported versions
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchEngineVersion {
+    Elasticsearch(ELASTICSEARCH),
+    Opensearch(OPENSEARCH),
+}
+
+impl std::fmt::Display for SearchEngineVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchEngineVersion::Elasticsearch(version) => write!(f, "{}", version),
+            SearchEngineVersion::Opensearch(version) => write!(f, "{}", version),
+        }
+    }
+}
+
+///
+/// `search_engine` in the wf2 file
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchEngine {
+    pub engine: SearchEngineKind,
+    pub version: Option<SearchEngineVersion>,
+}
+
+///
+/// `version` is only parsed once `engine` is known,
+/// so each engine lists just its own versions when it's invalid
+///
+impl<'de> Deserialize<'de> for SearchEngine {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw {
+            engine: SearchEngineKind,
+            #[serde(default)]
+            version: Option<serde_yaml::Value>,
+        }
+        let raw = Raw::deserialize(deserializer)?;
+        let version = match (raw.engine, raw.version) {
+            (_, None) => None,
+            (SearchEngineKind::Elasticsearch, Some(value)) => Some(
+                serde_yaml::from_value(value)
+                    .map(SearchEngineVersion::Elasticsearch)
+                    .map_err(|_| {
+                        D::Error::custom(format!(
+                            "expected an Elasticsearch version ({})",
+                            ELASTICSEARCH::expected()
+                        ))
+                    })?,
+            ),
+            (SearchEngineKind::Opensearch, Some(value)) => Some(
+                serde_yaml::from_value(value)
+                    .map(SearchEngineVersion::Opensearch)
+                    .map_err(|_| {
+                        D::Error::custom(format!(
+                            "expected an OpenSearch version ({})",
+                            OPENSEARCH::expected()
+                        ))
+                    })?,
+            ),
+        };
+        Ok(SearchEngine {
+            engine: raw.engine,
+            version,
+        })
+    }
+}
+
+impl SearchEngine {
+    pub fn from_ctx(ctx: &Context) -> SearchEngine {
+        ctx.search_engine.clone().unwrap_or_else(|| SearchEngine {
+            engine: SearchEngineKind::Elasticsearch,
+            version: Some(SearchEngineVersion::Elasticsearch(ctx.es_version.clone())),
+        })
+    }
+
+    pub fn is_opensearch(&self) -> bool {
+        self.engine == SearchEngineKind::Opensearch
+    }
+
+    pub fn versions(&self) -> Vec<&'static str> {
+        match self.engine {
+            SearchEngineKind::Elasticsearch => ELASTICSEARCH::versions(),
+            SearchEngineKind::Opensearch => OPENSEARCH::versions(),
+        }
+    }
+
+    ///
+    /// The version, with the engine's default applied
+    ///
+    pub fn version(&self, ctx: &Context) -> String {
+        self.version.as_ref().map_or_else(
+            || match self.engine {
+                SearchEngineKind::Elasticsearch => ctx.es_version.to_string(),
+                SearchEngineKind::Opensearch => OPENSEARCH::default().to_string(),
+            },
+            |version| version.to_string(),
+        )
+    }
+
+    pub fn validate(&self, ctx: &Context) -> Result<(), SearchEngineError> {
+        let version = self.version(ctx);
+        if self.versions().contains(&version.as_str()) {
+            return Ok(());
+        }
+        Err(SearchEngineError::UnsupportedVersion {
+            engine: self.engine,
+            version,
+            supported: self.versions().join(", "),
+        })
+    }
+
+    ///
+    /// Unsupported versions are rejected by [`SearchEngine::validate`],
+    /// so they fall back to the default image here
+    ///
+    pub fn image(&self, ctx: &Context) -> String {
+        let version = self.version(ctx);
+        match self.engine {
+            SearchEngineKind::Elasticsearch => ELASTICSEARCH::from_version(&version)
+                .unwrap_or_default()
+                .image(),
+            SearchEngineKind::Opensearch => OPENSEARCH::from_version(&version)
+                .unwrap_or_default()
+                .image(),
+        }
+    }
+
+    ///
+    /// The value Magento expects for `--search-engine`, eg: `elasticsearch7`
+    ///
+    pub fn magento_engine(&self, ctx: &Context) -> String {
+        match self.engine {
+            SearchEngineKind::Elasticsearch => {
+                let major = self
+                    .version(ctx)
+                    .split('.')
+                    .next()
+                    .unwrap_or("7")
+                    .to_string();
+                format!("elasticsearch{}", major)
+            }
+            SearchEngineKind::Opensearch => String::from("opensearch"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_engine() {
+        let ctx = Context::new_from_str("es_version: 6.8").expect("test");
+        let search = SearchEngine::from_ctx(&ctx);
+        assert_eq!(search.image(&ctx), "wearejh/elasticsearch:6.8-m2");
+        assert_eq!(search.magento_engine(&ctx), "elasticsearch6");
+
+        let ctx = Context::new_from_str("search_engine: { engine: opensearch }").expect("test");
+        let search = SearchEngine::from_ctx(&ctx);
+        assert_eq!(search.image(&ctx), "opensearchproject/opensearch:2.12.0");
+        assert_eq!(search.magento_engine(&ctx), "opensearch");
+
+        let ctx = Context::new_from_str("search_engine: { engine: opensearch, version: 2.11 }")
+            .expect("test");
+        assert_eq!(
+            SearchEngine::from_ctx(&ctx).image(&ctx),
+            "opensearchproject/opensearch:2.11.1"
+        );
+
+        let err = Context::new_from_str("search_engine: { engine: opensearch, version: 1.3 }")
+            .expect_err("test")
+            .to_string();
+        assert!(err.contains("expected an OpenSearch version (`1.2`, `2.5`, `2.11` or `2.12`)"));
+        assert!(!err.contains("Elasticsearch"));
+
+        // an OpenSearch version is no good for Elasticsearch
+        let err = Context::new_from_str("search_engine: { engine: elasticsearch, version: 2.12 }")
+            .expect_err("test")
+            .to_string();
+        assert!(err.contains("expected an Elasticsearch version (`6.8` or `7.6`)"));
+        assert!(!err.contains("OpenSearch"));
+
+        // `es_version` is still checked when it's used for the default
+        let ctx =
+            Context::new_from_str("{ es_version: 6.8, search_engine: { engine: elasticsearch } }")
+                .expect("test");
+        assert!(SearchEngine::from_ctx(&ctx).validate(&ctx).is_ok());
+    }
+}